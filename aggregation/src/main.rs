@@ -0,0 +1,29 @@
+//! Verifies compressed proofs of the Merkle program with SP1 recursion and commits their public
+//! values together, so that a single Groth16 or PLONK proof covers every claim.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use goldinals_lib::recursion::AggregatedValues;
+use sha2::{Digest, Sha256};
+
+pub fn main() {
+    let vkey_digest: [u32; 8] = sp1_zkvm::io::read();
+    let public_values: Vec<Vec<u8>> = sp1_zkvm::io::read();
+
+    // Each call consumes the next proof the host wrote with `SP1Stdin::write_proof`, and the proof
+    // of this program only verifies if every one of them is a valid proof of `vkey_digest`
+    // committing these public values.
+    for values in &public_values {
+        let digest: [u8; 32] = Sha256::digest(values).into();
+        sp1_zkvm::lib::verify::verify_sp1_proof(&vkey_digest, &digest);
+    }
+
+    sp1_zkvm::io::commit_slice(
+        &AggregatedValues {
+            vkey_digest,
+            public_values,
+        }
+        .to_bytes(),
+    );
+}
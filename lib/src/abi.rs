@@ -0,0 +1,62 @@
+//! The committed public values as an ABI-encoded struct, for contracts that decode them with
+//! `abi.decode(publicValues, (PublicValuesStruct))` rather than slicing bytes.
+//!
+//! The struct splits the regular layout of [`crate::public_values`] into its core fields and the
+//! packed extension records:
+//!
+//! ```solidity
+//! struct PublicValuesStruct {
+//!     bytes32 root;
+//!     bytes32 leaf;
+//!     bool isValid;
+//!     uint64 leafIndex;
+//!     uint64 totalLeaves;
+//!     bytes extensions;
+//! }
+//! ```
+//!
+//! so `abi.encodePacked(root, leaf, isValid, leafIndex, totalLeaves, extensions)` rebuilds the
+//! regular bytes and the records keep their usual tags.
+
+use alloc::vec::Vec;
+
+use alloy_sol_types::{sol, SolValue};
+
+use crate::public_values::CORE_LEN;
+
+sol! {
+    /// The committed public values split into ABI fields.
+    struct PublicValuesStruct {
+        bytes32 root;
+        bytes32 leaf;
+        bool isValid;
+        uint64 leafIndex;
+        uint64 totalLeaves;
+        bytes extensions;
+    }
+}
+
+/// ABI-encodes regular public values, starting with the full core, as a [`PublicValuesStruct`].
+pub fn encode(bytes: &[u8]) -> Vec<u8> {
+    PublicValuesStruct {
+        root: <[u8; 32]>::try_from(&bytes[0..32]).unwrap().into(),
+        leaf: <[u8; 32]>::try_from(&bytes[32..64]).unwrap().into(),
+        isValid: bytes[64] != 0,
+        leafIndex: u64::from_be_bytes(bytes[65..73].try_into().unwrap()),
+        totalLeaves: u64::from_be_bytes(bytes[73..81].try_into().unwrap()),
+        extensions: bytes[CORE_LEN..].to_vec().into(),
+    }
+    .abi_encode()
+}
+
+/// Decodes an ABI-encoded [`PublicValuesStruct`] back into the regular bytes it was encoded from.
+pub fn decode(bytes: &[u8]) -> Option<Vec<u8>> {
+    let values = PublicValuesStruct::abi_decode(bytes, true).ok()?;
+    let mut out = values.root.to_vec();
+    out.extend_from_slice(values.leaf.as_slice());
+    out.push(values.isValid as u8);
+    out.extend_from_slice(&values.leafIndex.to_be_bytes());
+    out.extend_from_slice(&values.totalLeaves.to_be_bytes());
+    out.extend_from_slice(&values.extensions);
+    Some(out)
+}
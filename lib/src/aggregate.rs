@@ -0,0 +1,44 @@
+//! Verifying many independent inclusion proofs against one root in a single SP1 proof.
+//!
+//! Each entry carries its own proof in the tree's scheme, so unlike a [`crate::batch`] multi-proof
+//! the entries need not be sorted or distinct, and one bad proof does not hide the others. The guest
+//! commits how many entries verified and the leaf index of every one that did not, together with a
+//! digest binding the committed counts to the exact entries checked:
+//!
+//! ```text
+//! entries_hash = SHA-256(index_0 (8, big-endian) || leaf_0 || index_1 || leaf_1 || ...)
+//! ```
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// A leaf, its position and its inclusion proof.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateEntry {
+    pub index: u64,
+    pub leaf: Hash,
+    pub proof_bytes: Vec<u8>,
+}
+
+/// The entries of an aggregated batch, passed to the guest in [`crate::Options::aggregate`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateProofs {
+    pub entries: Vec<AggregateEntry>,
+    /// Panic at the first entry that does not verify instead of committing it as failed.
+    pub require_all: bool,
+}
+
+impl AggregateProofs {
+    /// Returns the digest of the entries' indices and leaves, in order.
+    pub fn entries_hash(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        for entry in &self.entries {
+            hasher.update(entry.index.to_be_bytes());
+            hasher.update(entry.leaf);
+        }
+        hasher.finalize()
+    }
+}
@@ -0,0 +1,94 @@
+//! Appending leaves to a tree, proving the transition from the old root to the new one.
+//!
+//! A tree of `n` leaves shaped as in [`crate::tree`] is determined by its frontier: the roots of the
+//! perfect subtrees given by the binary decomposition of `n`, largest and leftmost first. Unpaired
+//! nodes are promoted, so the root folds the frontier from the right:
+//!
+//! ```text
+//! root = parent(peak_0, parent(peak_1, ... parent(peak_{k-2}, peak_{k-1})))
+//! ```
+//!
+//! Appending a leaf pushes it as a peak of height 0 and merges the two rightmost peaks while their
+//! heights are equal, as in a binary counter. The frontier of the old tree, checked against the old
+//! root, is therefore the only path the guest needs to recompute the root after the appended leaves.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{public_values::AppendCommitment, Hash};
+
+/// The frontier of the proven tree and the leaves appended to it, passed to the guest in
+/// [`crate::Options::append`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Append {
+    pub frontier: Vec<Hash>,
+    pub leaves: Vec<Hash>,
+}
+
+/// Pushes the leaf at position `total_leaves` onto the frontier of the first `total_leaves` leaves.
+fn push(
+    frontier: &mut Vec<Hash>,
+    total_leaves: usize,
+    leaf: Hash,
+    parent: impl Fn(&Hash, &Hash) -> Hash,
+) {
+    let mut node = leaf;
+    // Every trailing one bit of the old size is a peak of the height `node` just reached.
+    for _ in 0..total_leaves.trailing_ones() {
+        node = parent(&frontier.pop().expect("one peak per set bit"), &node);
+    }
+    frontier.push(node);
+}
+
+/// Returns the frontier of a tree over `leaves`.
+pub fn frontier(leaves: &[Hash], parent: impl Fn(&Hash, &Hash) -> Hash) -> Vec<Hash> {
+    let mut frontier = Vec::new();
+    for (total_leaves, &leaf) in leaves.iter().enumerate() {
+        push(&mut frontier, total_leaves, leaf, &parent);
+    }
+    frontier
+}
+
+/// Folds a frontier into its root, or returns `None` for the empty frontier.
+pub fn root(frontier: &[Hash], parent: impl Fn(&Hash, &Hash) -> Hash) -> Option<Hash> {
+    let (&last, rest) = frontier.split_last()?;
+    Some(
+        rest.iter()
+            .rev()
+            .fold(last, |right, left| parent(left, &right)),
+    )
+}
+
+impl Append {
+    /// Appends the leaves to the tree of `total_leaves` leaves under `root`.
+    ///
+    /// Unless the frontier has one peak per set bit of `total_leaves` and folds into `root`, nothing
+    /// is appended and `new_root` is `root`.
+    pub fn apply(
+        &self,
+        root: Hash,
+        total_leaves: usize,
+        parent: impl Fn(&Hash, &Hash) -> Hash,
+    ) -> AppendCommitment {
+        let valid = self.frontier.len() == total_leaves.count_ones() as usize
+            && self::root(&self.frontier, &parent) == Some(root);
+        if !valid {
+            return AppendCommitment {
+                valid,
+                count: 0,
+                new_root: root,
+            };
+        }
+
+        let mut frontier = self.frontier.clone();
+        for (offset, &leaf) in self.leaves.iter().enumerate() {
+            push(&mut frontier, total_leaves + offset, leaf, &parent);
+        }
+        AppendCommitment {
+            valid,
+            count: self.leaves.len() as u32,
+            new_root: self::root(&frontier, &parent).expect("the tree is not empty"),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! secp256k1 ECDSA authorization of the proven leaf.
+//!
+//! An authorized key signs the leaf as an Ethereum personal message, so ordinary wallets can
+//! produce the signature:
+//!
+//! ```text
+//! digest = keccak256("\x19Ethereum Signed Message:\n32" || leaf)
+//! ```
+//!
+//! The signature is the 64-byte `r || s` encoding with a low `s`, and the signer is identified by
+//! its Ethereum address, `keccak256(uncompressed public key without the 0x04 prefix)[12..32]`.
+
+use alloc::vec::Vec;
+
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::Hash;
+
+/// An Ethereum address.
+pub type Address = [u8; 20];
+
+/// A signature over the leaf and the key that produced it, passed to the guest in
+/// [`crate::Options::authorization`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Authorization {
+    /// The SEC1-encoded public key, compressed or uncompressed.
+    pub public_key: Vec<u8>,
+    /// The `r || s` signature over [`message_hash`] of the leaf.
+    pub signature: Vec<u8>,
+}
+
+/// Returns the digest an authorized key signs for `leaf`.
+pub fn message_hash(leaf: &Hash) -> Hash {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"\x19Ethereum Signed Message:\n32");
+    hasher.update(leaf);
+    hasher.finalize().into()
+}
+
+/// Returns the Ethereum address of a public key.
+pub fn address(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    hash[12..].try_into().unwrap()
+}
+
+impl Authorization {
+    /// Returns the signer's address, or `None` if the public key is malformed, and whether the
+    /// signature over `leaf` is valid for that key.
+    pub fn verify(&self, leaf: &Hash) -> (Option<Address>, bool) {
+        let Ok(key) = VerifyingKey::from_sec1_bytes(&self.public_key) else {
+            return (None, false);
+        };
+        let is_valid = Signature::from_slice(&self.signature)
+            .is_ok_and(|signature| key.verify_prehash(&message_hash(leaf), &signature).is_ok());
+        (Some(address(&key)), is_valid)
+    }
+}
@@ -0,0 +1,46 @@
+//! Verifying several leaves of a sha256 tree with one `rs_merkle` multi-proof.
+//!
+//! A multi-proof carries each sibling shared by the leaves' paths once, so it is smaller than one
+//! proof per leaf. `rs_merkle` builds and checks it for a set of indices in ascending order without
+//! duplicates: although verification sorts the leaves by index, a proof built for unsorted or
+//! repeated indices lists its siblings in a different order and silently fails to verify. Batches
+//! are therefore only accepted in that canonical order.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Hash;
+
+/// Leaves at distinct positions of a sha256 tree and their multi-proof, passed to the guest in
+/// [`crate::Options::batch`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeafBatch {
+    /// The leaves' positions, strictly ascending.
+    pub indices: Vec<u64>,
+    pub leaves: Vec<Hash>,
+    /// The `rs_merkle` multi-proof for `indices`.
+    pub proof_bytes: Vec<u8>,
+}
+
+impl LeafBatch {
+    /// Returns whether the batch is non-empty, has one leaf per index and lists its indices
+    /// strictly ascending and below `total_leaves`.
+    pub fn is_canonical(&self, total_leaves: usize) -> bool {
+        !self.indices.is_empty()
+            && self.indices.len() == self.leaves.len()
+            && self.indices.windows(2).all(|pair| pair[0] < pair[1])
+            && self
+                .indices
+                .last()
+                .is_some_and(|&last| last < total_leaves as u64)
+    }
+
+    /// Returns whether the batch holds `leaf` at `index`.
+    pub fn contains(&self, index: usize, leaf: &Hash) -> bool {
+        self.indices
+            .iter()
+            .zip(&self.leaves)
+            .any(|(&i, l)| i == index as u64 && l == leaf)
+    }
+}
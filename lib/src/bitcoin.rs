@@ -0,0 +1,498 @@
+//! Bitcoin SPV proofs that a transaction is included in a block.
+//!
+//! A block's transactions are committed by the Merkle root in its 80-byte header, over the txids:
+//!
+//! ```text
+//! parent(l, r) = SHA-256(SHA-256(l || r))
+//! ```
+//!
+//! A level of odd width pairs its last node with itself, so unlike [`crate::tree`] no node is
+//! promoted and every leaf's branch has one sibling per level. Hashes are in Bitcoin's internal byte
+//! order, the order they are hashed and stored in the header, which is the reverse of the order in
+//! which txids and block hashes are usually displayed.
+//!
+//! Duplicating the last node lets two different transaction lists share a root (CVE-2012-2459), so
+//! a branch is only accepted if each sibling equals the node exactly when the level has no real
+//! sibling for it. Branches are extracted on the host from a `merkleblock` message as returned by
+//! `bitcoin-cli gettxoutproof`, which carries the header and a BIP 37 partial Merkle tree.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, tree, Hash};
+
+/// The length of a block header, in bytes.
+pub const HEADER_LEN: usize = 80;
+
+/// Returns `SHA-256(SHA-256(data))`.
+pub fn double_sha256(data: &[u8]) -> Hash {
+    Sha256::digest(Sha256::digest(data))
+}
+
+/// Hashes two child nodes into their parent.
+pub fn parent(left: &Hash, right: &Hash) -> Hash {
+    double_sha256(&[left.as_slice(), right.as_slice()].concat())
+}
+
+/// Returns the Merkle root committed by a block header, or `None` if it is not [`HEADER_LEN`]
+/// bytes.
+pub fn merkle_root(header: &[u8]) -> Option<Hash> {
+    if header.len() != HEADER_LEN {
+        return None;
+    }
+    header[36..68].try_into().ok()
+}
+
+/// Returns the hash of a block header, in internal byte order.
+pub fn block_hash(header: &[u8]) -> Hash {
+    double_sha256(header)
+}
+
+/// The branch of one transaction, passed to the guest after the header and its txid.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpvProof {
+    /// The transaction's position in the block.
+    pub index: u32,
+    pub total_transactions: u32,
+    /// One sibling per level, from the txids up.
+    pub branch: Vec<Hash>,
+}
+
+impl SpvProof {
+    /// Returns the root of the block in which `txid` is at [`SpvProof::index`], or `None` if the
+    /// branch does not fit the block or duplicates a node it should not.
+    pub fn root(&self, txid: &Hash) -> Option<Hash> {
+        let total = self.total_transactions as usize;
+        let mut position = self.index as usize;
+        if position >= total || self.branch.len() != tree::height(total) {
+            return None;
+        }
+        let mut node = *txid;
+        let mut width = total;
+        for sibling in &self.branch {
+            // Only the last node of an odd level is paired with itself.
+            let duplicated = position ^ 1 >= width;
+            if duplicated != (*sibling == node) {
+                return None;
+            }
+            node = match position % 2 {
+                0 => parent(&node, sibling),
+                _ => parent(sibling, &node),
+            };
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+        Some(node)
+    }
+}
+
+/// Verifies that `txid` is in the block with the given header.
+pub fn verify(header: &[u8], txid: &Hash, proof: &SpvProof) -> bool {
+    merkle_root(header).is_some_and(|root| proof.root(txid) == Some(root))
+}
+
+/// Why a `merkleblock` message could not be read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MerkleBlockError {
+    /// The message ended early or has bytes after the partial tree.
+    Truncated,
+    /// The block has no transactions or the partial tree has more hashes than it.
+    InvalidCount,
+    /// The partial tree's flags and hashes do not describe a tree of the block's size.
+    InvalidTree,
+    /// The partial tree pairs a node with an identical sibling, see the module docs.
+    DuplicateNode,
+}
+
+impl fmt::Display for MerkleBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "merkleblock is truncated or has trailing bytes"),
+            Self::InvalidCount => write!(f, "merkleblock has an invalid transaction count"),
+            Self::InvalidTree => write!(f, "merkleblock has an invalid partial merkle tree"),
+            Self::DuplicateNode => write!(f, "merkleblock duplicates a node of its merkle tree"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleBlockError {}
+
+/// A parsed `merkleblock` message: a block header and a partial Merkle tree over its transactions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleBlock {
+    pub header: Vec<u8>,
+    pub total_transactions: u32,
+    hashes: Vec<Hash>,
+    flags: Vec<u8>,
+}
+
+impl MerkleBlock {
+    /// Parses a serialized `merkleblock` message.
+    pub fn parse(bytes: &[u8]) -> Result<Self, MerkleBlockError> {
+        let mut reader = Reader(bytes);
+        let header = reader.take(HEADER_LEN)?.to_vec();
+        let total_transactions = u32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+        let hash_count = reader.compact_size()?;
+        let hashes = (0..hash_count)
+            .map(|_| Ok(reader.take(32)?.try_into().unwrap()))
+            .collect::<Result<Vec<Hash>, _>>()?;
+        let flag_count = reader.compact_size()?;
+        let flags = reader.take(flag_count)?.to_vec();
+        if !reader.0.is_empty() {
+            return Err(MerkleBlockError::Truncated);
+        }
+        if total_transactions == 0 || hashes.len() > total_transactions as usize {
+            return Err(MerkleBlockError::InvalidCount);
+        }
+        Ok(Self {
+            header,
+            total_transactions,
+            hashes,
+            flags,
+        })
+    }
+
+    /// Walks the partial tree and returns the branch of every transaction it matches, in block
+    /// order, with its txid.
+    ///
+    /// The root the tree walks to is not checked against the header; [`verify`] does that.
+    pub fn branches(&self) -> Result<Vec<(Hash, SpvProof)>, MerkleBlockError> {
+        let total = self.total_transactions as usize;
+        let height = tree::height(total);
+        let mut walk = Walk {
+            block: self,
+            bits_used: 0,
+            hashes_used: 0,
+            nodes: BTreeMap::new(),
+            matches: Vec::new(),
+        };
+        walk.node(height, 0)?;
+        // Every hash must be used, and the flags only padded to a whole byte.
+        if walk.hashes_used != self.hashes.len() || walk.bits_used.div_ceil(8) != self.flags.len() {
+            return Err(MerkleBlockError::InvalidTree);
+        }
+
+        Ok(walk
+            .matches
+            .iter()
+            .map(|&(index, txid)| {
+                let branch = (0..height)
+                    .map(|level| {
+                        let position = index >> level;
+                        let sibling = match position ^ 1 {
+                            sibling if sibling < width(total, level) => sibling,
+                            _ => position,
+                        };
+                        walk.nodes[&(level, sibling)]
+                    })
+                    .collect();
+                let proof = SpvProof {
+                    index: index as u32,
+                    total_transactions: self.total_transactions,
+                    branch,
+                };
+                (txid, proof)
+            })
+            .collect())
+    }
+}
+
+/// The unread bytes of a message.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MerkleBlockError> {
+        if self.0.len() < len {
+            return Err(MerkleBlockError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    /// Reads a Bitcoin `CompactSize` integer.
+    fn compact_size(&mut self) -> Result<usize, MerkleBlockError> {
+        let value = match self.take(1)?[0] {
+            0xfd => u16::from_le_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            0xfe => u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            0xff => u64::from_le_bytes(self.take(8)?.try_into().unwrap()),
+            byte => byte as u64,
+        };
+        // Larger counts cannot fit in the message, so they are truncated either way.
+        usize::try_from(value).map_err(|_| MerkleBlockError::Truncated)
+    }
+}
+
+/// Returns the number of nodes `level` levels above the txids.
+fn width(total_transactions: usize, level: usize) -> usize {
+    total_transactions.div_ceil(1 << level)
+}
+
+/// The state of a depth-first walk of a partial tree, as in BIP 37.
+struct Walk<'a> {
+    block: &'a MerkleBlock,
+    bits_used: usize,
+    hashes_used: usize,
+    /// Every node the walk computed or read, by level and position.
+    nodes: BTreeMap<(usize, usize), Hash>,
+    /// The position and txid of every matched transaction.
+    matches: Vec<(usize, Hash)>,
+}
+
+impl Walk<'_> {
+    /// Returns the node at `position` of `level`, reading its subtree from the partial tree.
+    fn node(&mut self, level: usize, position: usize) -> Result<Hash, MerkleBlockError> {
+        let flag = *self
+            .block
+            .flags
+            .get(self.bits_used / 8)
+            .ok_or(MerkleBlockError::InvalidTree)?
+            >> (self.bits_used % 8)
+            & 1
+            == 1;
+        self.bits_used += 1;
+
+        let node = if level == 0 || !flag {
+            let node = *self
+                .block
+                .hashes
+                .get(self.hashes_used)
+                .ok_or(MerkleBlockError::InvalidTree)?;
+            self.hashes_used += 1;
+            if level == 0 && flag {
+                self.matches.push((position, node));
+            }
+            node
+        } else {
+            let left = self.node(level - 1, position * 2)?;
+            let right =
+                if position * 2 + 1 < width(self.block.total_transactions as usize, level - 1) {
+                    let right = self.node(level - 1, position * 2 + 1)?;
+                    if right == left {
+                        return Err(MerkleBlockError::DuplicateNode);
+                    }
+                    right
+                } else {
+                    left
+                };
+            parent(&left, &right)
+        };
+        self.nodes.insert((level, position), node);
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    /// Decodes a hash displayed in the usual, reversed, byte order.
+    fn displayed(hex: &str) -> Hash {
+        let mut hash: Hash = hex::decode(hex).unwrap().try_into().unwrap();
+        hash.reverse();
+        hash
+    }
+
+    /// The header of block 170, whose second transaction is the first that is not a coinbase.
+    fn block_170() -> Vec<u8> {
+        [
+            1u32.to_le_bytes().as_slice(),
+            &displayed("000000002a22cfee1f2c846adbd12b3e183d4f97683f85dad08a79780a84bd55"),
+            &displayed("7dac2c5666815c17a3b36427de37bb9d2e2c5ccec3f8633eb91a4205cb4c10ff"),
+            &1231731025u32.to_le_bytes(),
+            &0x1d00ffffu32.to_le_bytes(),
+            &1889418792u32.to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    const COINBASE_170: &str = "b1fea52486ce0c62bb442b530a3f0132b826c74e473d1f2c220bfa78111c5082";
+    const SPEND_170: &str = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+
+    #[test]
+    fn genesis_block_commits_its_coinbase() {
+        let header = hex::decode(
+            "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b2\
+             7ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        )
+        .unwrap();
+        assert_eq!(
+            block_hash(&header),
+            displayed("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+        );
+        let coinbase =
+            displayed("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b");
+        let proof = SpvProof {
+            index: 0,
+            total_transactions: 1,
+            branch: vec![],
+        };
+        assert!(verify(&header, &coinbase, &proof));
+        assert!(!verify(&header[..79], &coinbase, &proof));
+    }
+
+    #[test]
+    fn block_170_commits_both_transactions() {
+        let header = block_170();
+        assert_eq!(
+            block_hash(&header),
+            displayed("00000000d1145790a8694403d4063f323d499e655c83426834d4ce2f8dd4a2ee")
+        );
+        let (coinbase, spend) = (displayed(COINBASE_170), displayed(SPEND_170));
+        assert_eq!(merkle_root(&header), Some(parent(&coinbase, &spend)));
+        let proof = |index, sibling| SpvProof {
+            index,
+            total_transactions: 2,
+            branch: vec![sibling],
+        };
+        assert!(verify(&header, &coinbase, &proof(0, spend)));
+        assert!(verify(&header, &spend, &proof(1, coinbase)));
+        // The wrong position, or a transaction paired with itself.
+        assert!(!verify(&header, &spend, &proof(0, coinbase)));
+        assert!(!verify(&header, &spend, &proof(1, spend)));
+    }
+
+    #[test]
+    fn duplicated_nodes_are_only_accepted_at_the_end_of_a_level() {
+        // Three transactions: the third is paired with itself, the others are not.
+        let txids = [[1; 32], [2; 32], [3; 32]];
+        let left = parent(&txids[0], &txids[1]);
+        let right = parent(&txids[2], &txids[2]);
+        let root = parent(&left, &right);
+        let proof = SpvProof {
+            index: 2,
+            total_transactions: 3,
+            branch: vec![txids[2], left],
+        };
+        assert_eq!(proof.root(&txids[2]), Some(root));
+        // CVE-2012-2459: the same root claimed for a fourth transaction duplicating the third.
+        let forged = SpvProof {
+            index: 3,
+            total_transactions: 4,
+            branch: vec![txids[2], left],
+        };
+        assert_eq!(forged.root(&txids[2]), None);
+        // A branch of the wrong height, or a position outside the block.
+        assert_eq!(
+            SpvProof {
+                branch: vec![txids[2]],
+                ..proof.clone()
+            }
+            .root(&txids[2]),
+            None
+        );
+        assert_eq!(SpvProof { index: 3, ..proof }.root(&txids[2]), None);
+    }
+
+    #[test]
+    fn merkleblock_of_block_170_yields_its_branch() {
+        let header = block_170();
+        let (coinbase, spend) = (displayed(COINBASE_170), displayed(SPEND_170));
+        // The root and the spend are matched, and the coinbase is given as a hash: flags 1, 0, 1.
+        let message = [
+            header.as_slice(),
+            &2u32.to_le_bytes(),
+            &[2],
+            &coinbase,
+            &spend,
+            &[1, 0x05],
+        ]
+        .concat();
+        let block = MerkleBlock::parse(&message).unwrap();
+        assert_eq!(block.header, header);
+        let branches = block.branches().unwrap();
+        assert_eq!(branches.len(), 1);
+        let (txid, proof) = &branches[0];
+        assert_eq!(*txid, spend);
+        assert_eq!(proof.branch, vec![coinbase]);
+        assert!(verify(&header, txid, proof));
+
+        // Matching the coinbase as well: flags 1, 1, 1.
+        let both = [&message[..message.len() - 1], &[0x07]].concat();
+        let branches = MerkleBlock::parse(&both).unwrap().branches().unwrap();
+        assert_eq!(branches.len(), 2);
+        assert!(branches
+            .iter()
+            .all(|(txid, proof)| verify(&header, txid, proof)));
+    }
+
+    #[test]
+    fn merkleblock_from_the_developer_reference_verifies() {
+        // The example `merkleblock` of the Bitcoin developer reference: 7 transactions, 4 hashes
+        // and flags 0x1d, matching one transaction.
+        let message = hex::decode(
+            "0100000082bb869cf3a793432a66e826e05a6fc37469f8efb7421dc880670100000000007f16c5962e8bd963\
+             659c793ce370d95f093bc7e367117b3c30c1f8fdd0d9728776381b4d4c86041b554b852907000000043612262\
+             624047ee87660be1a707519a443b1c1ce3d248cbfc6c15870f6c5daa2019f5b01d4195ecbc9398fbf3c3b1fa9\
+             bb3183301d7a1fb3bd174fcfa40a2b6541ed70551dd7e841883ab8f0b16bf04176b7d1480e4f0af9f3d4c3595\
+             768d06820d2a7bc994987302e5b1ac80fc425fe25f8b63169ea78e68fbaaefa59379bbf011d",
+        )
+        .unwrap();
+        let block = MerkleBlock::parse(&message).unwrap();
+        assert_eq!(block.total_transactions, 7);
+        let branches = block.branches().unwrap();
+        assert_eq!(branches.len(), 1);
+        let (txid, proof) = &branches[0];
+        assert_eq!(proof.branch.len(), 3);
+        assert!(verify(&block.header, txid, proof));
+    }
+
+    #[test]
+    fn malformed_merkleblocks_are_rejected() {
+        let header = block_170();
+        let (coinbase, spend) = (displayed(COINBASE_170), displayed(SPEND_170));
+        let message = |count: u32, hashes: &[Hash], flags: &[u8]| {
+            [
+                header.as_slice(),
+                &count.to_le_bytes(),
+                &[hashes.len() as u8],
+                &hashes.concat(),
+                &[flags.len() as u8],
+                flags,
+            ]
+            .concat()
+        };
+        let valid = message(2, &[coinbase, spend], &[0x05]);
+        assert!(MerkleBlock::parse(&valid).is_ok());
+
+        let parse = |bytes: &[u8]| MerkleBlock::parse(bytes).and_then(|block| block.branches());
+        assert_eq!(
+            parse(&valid[..valid.len() - 1]),
+            Err(MerkleBlockError::Truncated)
+        );
+        assert_eq!(
+            parse(&[valid.as_slice(), &[0]].concat()),
+            Err(MerkleBlockError::Truncated)
+        );
+        assert_eq!(
+            parse(&message(0, &[], &[])),
+            Err(MerkleBlockError::InvalidCount)
+        );
+        assert_eq!(
+            parse(&message(1, &[coinbase, spend], &[0x05])),
+            Err(MerkleBlockError::InvalidCount)
+        );
+        // An unused hash, missing flags, or a padding byte of flags too many.
+        assert_eq!(
+            parse(&message(2, &[coinbase, spend], &[0x00])),
+            Err(MerkleBlockError::InvalidTree)
+        );
+        assert_eq!(
+            parse(&message(2, &[coinbase, spend], &[])),
+            Err(MerkleBlockError::InvalidTree)
+        );
+        assert_eq!(
+            parse(&message(2, &[coinbase, spend], &[0x05, 0x00])),
+            Err(MerkleBlockError::InvalidTree)
+        );
+        assert_eq!(
+            parse(&message(2, &[spend, spend], &[0x05])),
+            Err(MerkleBlockError::DuplicateNode)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! BLAKE2b-256 trees under a personalization string.
+//!
+//! Every node, leaves included, is a 32-byte BLAKE2b digest whose parameter block carries the
+//! tree's personalization:
+//!
+//! ```text
+//! leaf         = blake2b-256(data; personal = p)
+//! parent(l, r) = blake2b-256(l || r; personal = p)
+//! ```
+//!
+//! Personalization separates domains inside the hash function itself: two trees with different
+//! personalizations share no node hashes, even over the same leaf data. BLAKE2b accepts at most
+//! [`MAX_PERSONALIZATION_LEN`] bytes, zero-padded by the hash function, so a personalization and
+//! its zero-extensions agree; the empty personalization is plain BLAKE2b-256. The tree shape
+//! matches `rs_merkle` (see [`crate::tree`]) and proofs are plain sibling paths.
+
+use alloc::vec::Vec;
+
+use blake2b_simd::Params;
+
+use crate::{tree, Hash};
+
+/// The longest personalization BLAKE2b accepts, in bytes.
+pub const MAX_PERSONALIZATION_LEN: usize = 16;
+
+/// Returns whether BLAKE2b accepts `personalization`.
+pub fn is_valid_personalization(personalization: &[u8]) -> bool {
+    personalization.len() <= MAX_PERSONALIZATION_LEN
+}
+
+/// Returns the BLAKE2b-256 digest of `data` under `personalization`, which must be valid.
+pub fn hash(data: &[u8], personalization: &[u8]) -> Hash {
+    Params::new()
+        .hash_length(32)
+        .personal(personalization)
+        .hash(data)
+        .as_bytes()
+        .try_into()
+        .unwrap()
+}
+
+/// Hashes two child nodes into their parent under `personalization`.
+pub fn parent(left: &Hash, right: &Hash, personalization: &[u8]) -> Hash {
+    Params::new()
+        .hash_length(32)
+        .personal(personalization)
+        .to_state()
+        .update(left)
+        .update(right)
+        .finalize()
+        .as_bytes()
+        .try_into()
+        .unwrap()
+}
+
+/// A personalized BLAKE2b tree built on the host, keeping every level for proof generation.
+pub struct Blake2bTree {
+    levels: Vec<Vec<Hash>>,
+}
+
+impl Blake2bTree {
+    /// Builds a tree over leaves that are already hashed with [`hash`].
+    pub fn from_leaves(leaves: &[Hash], personalization: &[u8]) -> Self {
+        assert!(
+            is_valid_personalization(personalization),
+            "personalization must be at most 16 bytes"
+        );
+        Self {
+            levels: tree::build_levels(leaves.to_vec(), |l, r| parent(l, r, personalization)),
+        }
+    }
+
+    pub fn root(&self) -> Option<Hash> {
+        self.levels.last()?.first().copied()
+    }
+
+    pub fn proof_bytes(&self, index: usize) -> Vec<u8> {
+        tree::proof_path(&self.levels, index).concat()
+    }
+}
+
+/// Verifies a proof produced by [`Blake2bTree::proof_bytes`].
+pub fn verify(
+    root: Hash,
+    leaf: Hash,
+    proof_bytes: &[u8],
+    index: usize,
+    total_leaves: usize,
+    personalization: &[u8],
+) -> bool {
+    if !is_valid_personalization(personalization) || proof_bytes.len() % 32 != 0 {
+        return false;
+    }
+    let path: Vec<Hash> = proof_bytes
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+
+    tree::root_from_path(leaf, index, total_leaves, &path, |l, r| {
+        parent(l, r, personalization)
+    }) == Some(root)
+}
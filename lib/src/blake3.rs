@@ -0,0 +1,70 @@
+//! BLAKE3 trees.
+//!
+//! Every node, leaves included, is a 32-byte BLAKE3 digest:
+//!
+//! ```text
+//! leaf         = blake3(data)
+//! parent(l, r) = blake3(l || r)
+//! ```
+//!
+//! BLAKE3 is only the node hash here: its own internal tree mode is not used, so the tree shape
+//! matches `rs_merkle` (see [`crate::tree`]) and proofs are plain sibling paths.
+
+use alloc::vec::Vec;
+
+use crate::{tree, Hash};
+
+/// Returns the BLAKE3 digest of `data`.
+pub fn hash(data: &[u8]) -> Hash {
+    ::blake3::hash(data).into()
+}
+
+/// Hashes two child nodes into their parent.
+pub fn parent(left: &Hash, right: &Hash) -> Hash {
+    ::blake3::Hasher::new()
+        .update(left)
+        .update(right)
+        .finalize()
+        .into()
+}
+
+/// A BLAKE3 tree built on the host, keeping every level for proof generation.
+pub struct Blake3Tree {
+    levels: Vec<Vec<Hash>>,
+}
+
+impl Blake3Tree {
+    /// Builds a tree over leaves that are already hashed with [`hash`].
+    pub fn from_leaves(leaves: &[Hash]) -> Self {
+        Self {
+            levels: tree::build_levels(leaves.to_vec(), parent),
+        }
+    }
+
+    pub fn root(&self) -> Option<Hash> {
+        self.levels.last()?.first().copied()
+    }
+
+    pub fn proof_bytes(&self, index: usize) -> Vec<u8> {
+        tree::proof_path(&self.levels, index).concat()
+    }
+}
+
+/// Verifies a proof produced by [`Blake3Tree::proof_bytes`].
+pub fn verify(
+    root: Hash,
+    leaf: Hash,
+    proof_bytes: &[u8],
+    index: usize,
+    total_leaves: usize,
+) -> bool {
+    if proof_bytes.len() % 32 != 0 {
+        return false;
+    }
+    let path: Vec<Hash> = proof_bytes
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+
+    tree::root_from_path(leaf, index, total_leaves, &path, parent) == Some(root)
+}
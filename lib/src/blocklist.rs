@@ -0,0 +1,74 @@
+//! Proving that the proven leaf is absent from a separate blocklist tree.
+//!
+//! A blocklist is a standard sha256 tree whose leaves are the blocked leaf hashes, unhashed, sorted
+//! in ascending byte order and distinct. A leaf is absent when it falls strictly between two
+//! adjacent blocklist leaves, each proven by inclusion, or beyond the first or last one:
+//!
+//! ```text
+//! blocklist[position - 1] < leaf < blocklist[position]
+//! ```
+//!
+//! `position` is the number of blocklist leaves below `leaf`. The lower bound is omitted when it is
+//! 0 and the upper bound when it equals the number of leaves. An empty blocklist has the zero root
+//! and excludes every leaf.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sorted::Neighbor, tree, Hash};
+
+/// The blocklist leaves around the proven leaf, passed to the guest in
+/// [`crate::Options::blocklist`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlocklistExclusion {
+    pub root: Hash,
+    /// The number of blocklist leaves.
+    pub total_leaves: u64,
+    /// The number of blocklist leaves below the proven leaf.
+    pub position: u64,
+    /// The blocklist leaf at `position - 1`, absent when `position` is 0.
+    pub lower: Option<Neighbor>,
+    /// The blocklist leaf at `position`, absent when `position` is `total_leaves`.
+    pub upper: Option<Neighbor>,
+}
+
+impl BlocklistExclusion {
+    /// Returns whether the bounds prove that `leaf` is not in the blocklist.
+    pub fn excludes(&self, leaf: &Hash) -> bool {
+        if self.total_leaves == 0 {
+            return self.root == Hash::default() && self.lower.is_none() && self.upper.is_none();
+        }
+        let lower_ok = match (&self.lower, self.position.checked_sub(1)) {
+            (Some(lower), Some(index)) => lower.leaf < *leaf && self.contains(lower, index),
+            (None, None) => true,
+            _ => false,
+        };
+        let upper_ok = match &self.upper {
+            Some(upper) if self.position < self.total_leaves => {
+                *leaf < upper.leaf && self.contains(upper, self.position)
+            }
+            None => self.position == self.total_leaves,
+            Some(_) => false,
+        };
+        lower_ok && upper_ok
+    }
+
+    /// Returns whether `neighbor` is leaf `index` of the blocklist.
+    fn contains(&self, neighbor: &Neighbor, index: u64) -> bool {
+        if neighbor.proof_bytes.len() % 32 != 0 {
+            return false;
+        }
+        let path: Vec<Hash> = neighbor
+            .proof_bytes
+            .chunks_exact(32)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+        let (Ok(index), Ok(total)) = (usize::try_from(index), usize::try_from(self.total_leaves))
+        else {
+            return false;
+        };
+        tree::root_from_path(neighbor.leaf, index, total, &path, tree::sha256_parent)
+            == Some(self.root)
+    }
+}
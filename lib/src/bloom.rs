@@ -0,0 +1,76 @@
+//! Bloom filters over the leaf set, as an advisory pre-check before Merkle verification.
+//!
+//! A filter of `m` bits sets, for every leaf, the `k` bits
+//!
+//! ```text
+//! h1, h2 = SHA-256(leaf)[0..8], SHA-256(leaf)[8..16], as big-endian u64
+//! bit_i  = ((h1 + i * h2) mod 2^64) mod m,  for i in 0..k
+//! ```
+//!
+//! with bit `j` stored in byte `j / 8` at position `j % 8`, least significant first. A leaf of the
+//! set always passes the check, and a leaf outside it passes with a probability that falls as `m`
+//! grows, so a failed check is a definitive miss while a pass is only a hint. The Merkle proof stays
+//! authoritative either way.
+//!
+//! The filter is host-supplied, so the guest commits its hash next to the result and verifiers must
+//! compare it against the filter they expect.
+
+use alloc::{vec, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// A bloom filter over the tree's leaves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    /// The number of bits, `m`.
+    pub len: u64,
+    /// The number of bits set per leaf, `k`.
+    pub hashes: u8,
+    /// The bits, `ceil(m / 8)` bytes.
+    pub bits: Vec<u8>,
+}
+
+impl BloomFilter {
+    /// Builds a filter of `len` bits with `hashes` bits set per leaf. `len` must be nonzero.
+    pub fn from_leaves(leaves: &[Hash], len: u64, hashes: u8) -> Self {
+        let mut filter = BloomFilter {
+            len,
+            hashes,
+            bits: vec![0; len.div_ceil(8) as usize],
+        };
+        for leaf in leaves {
+            for bit in filter.positions(leaf) {
+                filter.bits[(bit / 8) as usize] |= 1 << (bit % 8);
+            }
+        }
+        filter
+    }
+
+    /// Returns whether every bit for `leaf` is set. Malformed filters contain nothing.
+    pub fn contains(&self, leaf: &Hash) -> bool {
+        self.len > 0
+            && self.bits.len() as u64 == self.len.div_ceil(8)
+            && self
+                .positions(leaf)
+                .all(|bit| self.bits[(bit / 8) as usize] >> (bit % 8) & 1 == 1)
+    }
+
+    /// Returns `SHA-256(len (8, big-endian) || hashes (1) || bits)`.
+    pub fn hash(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update(self.len.to_be_bytes());
+        hasher.update([self.hashes]);
+        hasher.update(&self.bits);
+        hasher.finalize()
+    }
+
+    fn positions(&self, leaf: &Hash) -> impl Iterator<Item = u64> {
+        let digest = Sha256::digest(leaf);
+        let h1 = u64::from_be_bytes(digest[..8].try_into().unwrap());
+        let h2 = u64::from_be_bytes(digest[8..16].try_into().unwrap());
+        let len = self.len;
+        (0..self.hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % len)
+    }
+}
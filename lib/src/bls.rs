@@ -0,0 +1,342 @@
+//! BLS12-381 signatures over the proven leaf or root.
+//!
+//! Signatures follow the Ethereum consensus scheme (the IETF BLS signature draft in its
+//! minimal-public-key-size variant, with proof of possession): public keys are compressed G1 points
+//! of 48 bytes, signatures compressed G2 points of 96 bytes, and messages are hashed to G2 with
+//! `hash_to_curve` under the domain separation tag [`DST`]. A signature is valid when
+//!
+//! ```text
+//! e(public_key, H(message)) = e(g1, signature)
+//! ```
+//!
+//! Signatures by several validators over the same message aggregate by adding the signatures, and
+//! verify against the sum of the public keys. Summing keys is only sound for keys whose owners have
+//! proven possession of them, as in the Ethereum validator registry; otherwise a rogue key can forge
+//! an aggregate.
+
+use alloc::vec::Vec;
+
+use bls12_381::{
+    hash_to_curve::{ExpandMessageState, HashToCurve, InitExpandMessage},
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// The domain separation tag of Ethereum consensus signatures.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A compressed G1 public key.
+pub type PublicKey = [u8; 48];
+
+/// Which committed value a [`BlsSignature`] signs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SignedValue {
+    Leaf = 0,
+    Root = 1,
+}
+
+impl SignedValue {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Leaf),
+            1 => Some(Self::Root),
+            _ => None,
+        }
+    }
+}
+
+/// A possibly aggregate signature over the leaf or the root.
+///
+/// Both points are kept as vectors because serde only derives arrays of up to 32 bytes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlsSignature {
+    /// The compressed, possibly aggregate, public key.
+    pub public_key: Vec<u8>,
+    /// The compressed, possibly aggregate, signature.
+    pub signature: Vec<u8>,
+    pub signed: SignedValue,
+}
+
+impl BlsSignature {
+    /// Returns whether the signature over `message` is valid for the public key.
+    ///
+    /// Malformed points, points outside the prime-order subgroups and the identity public key make
+    /// the signature invalid rather than failing.
+    pub fn verify(&self, message: &[u8]) -> bool {
+        let public_key: Option<G1Affine> = <&[u8; 48]>::try_from(self.public_key.as_slice())
+            .ok()
+            .and_then(|bytes| G1Affine::from_compressed(bytes).into());
+        let signature: Option<G2Affine> = <&[u8; 96]>::try_from(self.signature.as_slice())
+            .ok()
+            .and_then(|bytes| G2Affine::from_compressed(bytes).into());
+        let (Some(public_key), Some(signature)) = (public_key, signature) else {
+            return false;
+        };
+        if bool::from(public_key.is_identity()) {
+            return false;
+        }
+
+        let hashed = G2Prepared::from(G2Affine::from(hash(message)));
+        let signature = G2Prepared::from(signature);
+        let generator = -G1Affine::generator();
+        multi_miller_loop(&[(&public_key, &hashed), (&generator, &signature)])
+            .final_exponentiation()
+            == Gt::identity()
+    }
+}
+
+/// Parses a 32-byte big-endian secret key, or `None` if it is zero or not below the group order.
+pub fn secret_key(bytes: &Hash) -> Option<Scalar> {
+    let mut le = *bytes;
+    le.reverse();
+    Option::<Scalar>::from(Scalar::from_bytes(&le)).filter(|key| *key != Scalar::zero())
+}
+
+/// Returns the public key of a secret key.
+pub fn public_key(secret: &Scalar) -> G1Projective {
+    G1Projective::generator() * secret
+}
+
+/// Signs `message` with a secret key.
+pub fn sign(secret: &Scalar, message: &[u8]) -> G2Projective {
+    hash(message) * secret
+}
+
+/// Signs `message` with every secret key and aggregates the signatures and public keys.
+pub fn sign_aggregate(secrets: &[Scalar], signed: SignedValue, message: &[u8]) -> BlsSignature {
+    let public_key: G1Projective = secrets.iter().map(public_key).sum();
+    let signature: G2Projective = secrets.iter().map(|secret| sign(secret, message)).sum();
+    BlsSignature {
+        public_key: G1Affine::from(public_key).to_compressed().to_vec(),
+        signature: G2Affine::from(signature).to_compressed().to_vec(),
+        signed,
+    }
+}
+
+/// Hashes a message to G2 under [`DST`].
+fn hash(message: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmdSha256>>::hash_to_curve(message, DST)
+}
+
+/// `expand_message_xmd` with SHA-256, from the hash-to-curve specification.
+///
+/// `bls12_381` ships this only for the older `digest` 0.9 traits, so it is implemented here on top
+/// of [`crate::sha256`]. The output is at most a few hundred bytes, so it is expanded eagerly.
+struct ExpandMsgXmdSha256;
+
+struct Expanded {
+    output: Vec<u8>,
+    offset: usize,
+}
+
+impl<'x> InitExpandMessage<'x> for ExpandMsgXmdSha256 {
+    type Expander = Expanded;
+
+    fn init_expand(message: &[u8], dst: &'x [u8], len_in_bytes: usize) -> Expanded {
+        let ell = len_in_bytes.div_ceil(32);
+        assert!(
+            ell <= 255 && dst.len() <= 255,
+            "invalid expand_message_xmd use"
+        );
+        let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+        let b_0: Hash = Sha256::new()
+            .chain_update([0u8; 64])
+            .chain_update(message)
+            .chain_update((len_in_bytes as u16).to_be_bytes())
+            .chain_update([0u8])
+            .chain_update(&dst_prime)
+            .finalize();
+        let mut output = Vec::with_capacity(ell * 32);
+        let mut b_i = [0u8; 32];
+        for i in 1..=ell {
+            let mut xored = b_0;
+            for (x, b) in xored.iter_mut().zip(&b_i) {
+                *x ^= b;
+            }
+            b_i = Sha256::new()
+                .chain_update(xored)
+                .chain_update([i as u8])
+                .chain_update(&dst_prime)
+                .finalize();
+            output.extend_from_slice(&b_i);
+        }
+        output.truncate(len_in_bytes);
+        Expanded { output, offset: 0 }
+    }
+}
+
+impl ExpandMessageState<'_> for Expanded {
+    fn read_into(&mut self, output: &mut [u8]) -> usize {
+        let len = output.len().min(self.remain());
+        output[..len].copy_from_slice(&self.output[self.offset..self.offset + len]);
+        self.offset += len;
+        len
+    }
+
+    fn remain(&self) -> usize {
+        self.output.len() - self.offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec};
+
+    use super::*;
+
+    /// The DST of the `expand_message_xmd` SHA-256 vectors of RFC 9380, appendix K.1.
+    const EXPANDER_DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA256-128";
+
+    fn expand(message: &[u8], dst: &[u8], len_in_bytes: usize) -> String {
+        let mut expander = ExpandMsgXmdSha256::init_expand(message, dst, len_in_bytes);
+        let mut output = vec![0; len_in_bytes];
+        assert_eq!(expander.read_into(&mut output), len_in_bytes);
+        assert_eq!(expander.remain(), 0);
+        hex::encode(output)
+    }
+
+    #[test]
+    fn expand_message_xmd_matches_rfc_9380() {
+        let vectors: [(&[u8], usize, &str); 5] = [
+            (
+                b"",
+                0x20,
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc",
+                0x20,
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                b"abcdef0123456789",
+                0x20,
+                "eff31487c770a893cfb36f912fbfcbff40d5661771ca4b2cb4eafe524333f5c1",
+            ),
+            (
+                b"",
+                0x80,
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbee0d121587713a3e0\
+                 dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18eda8576c412b18ffb658e3dd6ec84946\
+                 9b979d444cf7b26911a08e63cf31f9dcc541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7\
+                 fa5b646c993f0ced",
+            ),
+            (
+                b"abc",
+                0x80,
+                "abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a647e6c3163d40b76\
+                 a73cf6a5674ef1d890f95b664ee0afa5359a5c4e07985635bbecbac65d747d3d2da7ec2b8221b17b\
+                 0ca9dc8a1ac1c07ea6a1e60583e2cb00058e77b7b72a298425cd1b941ad4ec65e8afc50303a22c0f\
+                 99b0509b4c895f40",
+            ),
+        ];
+        for (message, len_in_bytes, expected) in vectors {
+            assert_eq!(expand(message, EXPANDER_DST, len_in_bytes), expected);
+        }
+    }
+
+    #[test]
+    fn hash_to_curve_matches_rfc_9380() {
+        // The BLS12381G2_XMD:SHA-256_SSWU_RO_ vectors of appendix J.10.1, uncompressed.
+        const DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        let vectors: [(&[u8], &str); 2] = [
+            (
+                b"",
+                "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d\
+                 0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a\
+                 12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6\
+                 0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+            ),
+            (
+                b"abc",
+                "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8\
+                 02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6\
+                 00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16\
+                 1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+            ),
+        ];
+        for (message, expected) in vectors {
+            let point =
+                <G2Projective as HashToCurve<ExpandMsgXmdSha256>>::hash_to_curve(message, DST);
+            assert_eq!(
+                hex::encode(G2Affine::from(point).to_uncompressed()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn signature_matches_ethereum_consensus() {
+        // The `sign` case of the consensus spec BLS tests for this key and the zero message.
+        let secret = secret_key(
+            &hex::decode("263dbd792f5b1be47ed85f8938c0f29586af0d3ac7b977f21c278fe1462040e3")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(G2Affine::from(sign(&secret, &[0; 32])).to_compressed()),
+            "b6ed936746e01f8ecf281f020953fbf1f01debd5657c4a383940b020b26507f6076334f91e2366c96e9ab279fb5158090352ea1c5b0c9274504f4f0e7053af24802e51e4568d164fe986834f41e55c8e850ce1f98458c0cfc9ab380b55285a55"
+        );
+    }
+
+    #[test]
+    fn aggregate_signatures_verify() {
+        let secrets: Vec<Scalar> = (1..=3u8).map(|i| secret_key(&[i; 32]).unwrap()).collect();
+        let signature = sign_aggregate(&secrets, SignedValue::Leaf, b"leaf");
+        assert!(signature.verify(b"leaf"));
+        assert!(sign_aggregate(&secrets[..1], SignedValue::Root, b"root").verify(b"root"));
+    }
+
+    #[test]
+    fn tampered_signatures_are_rejected() {
+        let secrets: Vec<Scalar> = (1..=3u8).map(|i| secret_key(&[i; 32]).unwrap()).collect();
+        let signature = sign_aggregate(&secrets, SignedValue::Leaf, b"leaf");
+
+        // Another message.
+        assert!(!signature.verify(b"root"));
+        // A key missing from the aggregate.
+        let partial = sign_aggregate(&secrets[..2], SignedValue::Leaf, b"leaf");
+        assert!(!BlsSignature {
+            public_key: partial.public_key,
+            ..signature.clone()
+        }
+        .verify(b"leaf"));
+        // A corrupted or truncated signature.
+        let mut corrupted = signature.signature.clone();
+        corrupted[95] ^= 1;
+        assert!(!BlsSignature {
+            signature: corrupted,
+            ..signature.clone()
+        }
+        .verify(b"leaf"));
+        assert!(!BlsSignature {
+            signature: signature.signature[..48].to_vec(),
+            ..signature.clone()
+        }
+        .verify(b"leaf"));
+        // The identity public key, under which the identity signature would otherwise verify.
+        let mut identity = [0u8; 48];
+        identity[0] = 0xc0;
+        let mut identity_signature = [0u8; 96];
+        identity_signature[0] = 0xc0;
+        assert!(!BlsSignature {
+            public_key: identity.to_vec(),
+            signature: identity_signature.to_vec(),
+            signed: SignedValue::Leaf,
+        }
+        .verify(b"leaf"));
+    }
+
+    #[test]
+    fn secret_keys_must_be_nonzero_scalars() {
+        assert!(secret_key(&[0; 32]).is_none());
+        assert!(secret_key(&[0xff; 32]).is_none());
+        assert!(secret_key(&[1; 32]).is_some());
+    }
+}
@@ -0,0 +1,52 @@
+//! Binding the proven root to its position in a history of checkpointed roots.
+//!
+//! A checkpointed log publishes the sequence of its roots as a second, standard sha256 tree whose
+//! leaves are the roots themselves, unhashed, in checkpoint order. Proving that the current root is
+//! leaf `index` of that checkpoint tree, next to the usual proof that the leaf is in the current
+//! root, ties the inclusion to one point in the log's history:
+//!
+//! ```text
+//! checkpoint_root
+//!   └── root      (leaf `index` of `count` in the checkpoint tree)
+//!         └── leaf (leaf `leaf_index` of `total_leaves` in the proven tree)
+//! ```
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{tree, Hash};
+
+/// The proven root's inclusion in a checkpoint tree, passed to the guest in
+/// [`crate::Options::checkpoint`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointProof {
+    pub checkpoint_root: Hash,
+    /// The root's position in the checkpoint history.
+    pub index: u64,
+    /// The number of checkpoints.
+    pub count: u64,
+    /// The root's sibling path in the checkpoint tree, concatenated bottom-up as in a single-leaf
+    /// `rs_merkle` proof.
+    pub proof_bytes: Vec<u8>,
+}
+
+impl CheckpointProof {
+    /// Returns whether `root` is checkpoint `index` of the checkpoint tree.
+    pub fn verify(&self, root: &Hash) -> bool {
+        if self.proof_bytes.len() % 32 != 0 {
+            return false;
+        }
+        let path: Vec<Hash> = self
+            .proof_bytes
+            .chunks_exact(32)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+        let (Ok(index), Ok(count)) = (usize::try_from(self.index), usize::try_from(self.count))
+        else {
+            return false;
+        };
+        tree::root_from_path(*root, index, count, &path, tree::sha256_parent)
+            == Some(self.checkpoint_root)
+    }
+}
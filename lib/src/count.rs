@@ -0,0 +1,41 @@
+//! Binding the number of leaves to the root with a count leaf.
+//!
+//! `total_leaves` fixes the tree shape the guest verifies against, but it is supplied by the host,
+//! and a proof can verify under more than one size. A counted tree reserves leaf 0 for a count
+//! leaf holding its own size:
+//!
+//! ```text
+//! count_leaf = SHA-256("goldinals.leaf-count" || total_leaves (8, big-endian))
+//! ```
+//!
+//! The data leaves follow at index 1 and up, and `total_leaves` includes the count leaf. Proving the
+//! count leaf of the claimed size at index 0 pins the size: a tree of a different size would need a
+//! count leaf that is a SHA-256 preimage of another size. As for any leaf, the count is only as
+//! truthful as the producer of the root, who must place the right count leaf. Truncated trees store
+//! the count leaf truncated like every other leaf.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// The prefix hashed into every count leaf, separating it from data leaves.
+pub const DOMAIN: &[u8] = b"goldinals.leaf-count";
+
+/// The index of the count leaf in a counted tree.
+pub const INDEX: u64 = 0;
+
+/// Returns the count leaf of a tree of `total_leaves` leaves.
+pub fn leaf(total_leaves: u64) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(DOMAIN);
+    hasher.update(total_leaves.to_be_bytes());
+    hasher.finalize()
+}
+
+/// The inclusion proof of the count leaf, passed to the guest in [`crate::Options::leaf_count`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CountProof {
+    pub proof_bytes: Vec<u8>,
+}
@@ -0,0 +1,42 @@
+//! EIP-712 typed-data hashes of the core public values.
+//!
+//! The core values are hashed as the struct
+//!
+//! ```text
+//! MerkleInclusion(bytes32 root,bytes32 leaf,bool isValid)
+//! ```
+//!
+//! and combined with a caller-supplied domain separator into the digest an EIP-712 signer signs:
+//!
+//! ```text
+//! digest = keccak256(0x19 || 0x01 || domainSeparator || hashStruct(MerkleInclusion))
+//! ```
+
+use sha3::{Digest, Keccak256};
+
+use crate::Hash;
+
+/// The EIP-712 type string of the hashed struct.
+pub const TYPE: &str = "MerkleInclusion(bytes32 root,bytes32 leaf,bool isValid)";
+
+/// Returns `hashStruct(MerkleInclusion(root, leaf, isValid))`.
+pub fn struct_hash(root: &Hash, leaf: &Hash, is_valid: bool) -> Hash {
+    let mut is_valid_word = [0u8; 32];
+    is_valid_word[31] = is_valid as u8;
+
+    let mut hasher = Keccak256::new();
+    hasher.update(Keccak256::digest(TYPE.as_bytes()));
+    hasher.update(root);
+    hasher.update(leaf);
+    hasher.update(is_valid_word);
+    hasher.finalize().into()
+}
+
+/// Returns the EIP-712 digest of the core values under `domain_separator`.
+pub fn typed_data_hash(domain_separator: &Hash, root: &Hash, leaf: &Hash, is_valid: bool) -> Hash {
+    let mut hasher = Keccak256::new();
+    hasher.update([0x19, 0x01]);
+    hasher.update(domain_separator);
+    hasher.update(struct_hash(root, leaf, is_valid));
+    hasher.finalize().into()
+}
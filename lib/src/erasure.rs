@@ -0,0 +1,137 @@
+//! Data-availability sampling over a tree of Reed-Solomon coded chunks.
+//!
+//! A blob is split into `data_chunks` chunks of equal size, zero-padding the last one, and extended
+//! to `total_chunks` chunks with a systematic Reed-Solomon code over GF(2^8), applied independently
+//! to every byte position:
+//!
+//! ```text
+//! chunk[x][i] = p_i(x)    for x in 0..total_chunks
+//! ```
+//!
+//! where `p_i` is the polynomial of degree below `data_chunks` with `p_i(j) = data[j][i]` for the
+//! data chunks `j < data_chunks`. The field is GF(2^8) modulo `x^8 + x^4 + x^3 + x^2 + 1` (`0x11d`)
+//! and the evaluation point of chunk `x` is the field element `x`, so there are at most 256 chunks.
+//! Any `data_chunks` of the chunks recover the blob, and the code rate is
+//! `data_chunks / total_chunks`.
+//!
+//! Chunk `x` is leaf `x` of the tree, hashed as `SHA-256(chunk)` (truncated in truncated trees).
+//! The guest checks a sampled chunk against the proven leaf and the tree size against the
+//! parameters, but it never decodes: whether the chunks really lie on one polynomial is only
+//! established by sampling enough of them, or by decoding off-chain.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// The largest number of chunks, one per element of GF(2^8).
+pub const MAX_CHUNKS: usize = 256;
+
+/// The reduction polynomial of GF(2^8), without the `x^8` term.
+const POLY: u8 = 0x1d;
+
+/// Multiplies two elements of GF(2^8).
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= POLY;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Returns the multiplicative inverse of a nonzero element of GF(2^8), as `a^254`.
+fn inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Returns the weight of every data chunk in chunk `x`, i.e. the Lagrange basis polynomials of the
+/// points `0..data_chunks` evaluated at `x`.
+fn lagrange_weights(x: u8, data_chunks: usize) -> Vec<u8> {
+    (0..data_chunks as u8)
+        .map(|j| {
+            let (numerator, denominator) = (0..data_chunks as u8)
+                .filter(|&m| m != j)
+                .fold((1, 1), |(num, den), m| (mul(num, x ^ m), mul(den, j ^ m)));
+            mul(numerator, inv(denominator))
+        })
+        .collect()
+}
+
+/// The coding parameters of a blob.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Coding {
+    pub data_chunks: u16,
+    pub total_chunks: u16,
+}
+
+impl Coding {
+    /// Returns whether the parameters describe a code: `1 <= data_chunks <= total_chunks <= 256`.
+    pub fn is_valid(&self) -> bool {
+        0 < self.data_chunks
+            && self.data_chunks <= self.total_chunks
+            && self.total_chunks as usize <= MAX_CHUNKS
+    }
+
+    /// Splits `data` into the data chunks and extends them to every chunk of the code.
+    ///
+    /// The parameters must be valid.
+    pub fn encode(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        let data_chunks = self.data_chunks as usize;
+        let chunk_size = data.len().div_ceil(data_chunks).max(1);
+        let mut chunks: Vec<Vec<u8>> = (0..data_chunks)
+            .map(|j| {
+                let mut chunk = data
+                    .get(j * chunk_size..)
+                    .map_or(&[][..], |rest| &rest[..chunk_size.min(rest.len())])
+                    .to_vec();
+                chunk.resize(chunk_size, 0);
+                chunk
+            })
+            .collect();
+        for x in data_chunks..self.total_chunks as usize {
+            let weights = lagrange_weights(x as u8, data_chunks);
+            let parity = (0..chunk_size)
+                .map(|i| {
+                    weights
+                        .iter()
+                        .zip(&chunks[..data_chunks])
+                        .fold(0, |byte, (&weight, chunk)| byte ^ mul(weight, chunk[i]))
+                })
+                .collect();
+            chunks.push(parity);
+        }
+        chunks
+    }
+}
+
+/// Returns the leaf of a chunk, `SHA-256(chunk)`.
+pub fn leaf(chunk: &[u8]) -> Hash {
+    Sha256::digest(chunk)
+}
+
+/// A sampled chunk and the coding parameters, passed to the guest in [`crate::Options::erasure`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkSample {
+    pub coding: Coding,
+    /// The chunk at the proven leaf's index.
+    pub chunk: Vec<u8>,
+}
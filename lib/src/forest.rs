@@ -0,0 +1,81 @@
+//! Forests of trees addressed by a single global index.
+//!
+//! A forest lays its trees end to end over one index space: tree `t` holds the global indices from
+//! `offset(t) = sizes[0] + ... + sizes[t - 1]` up to, but excluding, `offset(t) + sizes[t]`, and
+//! global index `g` in tree `t` is local index `g - offset(t)`. For sizes `[4, 2, 8]`, global
+//! indices `0..4` are tree 0, `4..6` are tree 1 and `6..14` are tree 2, so global index 7 is local
+//! index 1 of tree 2. Indices at or beyond the sum of the sizes are out of range.
+//!
+//! The sizes are committed as
+//!
+//! ```text
+//! sizes_hash = SHA-256(sizes[0] || sizes[1] || ...)
+//! ```
+//!
+//! with each size as 8 bytes, big-endian, so a verifier can check the layout the mapping used.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    public_values::{ForestCommitment, ForestStatus},
+    sha256::Sha256,
+    Hash,
+};
+
+/// Maps a global index to its tree and local index, or `None` if it is out of range.
+pub fn locate(sizes: &[u64], global_index: u64) -> Option<(u32, u64)> {
+    let mut offset = 0u64;
+    for (tree, &size) in sizes.iter().enumerate() {
+        let end = offset.checked_add(size)?;
+        if global_index < end {
+            return Some((tree as u32, global_index - offset));
+        }
+        offset = end;
+    }
+    None
+}
+
+/// Hashes the tree sizes of a forest.
+pub fn sizes_hash(sizes: &[u64]) -> Hash {
+    let mut hasher = Sha256::new();
+    for size in sizes {
+        hasher.update(size.to_be_bytes());
+    }
+    hasher.finalize()
+}
+
+/// A global index into a forest, to be checked against the proven tree position.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForestLookup {
+    pub sizes: Vec<u64>,
+    pub global_index: u64,
+}
+
+impl ForestLookup {
+    /// Checks that the global index maps to the proven leaf's position in a tree of the forest.
+    ///
+    /// `tree` is the tree the global index maps to, or zero if it is out of range.
+    pub fn commit(&self, leaf_index: usize, total_leaves: usize) -> ForestCommitment {
+        let (status, tree) = match locate(&self.sizes, self.global_index) {
+            None => (ForestStatus::OutOfRange, 0),
+            Some((tree, local)) => {
+                let matches =
+                    local == leaf_index as u64 && self.sizes[tree as usize] == total_leaves as u64;
+                let status = if matches {
+                    ForestStatus::Mapped
+                } else {
+                    ForestStatus::Mismatch
+                };
+                (status, tree)
+            }
+        };
+        ForestCommitment {
+            status,
+            global_index: self.global_index,
+            tree,
+            sizes_hash: sizes_hash(&self.sizes),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! The versioned header that opens every guest input.
+//!
+//! The host writes the header as the first item of `SP1Stdin`, as raw bytes rather than a bincode
+//! value, so that the guest can recognize an input without it, or of another version, before
+//! reading anything positionally:
+//!
+//! ```text
+//! header = MAGIC (4, big-endian) || version (1)
+//! ```
+//!
+//! The guest accepts the versions in [`SUPPORTED_VERSIONS`] and aborts with a [`HeaderError`] on
+//! anything else, instead of misparsing the fields after it. [`FORMAT_VERSION`] must be bumped
+//! whenever the order or encoding of those fields changes.
+
+use core::fmt;
+
+/// The magic number of a guest input, `GLDN` in ASCII.
+pub const MAGIC: u32 = u32::from_be_bytes(*b"GLDN");
+
+/// The input format version the host writes.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// The input format versions the guest reads.
+pub const SUPPORTED_VERSIONS: &[u8] = &[FORMAT_VERSION];
+
+/// The length of the header, in bytes.
+pub const HEADER_LEN: usize = 5;
+
+/// Returns the header of an input in format `version`.
+pub fn header(version: u8) -> [u8; HEADER_LEN] {
+    let mut header = [0; HEADER_LEN];
+    header[..4].copy_from_slice(&MAGIC.to_be_bytes());
+    header[4] = version;
+    header
+}
+
+/// Parses a header and returns its version if it is in `supported`.
+pub fn check_header(bytes: &[u8], supported: &[u8]) -> Result<u8, HeaderError> {
+    let Ok(header) = <[u8; HEADER_LEN]>::try_from(bytes) else {
+        return Err(HeaderError::Missing);
+    };
+    let magic = u32::from_be_bytes(header[..4].try_into().unwrap());
+    if magic != MAGIC {
+        return Err(HeaderError::BadMagic(magic));
+    }
+    match header[4] {
+        version if supported.contains(&version) => Ok(version),
+        version => Err(HeaderError::UnsupportedVersion(version)),
+    }
+}
+
+/// Why an input's header was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The first item is not a header, as in inputs written before the header existed.
+    Missing,
+    /// The first item has the length of a header but not its magic number.
+    BadMagic(u32),
+    /// The header is valid but its version is not supported.
+    UnsupportedVersion(u8),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "input has no format header"),
+            Self::BadMagic(magic) => write!(f, "input has bad magic number 0x{magic:08x}"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "input format version {version} is not supported")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HeaderError {}
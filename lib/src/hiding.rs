@@ -0,0 +1,44 @@
+//! Hiding commitments to the proven leaf.
+//!
+//! Instead of the raw leaf, the guest can commit
+//!
+//! ```text
+//! commitment = Poseidon([1, l[0..16], l[16..32], r[0..16], r[16..32]])
+//! ```
+//!
+//! where `l` is the leaf and `r` is 32 bytes of host-supplied randomness, using circom-compatible
+//! Poseidon over the BN254 scalar field so the commitment can be opened cheaply inside a downstream
+//! circuit. As in [`crate::hybrid`], each 16-byte half is read as a big-endian integer, which keeps
+//! the encoding injective and all 256 bits of randomness. The leading `1` separates commitments
+//! from hybrid tree nodes, which hash four inputs.
+//!
+//! The commitment is binding as long as Poseidon is collision resistant, and hiding as long as the
+//! randomness is uniformly random, kept secret and never reused. The randomness is a private input:
+//! it is read from stdin and never committed.
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+
+use crate::Hash;
+
+/// Commits to `leaf` with `randomness`.
+pub fn commit(leaf: &Hash, randomness: &Hash) -> Hash {
+    let halves = [
+        &leaf[..16],
+        &leaf[16..],
+        &randomness[..16],
+        &randomness[16..],
+    ]
+    .map(Fr::from_be_bytes_mod_order);
+    let inputs = [Fr::from(1u64), halves[0], halves[1], halves[2], halves[3]];
+
+    let mut poseidon = Poseidon::<Fr>::new_circom(inputs.len()).expect("supported width");
+    let digest = poseidon.hash(&inputs).expect("input count matches width");
+
+    digest
+        .into_bigint()
+        .to_bytes_be()
+        .try_into()
+        .expect("field element is 32 bytes")
+}
@@ -0,0 +1,78 @@
+//! SHA-256 leaves with Poseidon internal nodes.
+//!
+//! Leaves are 32-byte SHA-256 digests, exactly as in the standard scheme, so existing leaf data
+//! interoperates unchanged. Every internal node is instead computed with circom-compatible Poseidon
+//! over the BN254 scalar field, which is cheap to re-prove inside a SNARK:
+//!
+//! ```text
+//! parent(l, r) = Poseidon([l[0..16], l[16..32], r[0..16], r[16..32]])
+//! ```
+//!
+//! Each 16-byte half is read as a big-endian integer, so every input is below the field modulus and
+//! the split is injective even for 256-bit SHA-256 leaves. The resulting field element is serialized
+//! as 32 big-endian bytes. The tree shape matches `rs_merkle` (see [`crate::tree`]).
+
+use alloc::vec::Vec;
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+
+use crate::{tree, Hash};
+
+/// Hashes two child nodes into their parent.
+pub fn parent(left: &Hash, right: &Hash) -> Hash {
+    let inputs =
+        [&left[..16], &left[16..], &right[..16], &right[16..]].map(Fr::from_be_bytes_mod_order);
+
+    let mut poseidon = Poseidon::<Fr>::new_circom(inputs.len()).expect("supported width");
+    let digest = poseidon.hash(&inputs).expect("input count matches width");
+
+    digest
+        .into_bigint()
+        .to_bytes_be()
+        .try_into()
+        .expect("field element is 32 bytes")
+}
+
+/// A hybrid tree built on the host, keeping every level for proof generation.
+pub struct HybridTree {
+    levels: Vec<Vec<Hash>>,
+}
+
+impl HybridTree {
+    pub fn from_leaves(leaves: &[Hash]) -> Self {
+        Self {
+            levels: tree::build_levels(leaves.to_vec(), parent),
+        }
+    }
+
+    pub fn root(&self) -> Option<Hash> {
+        self.levels.last()?.first().copied()
+    }
+
+    /// Returns the sibling path for `index`, serialized as concatenated hashes like
+    /// `rs_merkle::MerkleProof::to_bytes`.
+    pub fn proof_bytes(&self, index: usize) -> Vec<u8> {
+        tree::proof_path(&self.levels, index).concat()
+    }
+}
+
+/// Verifies a proof produced by [`HybridTree::proof_bytes`].
+pub fn verify(
+    root: Hash,
+    leaf: Hash,
+    proof_bytes: &[u8],
+    index: usize,
+    total_leaves: usize,
+) -> bool {
+    if proof_bytes.len() % 32 != 0 {
+        return false;
+    }
+    let path: Vec<Hash> = proof_bytes
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+
+    tree::root_from_path(leaf, index, total_leaves, &path, parent) == Some(root)
+}
@@ -0,0 +1,84 @@
+//! Indexed Merkle trees, for proving that a value is absent from a set such as a nullifier set.
+//!
+//! Every leaf of an indexed tree stores a value of the set together with a pointer to the next
+//! larger value, so the leaves form a sorted linked list in insertion order:
+//!
+//! ```text
+//! leaf = SHA-256(value (32) || next_index (8, big-endian) || next_value (32))
+//! ```
+//!
+//! Values compare as 32-byte big-endian integers. Leaf 0 is the zero leaf, holding the reserved
+//! value 0, and the leaf holding the largest value has `next_index = 0` and `next_value = 0`. A value
+//! `v` is absent when some leaf, its low leaf, satisfies `value < v < next_value`, or
+//! `value < v` with `next_value = 0`. This relies on the tree being well-formed, i.e. on every
+//! insertion having updated its low leaf, which the root's producer has to guarantee.
+
+use alloc::{vec, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// A leaf of an indexed Merkle tree.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImtLeaf {
+    pub value: Hash,
+    pub next_index: u64,
+    pub next_value: Hash,
+}
+
+impl ImtLeaf {
+    /// Returns `value || next_index (8, big-endian) || next_value`.
+    pub fn to_bytes(&self) -> [u8; 72] {
+        let mut bytes = [0; 72];
+        bytes[..32].copy_from_slice(&self.value);
+        bytes[32..40].copy_from_slice(&self.next_index.to_be_bytes());
+        bytes[40..].copy_from_slice(&self.next_value);
+        bytes
+    }
+
+    /// Returns the SHA-256 of [`Self::to_bytes`].
+    pub fn hash(&self) -> Hash {
+        Sha256::digest(self.to_bytes())
+    }
+
+    /// Returns whether this leaf is the low leaf of `value`, proving that `value` is absent.
+    pub fn excludes(&self, value: &Hash) -> bool {
+        self.value < *value && (*value < self.next_value || self.next_value == Hash::default())
+    }
+}
+
+/// A queried value and its low leaf, passed to the guest in [`crate::Options::imt`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImtQuery {
+    pub value: Hash,
+    pub low_leaf: ImtLeaf,
+}
+
+/// Builds the leaves of an indexed tree holding `values`, inserted in order after the zero leaf.
+///
+/// `values` must be distinct and nonzero.
+pub fn leaves_from_values(values: &[Hash]) -> Vec<ImtLeaf> {
+    let zero = ImtLeaf {
+        value: Hash::default(),
+        next_index: 0,
+        next_value: Hash::default(),
+    };
+    let mut leaves = vec![zero];
+    leaves.extend(values.iter().map(|&value| ImtLeaf { value, ..zero }));
+
+    let mut order: Vec<usize> = (1..leaves.len()).collect();
+    order.sort_by_key(|&i| leaves[i].value);
+    let mut prev = 0;
+    for i in order {
+        leaves[prev].next_index = i as u64;
+        leaves[prev].next_value = leaves[i].value;
+        prev = i;
+    }
+    leaves
+}
+
+/// Returns the index of the low leaf of `value` among `leaves`, if `value` is absent.
+pub fn low_leaf_index(leaves: &[ImtLeaf], value: &Hash) -> Option<usize> {
+    leaves.iter().position(|leaf| leaf.excludes(value))
+}
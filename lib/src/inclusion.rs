@@ -0,0 +1,233 @@
+//! Inclusion proofs of the trees whose proofs prove one leaf by its index: sha256, keccak256,
+//! hybrid, truncated sha256, k-ary, blake2b and blake3.
+//!
+//! [`verify`] is the check the guest commits as `is_valid` and the host predicts before proving, so
+//! both reject a malformed proof or an index outside the tree the same way. The parameters, the
+//! index and the proof's shape are checked before the proof is parsed, since `rs_merkle` does not
+//! expect an index outside the tree and hashing with invalid parameters cannot succeed.
+
+use rs_merkle::MerkleProof;
+
+use crate::{
+    blake2b, blake3, hybrid, kary, keccak::Keccak256Hasher, public_values::Diagnostic,
+    sha256::Sha256Hasher, tree, truncated, Hash, Scheme,
+};
+
+/// The parameters a scheme's nodes are hashed with.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Parameters<'a> {
+    /// The node length, read by truncated sha256.
+    pub hash_bytes: Option<u8>,
+    /// The number of children per node, read by k-ary trees.
+    pub arity: Option<u8>,
+    /// The BLAKE2b personalization, only read by blake2b.
+    pub personalization: &'a [u8],
+}
+
+impl Parameters<'_> {
+    /// Whether the parameters that are set, and the personalization under blake2b, are in range.
+    pub fn is_valid(&self, scheme: Scheme) -> bool {
+        self.hash_bytes.map_or(true, truncated::is_valid_len)
+            && self.arity.map_or(true, kary::is_valid_arity)
+            && (scheme != Scheme::Blake2b
+                || blake2b::is_valid_personalization(self.personalization))
+    }
+}
+
+/// Verifies that `proof` proves `leaf` at `index` of a tree of `total_leaves` leaves under `root`.
+///
+/// A proof that cannot be split into nodes or does not have one node per level of the leaf's path
+/// is reported rather than treated as a mismatch. k-ary proofs carry their own group sizes and are
+/// only checked by verifying them.
+///
+/// # Panics
+///
+/// If `scheme` is truncated sha256 or k-ary and `parameters` lacks its node length or arity, or if
+/// `scheme` is not one of the schemes above.
+pub fn verify(
+    scheme: Scheme,
+    parameters: Parameters,
+    root: Hash,
+    leaf: Hash,
+    proof: &[u8],
+    index: usize,
+    total_leaves: usize,
+) -> Result<bool, Diagnostic> {
+    let node_len = match scheme {
+        Scheme::Kary => None,
+        Scheme::TruncatedSha256 => parameters.hash_bytes.map(usize::from),
+        _ => Some(32),
+    };
+    match scheme {
+        _ if !parameters.is_valid(scheme) => Err(Diagnostic::InvalidParameters),
+        _ if index >= total_leaves => Err(Diagnostic::IndexOutOfRange),
+        _ if node_len.is_some_and(|len| proof.len() % len != 0) => Err(Diagnostic::MalformedProof),
+        _ if node_len
+            .is_some_and(|len| proof.len() / len != tree::path_len(index, total_leaves)) =>
+        {
+            Err(Diagnostic::PathLengthMismatch)
+        }
+        Scheme::Sha256 => MerkleProof::<Sha256Hasher>::from_bytes(proof)
+            .map(|proof| proof.verify(root, &[index], &[leaf], total_leaves))
+            .map_err(|_| Diagnostic::MalformedProof),
+        Scheme::Keccak256 => MerkleProof::<Keccak256Hasher>::from_bytes(proof)
+            .map(|proof| proof.verify(root, &[index], &[leaf], total_leaves))
+            .map_err(|_| Diagnostic::MalformedProof),
+        Scheme::Hybrid => Ok(hybrid::verify(root, leaf, proof, index, total_leaves)),
+        Scheme::TruncatedSha256 => Ok(truncated::verify(
+            root,
+            leaf,
+            proof,
+            index,
+            total_leaves,
+            parameters
+                .hash_bytes
+                .expect("truncated sha256 needs a node length"),
+        )),
+        Scheme::Kary => Ok(kary::verify(
+            root,
+            leaf,
+            proof,
+            index,
+            total_leaves,
+            parameters.arity.expect("k-ary trees need an arity"),
+        )),
+        Scheme::Blake2b => Ok(blake2b::verify(
+            root,
+            leaf,
+            proof,
+            index,
+            total_leaves,
+            parameters.personalization,
+        )),
+        Scheme::Blake3 => Ok(blake3::verify(root, leaf, proof, index, total_leaves)),
+        Scheme::Patricia | Scheme::Sparse | Scheme::Bitcoin | Scheme::Mmr => {
+            unreachable!(
+                "patricia, sparse, bitcoin and mmr proofs do not prove a leaf by its index"
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rs_merkle::{Hasher, MerkleTree};
+
+    use super::*;
+
+    /// The root, leaves and proof of leaf 2 of a five-leaf sha256 tree.
+    fn five_leaves() -> (Hash, Vec<Hash>, Vec<u8>) {
+        let leaves: Vec<Hash> = (0u8..5).map(|i| Sha256Hasher::hash(&[i])).collect();
+        let tree = MerkleTree::<Sha256Hasher>::from_leaves(&leaves);
+        (
+            tree.root().unwrap(),
+            leaves.clone(),
+            tree.proof(&[2]).to_bytes(),
+        )
+    }
+
+    fn verify_sha256(
+        root: Hash,
+        leaf: Hash,
+        proof: &[u8],
+        index: usize,
+        total: usize,
+    ) -> Result<bool, Diagnostic> {
+        verify(
+            Scheme::Sha256,
+            Parameters::default(),
+            root,
+            leaf,
+            proof,
+            index,
+            total,
+        )
+    }
+
+    #[test]
+    fn valid_proof_verifies() {
+        let (root, leaves, proof) = five_leaves();
+        assert_eq!(verify_sha256(root, leaves[2], &proof, 2, 5), Ok(true));
+        assert_eq!(verify_sha256(root, leaves[3], &proof, 2, 5), Ok(false));
+    }
+
+    #[test]
+    fn truncated_proof_is_malformed() {
+        let (root, leaves, proof) = five_leaves();
+        assert_eq!(
+            verify_sha256(root, leaves[2], &proof[..proof.len() - 1], 2, 5),
+            Err(Diagnostic::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn empty_proof_of_inner_leaf_is_too_short() {
+        let (root, leaves, _) = five_leaves();
+        assert_eq!(
+            verify_sha256(root, leaves[2], &[], 2, 5),
+            Err(Diagnostic::PathLengthMismatch)
+        );
+    }
+
+    #[test]
+    fn proof_of_smaller_tree_does_not_fit() {
+        let (root, leaves, proof) = five_leaves();
+        assert_eq!(
+            verify_sha256(root, leaves[2], &proof, 2, 9),
+            Err(Diagnostic::PathLengthMismatch)
+        );
+    }
+
+    #[test]
+    fn oversized_leaf_index_is_out_of_range() {
+        let (root, leaves, proof) = five_leaves();
+        for index in [5, 6, usize::MAX] {
+            assert_eq!(
+                verify_sha256(root, leaves[2], &proof, index, 5),
+                Err(Diagnostic::IndexOutOfRange)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_tree_is_out_of_range() {
+        let (root, leaves, proof) = five_leaves();
+        assert_eq!(
+            verify_sha256(root, leaves[2], &proof, 0, 0),
+            Err(Diagnostic::IndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn invalid_parameters_are_reported_first() {
+        let (root, leaves, proof) = five_leaves();
+        // A node length of zero would divide by zero when splitting the proof.
+        let parameters = Parameters {
+            hash_bytes: Some(0),
+            ..Parameters::default()
+        };
+        assert_eq!(
+            verify(
+                Scheme::TruncatedSha256,
+                parameters,
+                root,
+                leaves[2],
+                &proof,
+                9,
+                5
+            ),
+            Err(Diagnostic::InvalidParameters)
+        );
+        // Personalizations longer than 16 bytes do not fit BLAKE2b's parameter block.
+        let parameters = Parameters {
+            personalization: &[0; 17],
+            ..Parameters::default()
+        };
+        assert_eq!(
+            verify(Scheme::Blake2b, parameters, root, leaves[2], &proof, 2, 5),
+            Err(Diagnostic::InvalidParameters)
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! A secondary index tree mapping keys to positions in the main tree.
+//!
+//! Each index leaf binds one key to one position:
+//!
+//! ```text
+//! leaf(key, position) = SHA-256(position (8 bytes, big-endian) || key)
+//! ```
+//!
+//! The position has a fixed width, so the encoding is unambiguous for keys of any length. Internal
+//! nodes are plain SHA-256 of the two children with the `rs_merkle` shape (see [`crate::tree`]), so
+//! an index tree can equally be built with `rs_merkle`.
+//!
+//! Proving a lookup and then the main-tree leaf at the looked-up position binds
+//! `key -> position -> value` in a single proof.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, tree, Hash};
+
+/// Hashes a key and its position into an index leaf.
+pub fn leaf(key: &[u8], position: u64) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(position.to_be_bytes());
+    hasher.update(key);
+    hasher.finalize()
+}
+
+/// Hashes two child nodes into their parent.
+pub fn parent(left: &Hash, right: &Hash) -> Hash {
+    tree::sha256_parent(left, right)
+}
+
+/// An index tree built on the host, keeping every level for proof generation.
+pub struct IndexTree {
+    levels: Vec<Vec<Hash>>,
+}
+
+impl IndexTree {
+    /// Builds the tree over `(key, position)` entries, in the given order.
+    pub fn from_entries<K: AsRef<[u8]>>(entries: &[(K, u64)]) -> Self {
+        let leaves = entries
+            .iter()
+            .map(|(key, position)| leaf(key.as_ref(), *position))
+            .collect();
+        Self {
+            levels: tree::build_levels(leaves, parent),
+        }
+    }
+
+    pub fn root(&self) -> Option<Hash> {
+        self.levels.last()?.first().copied()
+    }
+
+    /// Returns the sibling path for the entry at `index`, serialized as concatenated hashes.
+    pub fn proof_bytes(&self, index: usize) -> Vec<u8> {
+        tree::proof_path(&self.levels, index).concat()
+    }
+}
+
+/// A key's lookup in an index tree, passed to the guest in [`crate::Options::index_lookup`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexLookup {
+    /// The root of the index tree.
+    pub root: Hash,
+    /// The key being looked up.
+    pub key: Vec<u8>,
+    /// The position the key maps to in the main tree.
+    pub position: u64,
+    /// The sibling path of the `(key, position)` entry, from [`IndexTree::proof_bytes`].
+    pub proof_bytes: Vec<u8>,
+    /// The entry's index within the index tree.
+    pub entry_index: usize,
+    /// The number of entries in the index tree.
+    pub total_entries: usize,
+}
+
+impl IndexLookup {
+    /// Returns whether `(key, position)` is an entry of the index tree.
+    pub fn verify(&self) -> bool {
+        if self.proof_bytes.len() % 32 != 0 {
+            return false;
+        }
+        let path: Vec<Hash> = self
+            .proof_bytes
+            .chunks_exact(32)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+
+        let leaf = leaf(&self.key, self.position);
+        tree::root_from_path(leaf, self.entry_index, self.total_entries, &path, parent)
+            == Some(self.root)
+    }
+}
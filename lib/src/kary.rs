@@ -0,0 +1,127 @@
+//! SHA-256 trees with a configurable arity.
+//!
+//! Children are grouped left-to-right in runs of `arity`, and a parent is the SHA-256 of its
+//! children concatenated in order:
+//!
+//! ```text
+//! parent(c_0, ..., c_{m-1}) = sha256(c_0 || c_1 || ... || c_{m-1})
+//! ```
+//!
+//! Only the last group of a level can have `m < arity` children. As in the binary scheme, a group
+//! with a single child promotes it unchanged, so `arity = 2` yields the same root as `rs_merkle`.
+//!
+//! A proof is a sequence of level entries, bottom-up, for every level where the node's group has
+//! more than one child: `position (1 byte) || siblings`, where `position` is the node's offset in
+//! its group and `siblings` are the other `m - 1` children in order, 32 bytes each. The verifier
+//! derives the expected position from the leaf index and rejects entries that disagree.
+
+use alloc::{vec, vec::Vec};
+
+use crate::{sha256::Sha256, Hash};
+
+/// Returns whether `arity` is supported.
+pub fn is_valid_arity(arity: u8) -> bool {
+    (2..=16).contains(&arity)
+}
+
+/// Hashes an ordered group of children into their parent.
+pub fn parent(children: &[Hash]) -> Hash {
+    let mut hasher = Sha256::new();
+    for child in children {
+        hasher.update(child);
+    }
+    hasher.finalize()
+}
+
+/// A k-ary tree built on the host, keeping every level for proof generation.
+pub struct KaryTree {
+    levels: Vec<Vec<Hash>>,
+    arity: usize,
+}
+
+impl KaryTree {
+    pub fn from_leaves(leaves: &[Hash], arity: u8) -> Self {
+        assert!(is_valid_arity(arity), "arity must be between 2 and 16");
+        let arity = arity as usize;
+
+        let mut levels = vec![leaves.to_vec()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(arity)
+                .map(|group| match group {
+                    [single] => *single,
+                    _ => parent(group),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels, arity }
+    }
+
+    pub fn root(&self) -> Option<Hash> {
+        self.levels.last()?.first().copied()
+    }
+
+    /// Returns the proof for `index` in the format described in the module docs.
+    pub fn proof_bytes(&self, mut index: usize) -> Vec<u8> {
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let start = index - index % self.arity;
+            let group = &level[start..level.len().min(start + self.arity)];
+            if group.len() > 1 {
+                proof.push((index - start) as u8);
+                for (offset, sibling) in group.iter().enumerate() {
+                    if start + offset != index {
+                        proof.extend_from_slice(sibling);
+                    }
+                }
+            }
+            index /= self.arity;
+        }
+        proof
+    }
+}
+
+/// Verifies a proof produced by [`KaryTree::proof_bytes`].
+pub fn verify(
+    root: Hash,
+    leaf: Hash,
+    proof_bytes: &[u8],
+    mut index: usize,
+    total_leaves: usize,
+    arity: u8,
+) -> bool {
+    if !is_valid_arity(arity) || index >= total_leaves {
+        return false;
+    }
+    let arity = arity as usize;
+
+    let mut node = leaf;
+    let mut width = total_leaves;
+    let mut proof = proof_bytes;
+    while width > 1 {
+        let start = index - index % arity;
+        let group_len = width.min(start + arity) - start;
+        if group_len > 1 {
+            let entry_len = 1 + (group_len - 1) * 32;
+            if proof.len() < entry_len || proof[0] as usize != index - start {
+                return false;
+            }
+            let mut siblings = proof[1..entry_len].chunks_exact(32);
+            let children: Vec<Hash> = (0..group_len)
+                .map(|offset| match offset == index - start {
+                    true => node,
+                    false => siblings.next().unwrap().try_into().unwrap(),
+                })
+                .collect();
+            node = parent(&children);
+            proof = &proof[entry_len..];
+        }
+        index /= arity;
+        width = width.div_ceil(arity);
+    }
+
+    proof.is_empty() && node == root
+}
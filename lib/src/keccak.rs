@@ -0,0 +1,99 @@
+//! Keccak-256 trees, as built by Solidity contracts and OpenZeppelin's `MerkleProof`.
+//!
+//! Leaves are the Keccak-256 digests of their data and every internal node hashes its two children
+//! in sorted order:
+//!
+//! ```text
+//! parent(a, b) = keccak256(min(a, b) || max(a, b))
+//! ```
+//!
+//! Sorting makes a proof independent of the left/right position of each sibling, which is how
+//! `MerkleProof.verify` hashes pairs, so a proof of this tree verifies on-chain with only the root,
+//! the leaf and the sibling hashes. The tree shape matches `rs_merkle` (see [`crate::tree`]).
+//!
+//! In the zkVM [`hash`] runs the sponge on the Keccak-f\[1600\] precompile.
+
+use rs_merkle::Hasher;
+use sha3::{Digest, Keccak256};
+
+use crate::Hash;
+
+/// The number of bytes absorbed per Keccak-f\[1600\] permutation by Keccak-256.
+const RATE: usize = 136;
+
+/// Returns the Keccak-256 digest of `data`, on the permutation precompile in the zkVM.
+pub fn hash(data: &[u8]) -> Hash {
+    #[cfg(target_os = "zkvm")]
+    {
+        // SAFETY: the precompile only reads and writes the state it is given.
+        sponge(data, |state| unsafe {
+            sp1_lib::syscall_keccak_permute(state)
+        })
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    {
+        Keccak256::digest(data).into()
+    }
+}
+
+/// Returns the two children in the order they are hashed, the smaller first.
+pub fn sorted_pair(left: &Hash, right: &Hash) -> [u8; 64] {
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    let mut pair = [0; 64];
+    pair[..32].copy_from_slice(first);
+    pair[32..].copy_from_slice(second);
+    pair
+}
+
+/// Hashes two child nodes into their parent.
+pub fn parent(left: &Hash, right: &Hash) -> Hash {
+    hash(&sorted_pair(left, right))
+}
+
+/// The `rs_merkle` hasher of this tree, shared by the guest and the host.
+#[derive(Clone)]
+pub struct Keccak256Hasher;
+
+impl Hasher for Keccak256Hasher {
+    type Hash = Hash;
+
+    fn hash(data: &[u8]) -> Hash {
+        hash(data)
+    }
+
+    fn concat_and_hash(left: &Hash, right: Option<&Hash>) -> Hash {
+        match right {
+            Some(right) => parent(left, right),
+            None => *left,
+        }
+    }
+}
+
+/// Returns the Keccak-256 digest of `data`, computed with the given Keccak-f\[1600\] permutation.
+///
+/// [`hash`] runs this on the zkVM's keccak precompile; with a software permutation it agrees with
+/// `sha3`.
+pub fn sponge(data: &[u8], mut permute: impl FnMut(&mut [u64; 25])) -> Hash {
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().div_ceil(RATE) * RATE, 0);
+    *padded.last_mut().unwrap() |= 0x80;
+
+    let mut state = [0u64; 25];
+    for block in padded.chunks_exact(RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        permute(&mut state);
+    }
+
+    let mut digest = [0; 32];
+    for (bytes, lane) in digest.chunks_exact_mut(8).zip(&state) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
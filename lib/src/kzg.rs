@@ -0,0 +1,279 @@
+//! KZG polynomial commitment openings over BN254.
+//!
+//! Alongside Merkle inclusion, the guest can check that the leaf is also the evaluation of a
+//! committed polynomial at the leaf's index, `p(index) = leaf`. The leaf is read as a big-endian
+//! integer reduced modulo the BN254 scalar field order `r`, and the index as a field element. Given a
+//! commitment `C = [p(τ)]₁`, an opening proof `π = [q(τ)]₁` for the quotient
+//! `q(x) = (p(x) - y) / (x - z)` is valid when
+//!
+//! ```text
+//! e(C - [y]₁, [1]₂) = e(π, [τ]₂ - [z]₂)
+//! ```
+//!
+//! Points are encoded in arkworks' compressed form: 32 bytes in G1 and 64 bytes in G2.
+//!
+//! The check is only as sound as the trusted setup `τ` was secret. The guest does not know which
+//! setup to trust, so it takes `[τ]₂` as an input and commits it next to the result; verifiers must
+//! compare it against the `[τ]₂` of a setup they trust, such as a powers-of-tau ceremony. [`Setup`]
+//! builds a setup from a known `τ`, which is only suitable for testing.
+
+use alloc::{vec, vec::Vec};
+
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::Hash;
+
+/// A compressed G1 point.
+pub type G1Bytes = [u8; 32];
+
+/// A compressed G2 point.
+pub type G2Bytes = [u8; 64];
+
+/// An opening of a committed polynomial at the proven leaf's index.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Opening {
+    /// The polynomial commitment.
+    pub commitment: G1Bytes,
+    /// The commitment to the quotient polynomial.
+    pub proof: G1Bytes,
+    /// `[τ]₂` from the trusted setup, compressed. Kept as a vector because serde only derives
+    /// arrays of up to 32 bytes.
+    pub tau_g2: Vec<u8>,
+}
+
+impl Opening {
+    /// Returns whether the committed polynomial evaluates to `leaf` at `index`.
+    ///
+    /// Malformed or off-curve points make the opening invalid rather than failing.
+    pub fn verify(&self, leaf: &Hash, index: u64) -> bool {
+        let (Ok(commitment), Ok(proof), Ok(tau_g2)) = (
+            G1Affine::deserialize_compressed(&self.commitment[..]),
+            G1Affine::deserialize_compressed(&self.proof[..]),
+            G2Affine::deserialize_compressed(&self.tau_g2[..]),
+        ) else {
+            return false;
+        };
+        let y = Fr::from_be_bytes_mod_order(leaf);
+        let z = Fr::from(index);
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+
+        let lhs = (commitment.into_group() - g1 * y).into_affine();
+        let rhs = (tau_g2.into_group() - g2 * z).into_affine();
+        Bn254::multi_pairing([lhs, (-proof.into_group()).into_affine()], [g2, rhs]).is_zero()
+    }
+}
+
+/// Powers of `τ` in G1, and `[τ]₂`.
+pub struct Setup {
+    pub g1_powers: Vec<G1Affine>,
+    pub tau_g2: G2Affine,
+}
+
+impl Setup {
+    /// Builds a setup supporting polynomials with up to `len` coefficients from a known `τ`, read
+    /// as a big-endian integer modulo `r`.
+    ///
+    /// Anyone who knows `τ` can open a commitment to any value, so this is for testing only.
+    pub fn insecure(tau: &Hash, len: usize) -> Self {
+        let tau = Fr::from_be_bytes_mod_order(tau);
+        let g1 = G1Affine::generator();
+        let mut power = Fr::one();
+        let g1_powers: Vec<G1Projective> = (0..len)
+            .map(|_| {
+                let point = g1 * power;
+                power *= tau;
+                point
+            })
+            .collect();
+        Setup {
+            g1_powers: G1Projective::normalize_batch(&g1_powers),
+            tau_g2: (G2Affine::generator() * tau).into_affine(),
+        }
+    }
+
+    /// Commits to the polynomial with these coefficients, lowest degree first.
+    pub fn commit(&self, coefficients: &[Fr]) -> G1Affine {
+        G1Projective::msm(&self.g1_powers[..coefficients.len()], coefficients)
+            .expect("base and scalar counts match")
+            .into_affine()
+    }
+
+    /// Opens the polynomial with these coefficients at `index` for use with [`Opening::verify`].
+    pub fn open(&self, coefficients: &[Fr], index: u64) -> Opening {
+        let z = Fr::from(index);
+        // Synthetic division by (x - z); the remainder p(z) is dropped.
+        let mut quotient = vec![Fr::zero(); coefficients.len().saturating_sub(1)];
+        let mut carry = Fr::zero();
+        for i in (1..coefficients.len()).rev() {
+            carry = coefficients[i] + carry * z;
+            quotient[i - 1] = carry;
+        }
+
+        Opening {
+            commitment: compress(&self.commit(coefficients)),
+            proof: compress(&self.commit(&quotient)),
+            tau_g2: compress_g2(&self.tau_g2).to_vec(),
+        }
+    }
+}
+
+/// Returns the coefficients of the polynomial with `p(i) = leaves[i]` for every `i`, each leaf read
+/// as a field element as in [`Opening::verify`].
+pub fn interpolate(leaves: &[Hash]) -> Vec<Fr> {
+    let n = leaves.len();
+    // The product of (x - j) over every point j, lowest degree first.
+    let mut vanishing = vec![Fr::one()];
+    for j in 0..n {
+        let j = Fr::from(j as u64);
+        vanishing.insert(0, Fr::zero());
+        for k in 0..vanishing.len() - 1 {
+            let next = vanishing[k + 1];
+            vanishing[k] -= j * next;
+        }
+    }
+
+    let mut coefficients = vec![Fr::zero(); n];
+    for (i, leaf) in leaves.iter().enumerate() {
+        let x = Fr::from(i as u64);
+        // The vanishing polynomial divided by (x - i), and its value at i.
+        let mut basis = vec![Fr::zero(); n];
+        let mut carry = Fr::zero();
+        for k in (1..=n).rev() {
+            carry = vanishing[k] + carry * x;
+            basis[k - 1] = carry;
+        }
+        let denominator = basis.iter().rev().fold(Fr::zero(), |acc, &c| acc * x + c);
+        let scale = Fr::from_be_bytes_mod_order(leaf) * denominator.inverse().expect("nonzero");
+        for (coefficient, b) in coefficients.iter_mut().zip(&basis) {
+            *coefficient += scale * b;
+        }
+    }
+    coefficients
+}
+
+/// Compresses a G1 point.
+pub fn compress(point: &G1Affine) -> G1Bytes {
+    let mut bytes = [0u8; 32];
+    point
+        .serialize_compressed(&mut bytes[..])
+        .expect("G1 points compress to 32 bytes");
+    bytes
+}
+
+/// Compresses a G2 point.
+pub fn compress_g2(point: &G2Affine) -> G2Bytes {
+    let mut bytes = [0u8; 64];
+    point
+        .serialize_compressed(&mut bytes[..])
+        .expect("G2 points compress to 64 bytes");
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves() -> Vec<Hash> {
+        (0..5u8).map(|i| [i.wrapping_mul(37) ^ 0xa5; 32]).collect()
+    }
+
+    fn evaluate(coefficients: &[Fr], x: Fr) -> Fr {
+        coefficients
+            .iter()
+            .rev()
+            .fold(Fr::zero(), |acc, &c| acc * x + c)
+    }
+
+    #[test]
+    fn interpolation_passes_through_every_leaf() {
+        let leaves = leaves();
+        let coefficients = interpolate(&leaves);
+        assert_eq!(coefficients.len(), leaves.len());
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(
+                evaluate(&coefficients, Fr::from(i as u64)),
+                Fr::from_be_bytes_mod_order(leaf)
+            );
+        }
+    }
+
+    #[test]
+    fn quotient_divides_out_the_opening_point() {
+        let setup = Setup::insecure(&[7; 32], 8);
+        let coefficients = interpolate(&leaves());
+        let opening = setup.open(&coefficients, 3);
+
+        // Recompute the quotient and check q(x) * (x - z) + p(z) = p(x) at a few points.
+        let z = Fr::from(3u64);
+        let mut quotient = vec![Fr::zero(); coefficients.len() - 1];
+        let mut carry = Fr::zero();
+        for i in (1..coefficients.len()).rev() {
+            carry = coefficients[i] + carry * z;
+            quotient[i - 1] = carry;
+        }
+        let remainder = evaluate(&coefficients, z);
+        for x in [0u64, 1, 11, 1000] {
+            let x = Fr::from(x);
+            assert_eq!(
+                evaluate(&quotient, x) * (x - z) + remainder,
+                evaluate(&coefficients, x)
+            );
+        }
+        assert_eq!(opening.proof, compress(&setup.commit(&quotient)));
+    }
+
+    #[test]
+    fn openings_verify_at_every_index() {
+        let leaves = leaves();
+        let setup = Setup::insecure(&[7; 32], leaves.len());
+        let coefficients = interpolate(&leaves);
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert!(setup.open(&coefficients, i as u64).verify(leaf, i as u64));
+        }
+    }
+
+    #[test]
+    fn tampered_openings_are_rejected() {
+        let leaves = leaves();
+        let setup = Setup::insecure(&[7; 32], leaves.len());
+        let coefficients = interpolate(&leaves);
+        let opening = setup.open(&coefficients, 2);
+        assert!(opening.verify(&leaves[2], 2));
+
+        // Another leaf or index.
+        assert!(!opening.verify(&leaves[3], 2));
+        assert!(!opening.verify(&leaves[2], 3));
+        // The proof of another index.
+        let other = setup.open(&coefficients, 1);
+        assert!(!Opening {
+            proof: other.proof,
+            ..opening.clone()
+        }
+        .verify(&leaves[2], 2));
+        // A setup with another τ.
+        let tau_g2 = Setup::insecure(&[8; 32], 1).tau_g2;
+        assert!(!Opening {
+            tau_g2: compress_g2(&tau_g2).to_vec(),
+            ..opening.clone()
+        }
+        .verify(&leaves[2], 2));
+        // A corrupted proof and a truncated `[τ]₂`.
+        let mut proof = opening.proof;
+        proof[0] ^= 1;
+        assert!(!Opening {
+            proof,
+            ..opening.clone()
+        }
+        .verify(&leaves[2], 2));
+        assert!(!Opening {
+            tau_g2: vec![0; 3],
+            ..opening
+        }
+        .verify(&leaves[2], 2));
+    }
+}
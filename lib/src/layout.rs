@@ -0,0 +1,400 @@
+//! A machine-readable description of the [`crate::public_values`] layout.
+//!
+//! The tables here reference the same constants the encoder uses, so a schema generated from them
+//! stays in sync with [`crate::PublicValues::to_bytes`]. Bump [`LAYOUT_VERSION`] whenever a field
+//! or record changes meaning.
+
+use serde::Serialize;
+
+use crate::public_values::*;
+
+/// The version of the layout described by [`CORE_FIELDS`] and [`RECORDS`].
+pub const LAYOUT_VERSION: u32 = 2;
+
+/// How every extension record after the core is framed.
+pub const RECORD_FRAMING: &str = "tag (1) || length (2, big-endian) || payload";
+
+/// The Solidity struct an ABI-encoded proof commits, see [`crate::abi`].
+pub const ABI_STRUCT: &str = "PublicValuesStruct(bytes32 root, bytes32 leaf, bool isValid, \
+                              uint64 leafIndex, uint64 totalLeaves, bytes extensions)";
+
+/// A field of the fixed core.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct CoreField {
+    pub name: &'static str,
+    pub offset: usize,
+    pub length: usize,
+    pub encoding: &'static str,
+}
+
+/// An extension record that may follow the core.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct RecordLayout {
+    pub tag: u8,
+    pub name: &'static str,
+    /// The payload length, or `None` if it varies.
+    pub length: Option<usize>,
+    pub encoding: &'static str,
+}
+
+/// The fields of the fixed core, in order. Their lengths add up to [`CORE_LEN`].
+pub const CORE_FIELDS: [CoreField; 5] = [
+    CoreField {
+        name: "root",
+        offset: 0,
+        length: 32,
+        encoding: "bytes32",
+    },
+    CoreField {
+        name: "leaf",
+        offset: 32,
+        length: 32,
+        encoding: "bytes32",
+    },
+    CoreField {
+        name: "is_valid",
+        offset: 64,
+        length: 1,
+        encoding: "bool (0 or 1)",
+    },
+    CoreField {
+        name: "leaf_index",
+        offset: 65,
+        length: 8,
+        encoding: "u64, big-endian",
+    },
+    CoreField {
+        name: "total_leaves",
+        offset: 73,
+        length: 8,
+        encoding: "u64, big-endian",
+    },
+];
+
+/// The words of a field-packed proof, each a 32-byte big-endian field element. Their lengths add
+/// up to [`FIELD_PACKED_LEN`].
+pub const FIELD_PACKED_WORDS: [CoreField; 7] = [
+    CoreField {
+        name: "root_hi",
+        offset: 0,
+        length: 32,
+        encoding: "uint256, root[0..16]",
+    },
+    CoreField {
+        name: "root_lo",
+        offset: 32,
+        length: 32,
+        encoding: "uint256, root[16..32]",
+    },
+    CoreField {
+        name: "leaf_index",
+        offset: 64,
+        length: 32,
+        encoding: "uint256, u64",
+    },
+    CoreField {
+        name: "total_leaves",
+        offset: 96,
+        length: 32,
+        encoding: "uint256, u64",
+    },
+    CoreField {
+        name: "leaf_hi",
+        offset: 128,
+        length: 32,
+        encoding: "uint256, leaf[0..16]",
+    },
+    CoreField {
+        name: "leaf_lo",
+        offset: 160,
+        length: 32,
+        encoding: "uint256, leaf[16..32]",
+    },
+    CoreField {
+        name: "is_valid",
+        offset: 192,
+        length: 32,
+        encoding: "uint256 (0 or 1)",
+    },
+];
+
+/// Every known extension record, by tag.
+pub const RECORDS: &[RecordLayout] = &[
+    RecordLayout {
+        tag: TAG_SCHEME,
+        name: "scheme",
+        length: Some(1),
+        encoding: "u8 (1 hybrid, 2 truncated sha256, 3 k-ary, 4 patricia, 5 keccak256, 6 blake2b, \
+                   7 sparse, 8 blake3, 9 bitcoin, 10 mmr)",
+    },
+    RecordLayout {
+        tag: TAG_HASH_BYTES,
+        name: "hash_bytes",
+        length: Some(1),
+        encoding: "u8",
+    },
+    RecordLayout {
+        tag: TAG_LEAF_MATCH,
+        name: "leaf_matches",
+        length: Some(1),
+        encoding: "bool (0 or 1)",
+    },
+    RecordLayout {
+        tag: TAG_ORDINAL,
+        name: "ordinal",
+        length: Some(8),
+        encoding: "value (u32, big-endian) || scale (u32, big-endian)",
+    },
+    RecordLayout {
+        tag: TAG_ROOT_REVERSED,
+        name: "root_reversed",
+        length: Some(32),
+        encoding: "bytes32",
+    },
+    RecordLayout {
+        tag: TAG_ARITY,
+        name: "arity",
+        length: Some(1),
+        encoding: "u8",
+    },
+    RecordLayout {
+        tag: TAG_RESULTS,
+        name: "results",
+        length: None,
+        encoding: "(label (4 ASCII bytes) || result (u8))*, sorted by label",
+    },
+    RecordLayout {
+        tag: TAG_INDEX,
+        name: "index",
+        length: None,
+        encoding: "index_root (bytes32) || valid (bool) || position (u64, big-endian) || key",
+    },
+    RecordLayout {
+        tag: TAG_ELF_HASH,
+        name: "elf_hash",
+        length: Some(32),
+        encoding: "bytes32",
+    },
+    RecordLayout {
+        tag: TAG_TRIE,
+        name: "trie",
+        length: None,
+        encoding: "key length (u16, big-endian) || key || value",
+    },
+    RecordLayout {
+        tag: TAG_EIP712,
+        name: "eip712_digest",
+        length: Some(32),
+        encoding: "bytes32",
+    },
+    RecordLayout {
+        tag: TAG_LEAF_HIDDEN,
+        name: "leaf_hidden",
+        length: Some(0),
+        encoding: "empty",
+    },
+    RecordLayout {
+        tag: TAG_UPDATES,
+        name: "updates",
+        length: Some(37),
+        encoding: "status (u8) || count (u32, big-endian) || final_root (bytes32)",
+    },
+    RecordLayout {
+        tag: TAG_AUTHORIZATION,
+        name: "authorization",
+        length: Some(22),
+        encoding: "address (bytes20) || signature_valid (bool) || authorized (bool)",
+    },
+    RecordLayout {
+        tag: TAG_PROOF_STATS,
+        name: "proof_stats",
+        length: Some(8),
+        encoding: "leaves (u32, big-endian) || distinct_siblings (u32, big-endian)",
+    },
+    RecordLayout {
+        tag: TAG_NEIGHBORS,
+        name: "neighbors",
+        length: None,
+        encoding: "present (u8, bit 0 prev, bit 1 next) || ordered (bool) || prev (bytes32)? \
+                   || next (bytes32)?",
+    },
+    RecordLayout {
+        tag: TAG_KZG,
+        name: "kzg",
+        length: Some(105),
+        encoding: "commitment (compressed BN254 G1) || tau_g2 (compressed BN254 G2) \
+                   || point (u64, big-endian) || valid (bool)",
+    },
+    RecordLayout {
+        tag: TAG_CANONICALIZATION,
+        name: "canonicalization",
+        length: Some(1),
+        encoding: "u8: 1 json, 2 lowercase, 3 trim",
+    },
+    RecordLayout {
+        tag: TAG_FOREST,
+        name: "forest",
+        length: Some(45),
+        encoding:
+            "status (u8: 0 mapped, 1 out of range, 2 mismatch) || global_index (u64, big-endian) \
+                   || tree (u32, big-endian) || sizes_hash (bytes32)",
+    },
+    RecordLayout {
+        tag: TAG_BLS,
+        name: "bls",
+        length: Some(50),
+        encoding: "public_key (compressed BLS12-381 G1) || signed (u8: 0 leaf, 1 root) \
+                   || valid (bool)",
+    },
+    RecordLayout {
+        tag: TAG_VDF,
+        name: "vdf",
+        length: Some(297),
+        encoding: "challenge (bytes32) || iterations (u64, big-endian) \
+                   || output (256 bytes, big-endian) || valid (bool)",
+    },
+    RecordLayout {
+        tag: TAG_PROMOTIONS,
+        name: "promotions",
+        length: Some(9),
+        encoding: "depth (u8) || promoted (u64, big-endian, bit i set if the path has no sibling \
+                   at level i from the leaves)",
+    },
+    RecordLayout {
+        tag: TAG_CAP,
+        name: "cap",
+        length: Some(41),
+        encoding: "cap_level (u8, levels below the root) || position (u64, big-endian) \
+                   || node (bytes32)",
+    },
+    RecordLayout {
+        tag: TAG_PAIR,
+        name: "pair",
+        length: Some(49),
+        encoding: "status (u8: 0 ordered, 1 reversed, 2 equal, 3 invalid) || index_a (u64, \
+                   big-endian) || index_b (u64, big-endian) || leaf_b (bytes32)",
+    },
+    RecordLayout {
+        tag: TAG_BLOOM,
+        name: "bloom",
+        length: Some(42),
+        encoding: "filter_hash (bytes32) || bits (u64, big-endian) || hashes (u8) \
+                   || passed (bool, advisory)",
+    },
+    RecordLayout {
+        tag: TAG_TRANSFORM,
+        name: "transform",
+        length: Some(33),
+        encoding: "spec_hash (bytes32, sha256 of the comma-separated steps) || derived (bool)",
+    },
+    RecordLayout {
+        tag: TAG_CHECKPOINT,
+        name: "checkpoint",
+        length: Some(49),
+        encoding:
+            "checkpoint_root (bytes32) || index (u64, big-endian) || count (u64, big-endian) \
+                   || valid (bool)",
+    },
+    RecordLayout {
+        tag: TAG_DIAGNOSTICS,
+        name: "diagnostics",
+        length: None,
+        encoding: "(code (u8))*, in the order the guest recovered from each failure",
+    },
+    RecordLayout {
+        tag: TAG_REVEAL,
+        name: "reveal",
+        length: None,
+        encoding:
+            "phase (u8, 1 commit, 2 reveal) || matches (bool) || value (empty when committing)",
+    },
+    RecordLayout {
+        tag: TAG_RANGE,
+        name: "range",
+        length: None,
+        encoding: "first (u64, big-endian) || last (u64, big-endian) || count (u64, big-endian) \
+                   || verified (bool) || prefix (0 to 32 bytes)",
+    },
+    RecordLayout {
+        tag: TAG_IMT,
+        name: "imt",
+        length: Some(33),
+        encoding: "value (bytes32) || non_member (bool)",
+    },
+    RecordLayout {
+        tag: TAG_BATCH,
+        name: "batch",
+        length: None,
+        encoding: "(index (u64, big-endian) || leaf (bytes32))*, in ascending index order",
+    },
+    RecordLayout {
+        tag: TAG_BLOCKLIST,
+        name: "blocklist",
+        length: Some(41),
+        encoding: "root (bytes32) || total_leaves (u64, big-endian) || excluded (bool)",
+    },
+    RecordLayout {
+        tag: TAG_LEAF_COUNT,
+        name: "leaf_count",
+        length: Some(9),
+        encoding: "total_leaves (u64, big-endian) || verified (bool)",
+    },
+    RecordLayout {
+        tag: TAG_ERASURE,
+        name: "erasure",
+        length: Some(17),
+        encoding: "data_chunks (u16, big-endian) || total_chunks (u16, big-endian) || \
+                   chunk_size (u32, big-endian) || index (u64, big-endian) || sampled (bool)",
+    },
+    RecordLayout {
+        tag: TAG_PERSONALIZATION,
+        name: "personalization",
+        length: Some(32),
+        encoding: "sha256(personalization) (bytes32)",
+    },
+    RecordLayout {
+        tag: TAG_ABSENCE,
+        name: "absence",
+        length: Some(33),
+        encoding: "target (bytes32) || absent (bool)",
+    },
+    RecordLayout {
+        tag: TAG_AGGREGATE,
+        name: "aggregate",
+        length: None,
+        encoding: "total (u64, big-endian) || verified (u64, big-endian) || \
+                   entries_hash (bytes32) || failed index (u64, big-endian) per failed entry",
+    },
+    RecordLayout {
+        tag: TAG_SPARSE,
+        name: "sparse",
+        length: Some(65),
+        encoding: "key (bytes32) || value (bytes32) || is_member (bool)",
+    },
+    RecordLayout {
+        tag: TAG_APPEND,
+        name: "append",
+        length: Some(37),
+        encoding: "valid (bool) || count (u32, big-endian) || new_root (bytes32)",
+    },
+    RecordLayout {
+        tag: TAG_PREIMAGE,
+        name: "preimage",
+        length: Some(41),
+        encoding: "data_hash (bytes32, sha256 of the leaf data) || length (u64, big-endian) \
+                   || hashes_to_leaf (bool)",
+    },
+    RecordLayout {
+        tag: TAG_MMR,
+        name: "mmr",
+        length: Some(16),
+        encoding: "mmr_size (u64, big-endian) || position (u64, big-endian)",
+    },
+    RecordLayout {
+        tag: TAG_FLAGS,
+        name: "flags",
+        length: Some(1),
+        encoding: "u8, bit i the result of FLAG_LABELS[i] counting from the least significant; \
+                   replaces results",
+    },
+];
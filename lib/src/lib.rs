@@ -0,0 +1,66 @@
+//! Merkle primitives shared by the guest program and the host scripts.
+//!
+//! Anything that both sides must agree on byte-for-byte (node hashing, tree shape, the committed
+//! public values layout) lives here so that the guest verifier and the host tree builder cannot
+//! drift apart.
+//!
+//! The crate is `no_std` with `alloc`. The default `std` feature adds the `std::error::Error`
+//! impls, the Poseidon-based [`hybrid`] and [`hiding`] modules, whose `light-poseidon` dependency
+//! needs `std`, and [`inclusion`], which verifies hybrid proofs among others.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod abi;
+pub mod aggregate;
+pub mod append;
+pub mod authorization;
+pub mod batch;
+pub mod bitcoin;
+pub mod blake2b;
+pub mod blake3;
+pub mod blocklist;
+pub mod bloom;
+pub mod bls;
+pub mod checkpoint;
+pub mod count;
+pub mod eip712;
+pub mod erasure;
+pub mod forest;
+pub mod format;
+#[cfg(feature = "std")]
+pub mod hiding;
+#[cfg(feature = "std")]
+pub mod hybrid;
+pub mod imt;
+#[cfg(feature = "std")]
+pub mod inclusion;
+pub mod index;
+pub mod kary;
+pub mod keccak;
+pub mod kzg;
+pub mod layout;
+pub mod mmr;
+pub mod options;
+pub mod patricia;
+pub mod public_values;
+pub mod range;
+pub mod recursion;
+pub mod reveal;
+pub mod sha256;
+pub mod sorted;
+pub mod sparse;
+pub mod transform;
+pub mod tree;
+pub mod truncated;
+pub mod updates;
+pub mod vdf;
+
+pub use options::Options;
+pub use public_values::{PublicValues, Scheme};
+
+/// A 32-byte Merkle node.
+pub type Hash = [u8; 32];
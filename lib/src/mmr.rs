@@ -0,0 +1,307 @@
+//! Merkle Mountain Ranges, for append-only logs.
+//!
+//! An MMR over `n` leaves is a list of perfect binary trees, its peaks, one per set bit of `n` with
+//! the largest on the left. Appending a leaf adds a peak of height 0 and merges peaks of equal
+//! height, so a node never changes once it is written. Nodes are numbered in the order they are
+//! appended, and the MMR's size is its number of nodes, `2n - popcount(n)`:
+//!
+//! ```text
+//!         6
+//!       /   \
+//!      2     5     9
+//!     / \   / \   / \
+//!    0   1 3   4 7   8  10      size 11, 7 leaves, peaks 6, 9 and 10
+//! ```
+//!
+//! Leaves are taken as given and internal nodes hashed as in [`crate::tree`], `SHA-256(l || r)`.
+//! The root bags the peaks from the right and binds the size, so MMRs of different sizes never
+//! share a root:
+//!
+//! ```text
+//! bag  = SHA-256(peak_0 || SHA-256(peak_1 || ... SHA-256(peak_k-2 || peak_k-1)))
+//! root = SHA-256(size (8, big-endian) || bag)
+//! ```
+//!
+//! where a single peak is its own bag. A proof is the leaf's siblings up to its peak and the other
+//! peaks.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, tree, Hash};
+
+/// Returns the size of an MMR of `leaves` leaves.
+pub fn size(leaves: u64) -> u64 {
+    2 * leaves - leaves.count_ones() as u64
+}
+
+/// Returns the position of the leaf at `index`, the number of nodes appended before it.
+pub fn leaf_position(index: u64) -> u64 {
+    size(index)
+}
+
+/// Returns the height of every peak of an MMR of `size` nodes, left to right, or `None` if no MMR
+/// has that size.
+pub fn peak_heights(size: u64) -> Option<Vec<u32>> {
+    // A peak of height h has 2^(h+1) - 1 nodes, and the peaks' heights strictly decrease, so the
+    // largest peak that fits is always the next one.
+    let mut heights = Vec::new();
+    let mut remaining = size;
+    for height in (0..63).rev() {
+        let peak_size = (1u64 << (height + 1)) - 1;
+        if remaining >= peak_size {
+            heights.push(height);
+            remaining -= peak_size;
+        }
+    }
+    (remaining == 0).then_some(heights)
+}
+
+/// Returns the number of leaves of an MMR of `size` nodes, or `None` if no MMR has that size.
+pub fn leaf_count(size: u64) -> Option<u64> {
+    peak_heights(size).map(|heights| heights.iter().map(|&height| 1 << height).sum())
+}
+
+/// Returns the root of an MMR of `size` nodes with the given peaks, left to right.
+pub fn root(size: u64, peaks: &[Hash]) -> Hash {
+    let bag = peaks
+        .iter()
+        .rev()
+        .copied()
+        .reduce(|right, peak| tree::sha256_parent(&peak, &right))
+        .unwrap_or_default();
+    Sha256::new()
+        .chain_update(size.to_be_bytes())
+        .chain_update(bag)
+        .finalize()
+}
+
+/// An inclusion proof, passed to the guest after the root and the leaf.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MmrProof {
+    /// The leaf's position among the leaves, which is not its node position.
+    pub leaf_index: u64,
+    pub mmr_size: u64,
+    /// The leaf's siblings up to its peak, bottom-up.
+    pub siblings: Vec<Hash>,
+    /// Every peak but the leaf's, left to right.
+    pub peaks: Vec<Hash>,
+}
+
+impl MmrProof {
+    /// Returns the node position of the proven leaf.
+    pub fn position(&self) -> u64 {
+        leaf_position(self.leaf_index)
+    }
+
+    /// Returns the root of the MMR in which `leaf` is at [`MmrProof::leaf_index`], or `None` if the
+    /// proof does not fit an MMR of [`MmrProof::mmr_size`].
+    pub fn root(&self, leaf: &Hash) -> Option<Hash> {
+        let heights = peak_heights(self.mmr_size)?;
+        if self.peaks.len() + 1 != heights.len() {
+            return None;
+        }
+        let mut first = 0;
+        let (peak, height) = heights.iter().enumerate().find_map(|(peak, &height)| {
+            let leaves = 1u64 << height;
+            match self.leaf_index - first < leaves {
+                true => Some((peak, height)),
+                false => {
+                    first += leaves;
+                    None
+                }
+            }
+        })?;
+        if self.siblings.len() != height as usize {
+            return None;
+        }
+
+        let local = self.leaf_index - first;
+        let mut node = *leaf;
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            node = match local >> level & 1 {
+                0 => tree::sha256_parent(&node, sibling),
+                _ => tree::sha256_parent(sibling, &node),
+            };
+        }
+        let mut peaks = self.peaks.clone();
+        peaks.insert(peak, node);
+        Some(root(self.mmr_size, &peaks))
+    }
+}
+
+/// Verifies that `leaf` is in the MMR with the given root.
+pub fn verify(root: &Hash, leaf: &Hash, proof: &MmrProof) -> bool {
+    proof.root(leaf) == Some(*root)
+}
+
+/// An MMR built on the host, keeping every level of every peak for proof generation.
+pub struct Mmr {
+    /// The levels of each peak, left to right, each from its leaves up.
+    peaks: Vec<Vec<Vec<Hash>>>,
+    leaves: u64,
+}
+
+impl Mmr {
+    /// Builds the MMR that appending `leaves` in order produces.
+    pub fn from_leaves(leaves: &[Hash]) -> Self {
+        let mut peaks = Vec::new();
+        let mut rest = leaves;
+        while !rest.is_empty() {
+            let (peak, tail) = rest.split_at(1 << rest.len().ilog2());
+            peaks.push(tree::build_levels(peak.to_vec(), tree::sha256_parent));
+            rest = tail;
+        }
+        Self {
+            peaks,
+            leaves: leaves.len() as u64,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        size(self.leaves)
+    }
+
+    fn peak_hashes(&self) -> Vec<Hash> {
+        self.peaks
+            .iter()
+            .map(|levels| levels.last().unwrap()[0])
+            .collect()
+    }
+
+    pub fn root(&self) -> Hash {
+        root(self.size(), &self.peak_hashes())
+    }
+
+    /// Returns the proof of the leaf at `index`, or `None` if there is no such leaf.
+    pub fn proof(&self, index: u64) -> Option<MmrProof> {
+        let mut peaks = self.peak_hashes();
+        let mut first = 0;
+        for (peak, levels) in self.peaks.iter().enumerate() {
+            let leaves = levels[0].len() as u64;
+            if index - first < leaves {
+                peaks.remove(peak);
+                return Some(MmrProof {
+                    leaf_index: index,
+                    mmr_size: self.size(),
+                    siblings: tree::proof_path(levels, (index - first) as usize),
+                    peaks,
+                });
+            }
+            first += leaves;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn leaves(n: u8) -> Vec<Hash> {
+        (0..n).map(|i| [i; 32]).collect()
+    }
+
+    #[test]
+    fn sizes_and_peaks_follow_the_leaf_count() {
+        assert_eq!(size(7), 11);
+        assert_eq!(leaf_position(4), 7);
+        assert_eq!(peak_heights(11), Some(vec![2, 1, 0]));
+        assert_eq!(peak_heights(15), Some(vec![3]));
+        assert_eq!(peak_heights(0), Some(vec![]));
+        assert_eq!(leaf_count(11), Some(7));
+        assert_eq!(leaf_count(15), Some(8));
+        for invalid in [2, 5, 6, 9, 12] {
+            assert_eq!(peak_heights(invalid), None, "size {}", invalid);
+            assert_eq!(leaf_count(invalid), None, "size {}", invalid);
+        }
+    }
+
+    #[test]
+    fn root_bags_the_peaks_from_the_right() {
+        // The MMR of the module docs: peaks 6, 9 and 10.
+        let l = leaves(7);
+        let parent = tree::sha256_parent;
+        let peak_6 = parent(&parent(&l[0], &l[1]), &parent(&l[2], &l[3]));
+        let peak_9 = parent(&l[4], &l[5]);
+        let bag = parent(&peak_6, &parent(&peak_9, &l[6]));
+        let expected = Sha256::new()
+            .chain_update(11u64.to_be_bytes())
+            .chain_update(bag)
+            .finalize();
+        assert_eq!(Mmr::from_leaves(&l).root(), expected);
+        assert_eq!(root(11, &[peak_6, peak_9, l[6]]), expected);
+        // A single peak is its own bag.
+        assert_eq!(
+            Mmr::from_leaves(&l[..1]).root(),
+            Sha256::new()
+                .chain_update(1u64.to_be_bytes())
+                .chain_update(l[0])
+                .finalize()
+        );
+    }
+
+    #[test]
+    fn proofs_verify_for_every_leaf() {
+        for n in 1..=20 {
+            let l = leaves(n);
+            let mmr = Mmr::from_leaves(&l);
+            for (index, leaf) in l.iter().enumerate() {
+                let proof = mmr.proof(index as u64).unwrap();
+                assert!(verify(&mmr.root(), leaf, &proof), "leaf {} of {}", index, n);
+            }
+            assert_eq!(mmr.proof(n as u64), None);
+        }
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let l = leaves(11);
+        let mmr = Mmr::from_leaves(&l);
+        let root = mmr.root();
+        let proof = mmr.proof(9).unwrap();
+        assert!(verify(&root, &l[9], &proof));
+
+        // Another leaf, or the same leaf at another index.
+        assert!(!verify(&root, &l[8], &proof));
+        let moved = MmrProof {
+            leaf_index: 8,
+            ..proof.clone()
+        };
+        assert!(!verify(&root, &l[9], &moved));
+        // A corrupted sibling or peak, or peaks out of order.
+        let mut corrupted = proof.clone();
+        corrupted.siblings[0][0] ^= 1;
+        assert!(!verify(&root, &l[9], &corrupted));
+        let mut corrupted = proof.clone();
+        corrupted.peaks[0][0] ^= 1;
+        assert!(!verify(&root, &l[9], &corrupted));
+        let mut reordered = proof.clone();
+        reordered.peaks.reverse();
+        assert!(!verify(&root, &l[9], &reordered));
+        // A size the MMR does not have, or that no MMR has.
+        for mmr_size in [size(12), 12] {
+            let resized = MmrProof {
+                mmr_size,
+                ..proof.clone()
+            };
+            assert!(!verify(&root, &l[9], &resized));
+        }
+        // Too many or too few siblings or peaks.
+        let mut extended = proof.clone();
+        extended.siblings.push([0; 32]);
+        assert!(!verify(&root, &l[9], &extended));
+        let mut truncated = proof.clone();
+        truncated.peaks.pop();
+        assert!(!verify(&root, &l[9], &truncated));
+        // An index past the last leaf.
+        let past = MmrProof {
+            leaf_index: 11,
+            ..proof
+        };
+        assert_eq!(past.root(&l[9]), None);
+    }
+}
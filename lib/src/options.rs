@@ -0,0 +1,129 @@
+//! Optional checks and commitments, read by the guest after the core inputs.
+//!
+//! The host always writes an [`Options`] value, so adding a feature only means adding a field here.
+//! Each enabled option emits the matching extension record described in
+//! [`crate::public_values`].
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aggregate::AggregateProofs,
+    append::Append,
+    authorization::Authorization,
+    batch::LeafBatch,
+    blocklist::BlocklistExclusion,
+    bloom::BloomFilter,
+    bls::BlsSignature,
+    checkpoint::CheckpointProof,
+    count::CountProof,
+    erasure::ChunkSample,
+    forest::ForestLookup,
+    imt::ImtQuery,
+    index::IndexLookup,
+    kzg::Opening,
+    public_values::Canonicalization,
+    range::RangeCount,
+    reveal::Reveal,
+    sorted::{Absence, Neighbors, PairedLeaf},
+    transform::TransformedLeaf,
+    vdf::VdfProof,
+    Hash,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Options {
+    /// Additionally commit whether the proven leaf equals this value.
+    pub expect_leaf: Option<Hash>,
+    /// Additionally commit the leaf's position as a fixed-point fraction of the tree size.
+    pub commit_ordinal: bool,
+    /// Additionally commit the root byte-reversed.
+    pub commit_root_reversed: bool,
+    /// Additionally commit every check result under its four-byte label.
+    pub tagged_results: bool,
+    /// Additionally commit every check result as one bit of a bitfield, in place of the labelled
+    /// list of `tagged_results`.
+    pub pack_flags: bool,
+    /// Additionally verify that this lookup maps its key to the proven leaf's index.
+    pub index_lookup: Option<IndexLookup>,
+    /// Additionally commit this value as the SHA-256 of the program ELF.
+    ///
+    /// The guest cannot read its own ELF, so the value is supplied by the host and committed as-is.
+    /// It is only as trustworthy as the verifying key it is checked together with.
+    pub elf_hash: Option<Hash>,
+    /// Additionally commit the EIP-712 digest of the core values under this domain separator.
+    pub eip712_domain: Option<Hash>,
+    /// Commit a hiding commitment to the leaf with this randomness in place of the raw leaf.
+    pub leaf_randomness: Option<Hash>,
+    /// Additionally replace the leaf with each of these values in turn and commit the final root.
+    pub updates: Option<Vec<Hash>>,
+    /// Additionally verify this signature over the leaf and commit the signer's address.
+    pub authorization: Option<Authorization>,
+    /// Additionally commit the number of leaves proven and of distinct sibling hashes in the proof.
+    pub commit_proof_stats: bool,
+    /// Additionally prove these neighbors of the leaf in a sorted tree and commit their ordering.
+    pub neighbors: Option<Neighbors>,
+    /// Additionally verify that this polynomial commitment opens to the leaf at its index.
+    pub kzg_opening: Option<Opening>,
+    /// Additionally commit that the host applied this normalization to leaf data before hashing.
+    pub canonicalization: Option<Canonicalization>,
+    /// Additionally map this global forest index to a tree and check it against the proven leaf.
+    pub forest: Option<ForestLookup>,
+    /// Additionally verify this BLS signature over the leaf or root and commit the public key.
+    pub bls_signature: Option<BlsSignature>,
+    /// Additionally verify this VDF evaluation over the proof's challenge and commit its output.
+    pub vdf: Option<VdfProof>,
+    /// Additionally commit the levels at which the leaf's path is promoted without a sibling.
+    pub commit_promotions: bool,
+    /// Additionally commit the node this many levels below the root that the leaf descends from.
+    pub cap_level: Option<u8>,
+    /// Additionally prove this second leaf and commit whether it comes after the proven leaf.
+    pub pair: Option<PairedLeaf>,
+    /// Additionally commit whether the leaf passes this bloom filter, as an advisory hint.
+    pub bloom_filter: Option<BloomFilter>,
+    /// Additionally re-derive the leaf from its data with a hash chain and commit the result.
+    pub leaf_transform: Option<TransformedLeaf>,
+    /// Additionally prove that the root is in this checkpoint tree and commit its position.
+    pub checkpoint: Option<CheckpointProof>,
+    /// Additionally commit that the leaf is a commitment in the commit phase of a commit-reveal.
+    pub commit_phase: bool,
+    /// Additionally check that this salt and value open the leaf and commit the value. Takes
+    /// precedence over `commit_phase`.
+    pub reveal: Option<Reveal>,
+    /// Additionally verify every leaf of this range and commit how many satisfy its predicate.
+    pub range_count: Option<RangeCount>,
+    /// Additionally check that the leaf is this query's low leaf and commit the value's absence.
+    pub imt: Option<ImtQuery>,
+    /// Additionally verify these leaves, which include the proven leaf, with one multi-proof and
+    /// make `is_valid` cover all of them.
+    pub batch: Option<LeafBatch>,
+    /// Panic unless the proof is valid, and commit only the root and leaf.
+    pub strict: bool,
+    /// Commit only the root, leaf and validity, packed into BN254 field elements.
+    pub field_packed: bool,
+    /// Additionally prove that the leaf is absent from this blocklist and commit the result.
+    pub blocklist: Option<BlocklistExclusion>,
+    /// Additionally prove the count leaf of a counted tree and commit the verified leaf count.
+    pub leaf_count: Option<CountProof>,
+    /// Additionally check that this chunk of an erasure-coded blob is the proven leaf and commit
+    /// the sample with the coding parameters.
+    pub erasure: Option<ChunkSample>,
+    /// Additionally prove that this target falls strictly between two adjacent leaves of the
+    /// sorted tree and commit whether it is absent.
+    pub absence: Option<Absence>,
+    /// Additionally verify each of these inclusion proofs against the root and commit how many
+    /// verified and which did not.
+    pub aggregate: Option<AggregateProofs>,
+    /// Run every SHA-256 of the program in software instead of on the precompiles, to measure the
+    /// precompiles' savings. Commits nothing.
+    pub software_sha256: bool,
+    /// Additionally append these leaves to the tree whose frontier is given and commit the new
+    /// root.
+    pub append: Option<Append>,
+    /// Commit the regular public values as `abi.encode(PublicValuesStruct)`.
+    pub abi_encoded: bool,
+    /// The proven leaf's data, hashed into the leaf by the program with the scheme's leaf hash and
+    /// committed by its SHA-256.
+    pub preimage: Option<Vec<u8>>,
+}
@@ -0,0 +1,316 @@
+//! Merkle-Patricia trie proofs, as used for Ethereum state and storage.
+//!
+//! A trie node is an RLP list of either 17 items (a branch: one child per nibble, then a value) or
+//! 2 items (an extension or leaf: a hex-prefix encoded path, then a child or value). A child is
+//! referenced by the Keccak-256 of its encoding, or embedded inline when that encoding is shorter
+//! than 32 bytes. The root is always the hash of the root node.
+//!
+//! A proof is the list of hash-referenced nodes on the path from the root to the key, root first,
+//! exactly as returned by `eth_getProof`. Keys are walked as raw nibble paths: for Ethereum's
+//! secure tries, pass `keccak256(address)` or `keccak256(slot)` as the key. Values are returned as
+//! stored, e.g. the RLP-encoded account for the state trie.
+
+use alloc::vec::Vec;
+
+use sha3::{Digest, Keccak256};
+
+use crate::Hash;
+
+/// Why a proof could not be walked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// A node is not valid RLP or not a valid trie node.
+    InvalidNode,
+    /// A node's hash does not match its reference in the parent, or the root.
+    HashMismatch,
+    /// The proof ended before the walk did.
+    MissingNode,
+    /// The proof contains nodes after the walk ended.
+    UnusedNode,
+}
+
+/// Hashes data with Keccak-256.
+pub fn keccak256(data: &[u8]) -> Hash {
+    Keccak256::digest(data).into()
+}
+
+/// Looks up `key` in the trie with the given root.
+///
+/// Returns the value if the proof shows the key is present, `None` if it shows the key is absent,
+/// and an error if the proof does not connect to the root.
+pub fn get(root: Hash, key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, ProofError> {
+    let path: Vec<u8> = key
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect();
+    let mut path = &path[..];
+    let mut nodes = proof.iter();
+    let mut next = Child::Hash(root);
+
+    let value = loop {
+        let node = match next {
+            Child::Hash(hash) => {
+                let node = nodes.next().ok_or(ProofError::MissingNode)?;
+                if keccak256(node) != hash {
+                    return Err(ProofError::HashMismatch);
+                }
+                &node[..]
+            }
+            Child::Inline(node) => node,
+            Child::Empty => break None,
+        };
+
+        let items = list(node)?;
+        match items.as_slice() {
+            [children @ .., value] if children.len() == 16 => match path.split_first() {
+                None => break Some(string(value)?.to_vec()).filter(|value| !value.is_empty()),
+                Some((&nibble, rest)) => {
+                    next = child(children[nibble as usize])?;
+                    path = rest;
+                }
+            },
+            [encoded_path, item] => {
+                let (is_leaf, node_path) = hex_prefix(string(encoded_path)?)?;
+                if is_leaf {
+                    let value = string(item)?;
+                    break (path == node_path).then(|| value.to_vec());
+                }
+                match path.strip_prefix(node_path.as_slice()) {
+                    Some(rest) if !node_path.is_empty() => {
+                        next = child(item)?;
+                        path = rest;
+                    }
+                    Some(_) => return Err(ProofError::InvalidNode),
+                    None => break None,
+                }
+            }
+            _ => return Err(ProofError::InvalidNode),
+        }
+    };
+
+    if nodes.next().is_some() {
+        return Err(ProofError::UnusedNode);
+    }
+    Ok(value)
+}
+
+/// Returns whether the proof shows that `key` maps to `value`.
+pub fn verify(root: Hash, key: &[u8], value: &[u8], proof: &[Vec<u8>]) -> bool {
+    get(root, key, proof).is_ok_and(|found| found.as_deref() == Some(value))
+}
+
+/// A reference from a node to one of its children.
+enum Child<'a> {
+    Hash(Hash),
+    Inline(&'a [u8]),
+    Empty,
+}
+
+fn child(item: &[u8]) -> Result<Child<'_>, ProofError> {
+    if item.first().is_some_and(|&prefix| prefix >= 0xc0) {
+        return Ok(Child::Inline(item));
+    }
+    match string(item)? {
+        [] => Ok(Child::Empty),
+        hash => hash
+            .try_into()
+            .map(Child::Hash)
+            .map_err(|_| ProofError::InvalidNode),
+    }
+}
+
+/// Decodes a hex-prefix encoded path into its leaf flag and nibbles.
+fn hex_prefix(encoded: &[u8]) -> Result<(bool, Vec<u8>), ProofError> {
+    let (&first, rest) = encoded.split_first().ok_or(ProofError::InvalidNode)?;
+    let flag = first >> 4;
+    if flag > 3 || (flag & 1 == 0 && first & 0x0f != 0) {
+        return Err(ProofError::InvalidNode);
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+    Ok((flag >= 2, nibbles))
+}
+
+/// Splits one RLP item off the front of `input`, returning the whole item and the remainder.
+fn item(input: &[u8]) -> Result<(&[u8], &[u8]), ProofError> {
+    let (&prefix, rest) = input.split_first().ok_or(ProofError::InvalidNode)?;
+    let end = match prefix {
+        0x00..=0x7f => 1,
+        0x80..=0xb7 => 1 + (prefix - 0x80) as usize,
+        0xc0..=0xf7 => 1 + (prefix - 0xc0) as usize,
+        0xb8..=0xbf | 0xf8..=0xff => {
+            let len_of_len = (prefix & 0x07) as usize + 1;
+            let len_bytes = rest.get(..len_of_len).ok_or(ProofError::InvalidNode)?;
+            len_bytes
+                .iter()
+                .try_fold(0usize, |len, &byte| {
+                    len.checked_mul(256)?.checked_add(byte as usize)
+                })
+                .and_then(|len| len.checked_add(1 + len_of_len))
+                .ok_or(ProofError::InvalidNode)?
+        }
+    };
+    if input.len() < end {
+        return Err(ProofError::InvalidNode);
+    }
+    Ok(input.split_at(end))
+}
+
+/// Returns the payload of an RLP item and whether it is a list.
+fn payload(item: &[u8]) -> (bool, &[u8]) {
+    match item[0] {
+        0x00..=0x7f => (false, item),
+        0x80..=0xb7 => (false, &item[1..]),
+        0xc0..=0xf7 => (true, &item[1..]),
+        prefix => (prefix >= 0xf8, &item[2 + (prefix & 0x07) as usize..]),
+    }
+}
+
+/// Decodes a string item into its bytes.
+fn string(item: &[u8]) -> Result<&[u8], ProofError> {
+    match payload(item) {
+        (false, bytes) => Ok(bytes),
+        (true, _) => Err(ProofError::InvalidNode),
+    }
+}
+
+/// Decodes a node, which must be exactly one list item, into its raw items.
+fn list(node: &[u8]) -> Result<Vec<&[u8]>, ProofError> {
+    let (node, rest) = item(node)?;
+    let (true, mut payload) = payload(node) else {
+        return Err(ProofError::InvalidNode);
+    };
+    if !rest.is_empty() {
+        return Err(ProofError::InvalidNode);
+    }
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (item, rest) = item(payload)?;
+        items.push(item);
+        payload = rest;
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn rlp_string(bytes: &[u8]) -> Vec<u8> {
+        match bytes {
+            [byte] if *byte < 0x80 => vec![*byte],
+            _ => {
+                assert!(bytes.len() < 56);
+                [&[0x80 + bytes.len() as u8], bytes].concat()
+            }
+        }
+    }
+
+    fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        match payload.len() {
+            len if len < 56 => [vec![0xc0 + len as u8], payload].concat(),
+            len => [vec![0xf8, len as u8], payload].concat(),
+        }
+    }
+
+    /// References a child node, inline when its encoding is shorter than 32 bytes.
+    fn reference(node: &[u8]) -> Vec<u8> {
+        match node.len() {
+            len if len < 32 => node.to_vec(),
+            _ => rlp_string(&keccak256(node)),
+        }
+    }
+
+    fn branch(children: &[(usize, Vec<u8>)], value: &[u8]) -> Vec<u8> {
+        let mut items = vec![rlp_string(&[]); 17];
+        for (nibble, node) in children {
+            items[*nibble] = reference(node);
+        }
+        items[16] = rlp_string(value);
+        rlp_list(&items)
+    }
+
+    /// The nodes of the trie of `doe`, `dog` and `dogglesworth` from Ethereum's trie tests: an
+    /// extension over `646f6`, a branch to the inline `doe` leaf, and a branch holding `dog` with
+    /// the inline `dogglesworth` leaf below it.
+    fn dogs() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let doe = rlp_list(&[rlp_string(&[0x20]), rlp_string(b"reindeer")]);
+        let dogglesworth = rlp_list(&[
+            rlp_string(&hex::decode("376c6573776f727468").unwrap()),
+            rlp_string(b"cat"),
+        ]);
+        let dog = branch(&[(6, dogglesworth)], b"puppy");
+        let top = branch(&[(5, doe), (7, dog.clone())], b"");
+        let extension = rlp_list(&[rlp_string(&[0x16, 0x46, 0xf6]), reference(&top)]);
+        (extension, top, dog)
+    }
+
+    #[test]
+    fn root_matches_the_ethereum_trie_test() {
+        let (extension, ..) = dogs();
+        assert_eq!(
+            hex::encode(keccak256(&extension)),
+            "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+        );
+    }
+
+    #[test]
+    fn present_and_absent_keys_are_walked() {
+        let (extension, top, dog) = dogs();
+        let root = keccak256(&extension);
+        let to_dog = vec![extension.clone(), top.clone(), dog.clone()];
+
+        assert!(verify(root, b"doe", b"reindeer", &to_dog[..2]));
+        assert!(verify(root, b"dog", b"puppy", &to_dog));
+        assert!(verify(root, b"dogglesworth", b"cat", &to_dog));
+        // Diverging from the extension, stopping inside it, an empty branch slot, and a leaf with
+        // another path.
+        assert_eq!(get(root, b"dot", &to_dog[..1]), Ok(None));
+        assert_eq!(get(root, b"do", &to_dog[..1]), Ok(None));
+        assert_eq!(get(root, b"dogs", &to_dog), Ok(None));
+        assert_eq!(get(root, b"doggy", &to_dog), Ok(None));
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let (extension, top, dog) = dogs();
+        let root = keccak256(&extension);
+        let proof = vec![extension.clone(), top.clone(), dog.clone()];
+
+        assert!(!verify(root, b"dog", b"kitten", &proof));
+        assert!(!verify(keccak256(&top), b"dog", b"puppy", &proof));
+        let mut corrupted = proof.clone();
+        *corrupted[2].last_mut().unwrap() ^= 1;
+        assert_eq!(get(root, b"dog", &corrupted), Err(ProofError::HashMismatch));
+        assert_eq!(get(root, b"dog", &proof[..2]), Err(ProofError::MissingNode));
+        assert_eq!(get(root, b"doe", &proof), Err(ProofError::UnusedNode));
+        // Nodes that hash correctly but are not trie nodes.
+        for node in [
+            rlp_string(b"node"),
+            rlp_list(&[rlp_string(b"a")]),
+            vec![0xc5, 0x80],
+        ] {
+            assert_eq!(
+                get(keccak256(&node), b"dog", &[node]),
+                Err(ProofError::InvalidNode)
+            );
+        }
+    }
+
+    #[test]
+    fn hex_prefix_decodes_every_flag() {
+        assert_eq!(hex_prefix(&[0x00, 0x12]), Ok((false, vec![1, 2])));
+        assert_eq!(hex_prefix(&[0x13, 0x45]), Ok((false, vec![3, 4, 5])));
+        assert_eq!(hex_prefix(&[0x20]), Ok((true, vec![])));
+        assert_eq!(hex_prefix(&[0x3a]), Ok((true, vec![0xa])));
+        assert_eq!(hex_prefix(&[0x01]), Err(ProofError::InvalidNode));
+        assert_eq!(hex_prefix(&[0x40]), Err(ProofError::InvalidNode));
+        assert_eq!(hex_prefix(&[]), Err(ProofError::InvalidNode));
+    }
+}
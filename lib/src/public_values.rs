@@ -0,0 +1,2158 @@
+//! The public values committed by the guest program.
+//!
+//! Every proof starts with a fixed 81-byte core:
+//!
+//! | offset | length | field                         |
+//! |--------|--------|-------------------------------|
+//! | 0      | 32     | `root`                        |
+//! | 32     | 32     | `leaf`                        |
+//! | 64     | 1      | `is_valid`                    |
+//! | 65     | 8      | `leaf_index`, big-endian u64   |
+//! | 73     | 8      | `total_leaves`, big-endian u64 |
+//!
+//! Binding the leaf index and tree size in every proof keeps a proof from being claimed for another
+//! position or tree size. The trie and sparse modes, whose proofs do not locate a leaf by index,
+//! commit zero for both.
+//!
+//! Optional features append extension records after the core, each framed as
+//! `tag (1 byte) || length (2 bytes, big-endian) || payload`. Records are only emitted when their
+//! feature is in use, so a default proof commits exactly the 81-byte core.
+//!
+//! A strict proof, of a guest that refuses invalid proofs, commits only
+//! `root || leaf || leaf_index || total_leaves`, [`STRICT_LEN`] bytes, and nothing else. Its
+//! `is_valid` is implied.
+//!
+//! A field-packed proof instead commits the core as [`FIELD_PACKED_LEN`] bytes, seven 32-byte
+//! words, each a big-endian integer below 2^128 and so a canonical element of the BN254 scalar
+//! field:
+//!
+//! | word | offset | value                                       |
+//! |------|--------|---------------------------------------------|
+//! | 0    | 0      | `root[0..16]`, the high half of the root    |
+//! | 1    | 32     | `root[16..32]`, the low half of the root    |
+//! | 2    | 64     | `leaf_index`                                |
+//! | 3    | 96     | `total_leaves`                              |
+//! | 4    | 128    | `leaf[0..16]`                               |
+//! | 5    | 160    | `leaf[16..32]`                              |
+//! | 6    | 192    | `is_valid`, 0 or 1                          |
+//!
+//! Each half is stored in the low 16 bytes of its word, after 16 zero bytes, and each count in the
+//! low 8 bytes, so every word is already an ABI `uint256`. Bytes 64 to 87 are zero, while a regular
+//! proof longer than the core has its first record tag at byte 81 and tag `0x00` is never
+//! assigned, so the two layouts cannot be confused.
+//!
+//! An ABI-encoded proof commits the regular bytes as `abi.encode(PublicValuesStruct)` instead, see
+//! [`crate::abi`]. Nothing marks that layout, so it is parsed with [`PublicValues::from_abi_bytes`]
+//! rather than [`PublicValues::from_bytes`].
+//!
+//! Every new field or record must also be described in [`crate::layout`].
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{abi, bls::SignedValue, Hash};
+
+/// Length of the fixed core that precedes any extension records.
+pub const CORE_LEN: usize = 81;
+
+/// Length of the public values of a strict proof, `root || leaf || leaf_index || total_leaves`.
+pub const STRICT_LEN: usize = 80;
+
+/// Length of the public values of a field-packed proof, seven 32-byte field elements.
+pub const FIELD_PACKED_LEN: usize = 224;
+
+/// Extension tag carrying the tree scheme as a single byte. Omitted for [`Scheme::Sha256`].
+pub const TAG_SCHEME: u8 = 0x01;
+
+/// Extension tag carrying the node length in bytes of a [`Scheme::TruncatedSha256`] tree.
+pub const TAG_HASH_BYTES: u8 = 0x02;
+
+/// Extension tag carrying whether the leaf matched [`crate::Options::expect_leaf`], as one byte.
+pub const TAG_LEAF_MATCH: u8 = 0x03;
+
+/// Extension tag carrying an [`Ordinal`] as `value (4) || scale (4)`, both big-endian.
+pub const TAG_ORDINAL: u8 = 0x04;
+
+/// Extension tag carrying the root with its bytes in reverse order (32 bytes).
+pub const TAG_ROOT_REVERSED: u8 = 0x05;
+
+/// Extension tag carrying the arity of a [`Scheme::Kary`] tree as one byte.
+pub const TAG_ARITY: u8 = 0x06;
+
+/// Extension tag carrying labelled check results as consecutive `label (4) || result (1)` entries.
+///
+/// Labels are four ASCII bytes, entries are sorted by label and each label appears at most once.
+/// The entry count is the record length divided by five.
+pub const TAG_RESULTS: u8 = 0x07;
+
+/// Extension tag carrying an [`IndexCommitment`] as
+/// `index_root (32) || valid (1) || position (8, big-endian) || key`.
+///
+/// The key is the remainder of the record.
+pub const TAG_INDEX: u8 = 0x08;
+
+/// Extension tag carrying the host-supplied SHA-256 of the program ELF (32 bytes).
+pub const TAG_ELF_HASH: u8 = 0x09;
+
+/// Extension tag carrying a [`TrieEntry`] as `key length (2, big-endian) || key || value`.
+pub const TAG_TRIE: u8 = 0x0a;
+
+/// Extension tag carrying the EIP-712 digest of the core values (32 bytes), see
+/// [`crate::eip712`].
+pub const TAG_EIP712: u8 = 0x0b;
+
+/// Extension tag, with an empty payload, marking that the core `leaf` is a hiding commitment to the
+/// leaf rather than the leaf itself, see [`crate::hiding`].
+pub const TAG_LEAF_HIDDEN: u8 = 0x0c;
+
+/// Extension tag carrying an [`UpdateChain`] as
+/// `status (1) || count (4, big-endian) || final_root (32)`.
+pub const TAG_UPDATES: u8 = 0x0d;
+
+/// Extension tag carrying a [`SignerCommitment`] as
+/// `address (20) || signature_valid (1) || authorized (1)`.
+pub const TAG_AUTHORIZATION: u8 = 0x0e;
+
+/// Extension tag carrying [`ProofStats`] as `leaves (4) || distinct_siblings (4)`, both big-endian.
+pub const TAG_PROOF_STATS: u8 = 0x0f;
+
+/// Extension tag carrying a [`NeighborCommitment`] as
+/// `present (1) || ordered (1) || prev (32, if present) || next (32, if present)`, where bit 0 of
+/// `present` marks `prev` and bit 1 marks `next`.
+pub const TAG_NEIGHBORS: u8 = 0x10;
+
+/// Extension tag carrying a [`KzgCommitment`] as
+/// `commitment (32) || tau_g2 (64) || point (8, big-endian) || valid (1)`.
+pub const TAG_KZG: u8 = 0x11;
+
+/// Extension tag carrying the [`Canonicalization`] applied to leaf data as a single byte.
+pub const TAG_CANONICALIZATION: u8 = 0x12;
+
+/// Extension tag carrying a [`ForestCommitment`] as
+/// `status (1) || global_index (8, big-endian) || tree (4, big-endian) || sizes_hash (32)`.
+pub const TAG_FOREST: u8 = 0x13;
+
+/// Extension tag carrying a [`BlsCommitment`] as
+/// `public_key (48) || signed (1, 0 leaf or 1 root) || valid (1)`.
+pub const TAG_BLS: u8 = 0x14;
+
+/// Extension tag carrying a [`VdfCommitment`] as
+/// `challenge (32) || iterations (8, big-endian) || output (256) || valid (1)`.
+pub const TAG_VDF: u8 = 0x15;
+
+/// Extension tag carrying [`Promotions`] as `depth (1) || promoted (8, big-endian)`.
+pub const TAG_PROMOTIONS: u8 = 0x16;
+
+/// Extension tag carrying a [`CapNode`] as
+/// `cap_level (1) || position (8, big-endian) || node (32)`.
+pub const TAG_CAP: u8 = 0x17;
+
+/// Extension tag carrying a [`PairCommitment`] as
+/// `status (1) || index_a (8, big-endian) || index_b (8, big-endian) || leaf_b (32)`.
+pub const TAG_PAIR: u8 = 0x18;
+
+/// Extension tag carrying a [`BloomCommitment`] as
+/// `filter_hash (32) || bits (8, big-endian) || hashes (1) || passed (1)`.
+pub const TAG_BLOOM: u8 = 0x19;
+
+/// Extension tag carrying a [`TransformCommitment`] as `spec_hash (32) || derived (1)`.
+pub const TAG_TRANSFORM: u8 = 0x1a;
+
+/// Extension tag carrying a [`CheckpointCommitment`] as
+/// `checkpoint_root (32) || index (8, big-endian) || count (8, big-endian) || valid (1)`.
+pub const TAG_CHECKPOINT: u8 = 0x1b;
+
+/// Extension tag carrying the [`Diagnostic`] codes of failures the guest recovered from, one byte
+/// each, in the order they occurred.
+pub const TAG_DIAGNOSTICS: u8 = 0x1c;
+
+/// Extension tag carrying a [`RevealCommitment`] as `phase (1) || matches (1) || value`, with an
+/// empty value in the commit phase.
+pub const TAG_REVEAL: u8 = 0x1d;
+
+/// Extension tag carrying a [`RangeCommitment`] as
+/// `first (8, big-endian) || last (8, big-endian) || count (8, big-endian) || verified (1) || prefix`.
+pub const TAG_RANGE: u8 = 0x1e;
+
+/// Extension tag carrying the check results as a one-byte bitfield, in place of [`TAG_RESULTS`].
+///
+/// Bit `i`, counting from the least significant, is the result of label [`FLAG_LABELS`]`[i]`. A
+/// check that did not run reads as 0, like one that failed, so a verifier relies on the checks it
+/// asked for.
+pub const TAG_FLAGS: u8 = 0x1f;
+
+/// Extension tag carrying an [`ImtCommitment`] as `value (32) || non_member (1)`.
+pub const TAG_IMT: u8 = 0x20;
+
+/// Extension tag carrying the leaves of a [`crate::batch`] as consecutive
+/// `index (8, big-endian) || leaf (32)` entries in ascending index order.
+pub const TAG_BATCH: u8 = 0x21;
+
+/// Extension tag carrying a [`BlocklistCommitment`] as
+/// `root (32) || total_leaves (8, big-endian) || excluded (1)`.
+pub const TAG_BLOCKLIST: u8 = 0x23;
+
+/// Extension tag carrying a [`LeafCount`] as `total_leaves (8, big-endian) || verified (1)`.
+pub const TAG_LEAF_COUNT: u8 = 0x24;
+
+/// Extension tag carrying an [`ErasureSampleCommitment`] as
+/// `data_chunks (2) || total_chunks (2) || chunk_size (4) || index (8) || sampled (1)`, all
+/// big-endian.
+pub const TAG_ERASURE: u8 = 0x25;
+
+/// Extension tag carrying the SHA-256 of the personalization of a [`Scheme::Blake2b`] tree.
+pub const TAG_PERSONALIZATION: u8 = 0x26;
+
+/// Extension tag carrying an [`AbsenceCommitment`] as `target (32) || absent (1)`.
+pub const TAG_ABSENCE: u8 = 0x27;
+
+/// Extension tag carrying an [`AggregateCommitment`] as
+/// `total (8) || verified (8) || entries_hash (32)` followed by the 8-byte index of each failed
+/// entry, all big-endian.
+pub const TAG_AGGREGATE: u8 = 0x28;
+
+/// Extension tag carrying a [`SparseEntry`] as `key (32) || value (32) || is_member (1)`.
+pub const TAG_SPARSE: u8 = 0x29;
+
+/// Extension tag carrying an [`AppendCommitment`] as
+/// `valid (1) || count (4, big-endian) || new_root (32)`.
+pub const TAG_APPEND: u8 = 0x2a;
+
+/// Extension tag carrying a [`PreimageCommitment`] as
+/// `data_hash (32) || length (8, big-endian) || hashes_to_leaf (1)`.
+pub const TAG_PREIMAGE: u8 = 0x2b;
+
+/// Extension tag carrying an [`MmrEntry`] as `mmr_size (8) || position (8)`, both big-endian.
+pub const TAG_MMR: u8 = 0x2c;
+
+/// Result label for Merkle inclusion, mirroring `is_valid`.
+pub const RESULT_INCLUSION: [u8; 4] = *b"incl";
+
+/// Result label for the [`crate::Options::expect_leaf`] comparison.
+pub const RESULT_LEAF_MATCH: [u8; 4] = *b"leaf";
+
+/// Result label for the secondary index lookup, see [`crate::index`].
+pub const RESULT_INDEX: [u8; 4] = *b"indx";
+
+/// Result label for the leaf's authorization signature, see [`crate::authorization`].
+pub const RESULT_AUTHORIZATION: [u8; 4] = *b"auth";
+
+/// Result label for the neighbor ordering check, see [`crate::sorted`].
+pub const RESULT_ORDERED: [u8; 4] = *b"sort";
+
+/// Result label for the global forest index mapping, see [`crate::forest`].
+pub const RESULT_FOREST: [u8; 4] = *b"frst";
+
+/// Result label for the BLS signature check, see [`crate::bls`].
+pub const RESULT_BLS: [u8; 4] = *b"bls_";
+
+/// Result label for the VDF output check, see [`crate::vdf`].
+pub const RESULT_VDF: [u8; 4] = *b"vdf_";
+
+/// Result label for the ordered pair check, set when the pair's status is
+/// [`PairStatus::Ordered`].
+pub const RESULT_PAIR: [u8; 4] = *b"pair";
+
+/// Result label for the advisory bloom filter pre-check, see [`crate::bloom`].
+pub const RESULT_BLOOM: [u8; 4] = *b"blom";
+
+/// Result label for the leaf derivation check, see [`crate::transform`].
+pub const RESULT_TRANSFORM: [u8; 4] = *b"xfrm";
+
+/// Result label for the root's checkpoint inclusion, see [`crate::checkpoint`].
+pub const RESULT_CHECKPOINT: [u8; 4] = *b"ckpt";
+
+/// Result label for the reveal check, see [`crate::reveal`]. Only set in the reveal phase.
+pub const RESULT_REVEAL: [u8; 4] = *b"revl";
+
+/// Result label for verifying every leaf of the counted range, see [`crate::range`].
+pub const RESULT_RANGE: [u8; 4] = *b"rnge";
+
+/// Result label for the indexed tree non-membership check, see [`crate::imt`].
+pub const RESULT_IMT: [u8; 4] = *b"imt_";
+
+/// Result label for the polynomial commitment opening, see [`crate::kzg`].
+pub const RESULT_KZG: [u8; 4] = *b"kzg_";
+
+/// The result label of each bit of a [`TAG_FLAGS`] record, from bit 0. The byte is full, so the
+/// other results are only committed as a labelled list.
+pub const FLAG_LABELS: [[u8; 4]; 8] = [
+    RESULT_INCLUSION,
+    RESULT_ORDERED,
+    RESULT_RANGE,
+    RESULT_AUTHORIZATION,
+    RESULT_LEAF_MATCH,
+    RESULT_INDEX,
+    RESULT_PAIR,
+    RESULT_IMT,
+];
+
+/// The fixed-point denominator of committed ordinals.
+pub const ORDINAL_SCALE: u32 = 1_000_000_000;
+
+/// The hashing scheme of the tree a proof was checked against.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Scheme {
+    /// SHA-256 leaves and internal nodes, verified with `rs_merkle`.
+    Sha256 = 0,
+    /// SHA-256 leaves with Poseidon internal nodes, see [`crate::hybrid`].
+    Hybrid = 1,
+    /// SHA-256 with every node truncated to a fixed length, see [`crate::truncated`].
+    TruncatedSha256 = 2,
+    /// SHA-256 with a configurable number of children per node, see [`crate::kary`].
+    Kary = 3,
+    /// An Ethereum-style Merkle-Patricia trie, see [`crate::patricia`].
+    Patricia = 4,
+    /// Keccak-256 leaves and sorted-pair internal nodes, see [`crate::keccak`].
+    Keccak256 = 5,
+    /// BLAKE2b-256 nodes under a personalization string, see [`crate::blake2b`].
+    Blake2b = 6,
+    /// A sparse Merkle tree over 256-bit keys, see [`crate::sparse`].
+    Sparse = 7,
+    /// BLAKE3 leaves and internal nodes, see [`crate::blake3`].
+    Blake3 = 8,
+    /// A Bitcoin block's transaction tree, see [`crate::bitcoin`].
+    Bitcoin = 9,
+    /// A Merkle Mountain Range over SHA-256 nodes, see [`crate::mmr`].
+    Mmr = 10,
+}
+
+impl Scheme {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Sha256),
+            1 => Some(Self::Hybrid),
+            2 => Some(Self::TruncatedSha256),
+            3 => Some(Self::Kary),
+            4 => Some(Self::Patricia),
+            5 => Some(Self::Keccak256),
+            6 => Some(Self::Blake2b),
+            7 => Some(Self::Sparse),
+            8 => Some(Self::Blake3),
+            9 => Some(Self::Bitcoin),
+            10 => Some(Self::Mmr),
+            _ => None,
+        }
+    }
+}
+
+/// The leaf's relative position in the tree as a fixed-point fraction.
+///
+/// `value = floor(leaf_index * scale / total_leaves)` with `scale = ORDINAL_SCALE`, so a valid
+/// ordinal lies in `[0, scale)`. An out-of-range index commits `value = scale`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Ordinal {
+    pub value: u32,
+    pub scale: u32,
+}
+
+impl Ordinal {
+    pub fn new(leaf_index: usize, total_leaves: usize) -> Self {
+        let value = if leaf_index < total_leaves {
+            (leaf_index as u128 * ORDINAL_SCALE as u128 / total_leaves as u128) as u32
+        } else {
+            ORDINAL_SCALE
+        };
+        Self {
+            value,
+            scale: ORDINAL_SCALE,
+        }
+    }
+}
+
+/// A secondary index lookup of a key to the proven leaf's position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexCommitment {
+    /// The root of the index tree.
+    pub root: Hash,
+    /// Whether the index tree contains `(key, position)` and `position` equals the proven leaf's
+    /// index.
+    pub valid: bool,
+    pub position: u64,
+    pub key: Vec<u8>,
+}
+
+/// The key and value proven against a [`Scheme::Patricia`] root.
+///
+/// The committed `leaf` of such a proof is `keccak256(value)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrieEntry {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+/// The outcome of a leaf update chain, see [`crate::updates`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum UpdateStatus {
+    /// Every update was applied.
+    Applied = 0,
+    /// The leaf does not recompute the initial root, so no update was applied.
+    InvalidInitialRoot = 1,
+    /// An intermediate leaf did not recompute its root.
+    Inconsistent = 2,
+}
+
+impl UpdateStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Applied),
+            1 => Some(Self::InvalidInitialRoot),
+            2 => Some(Self::Inconsistent),
+            _ => None,
+        }
+    }
+}
+
+/// The normalization the host applied to leaf data before hashing it.
+///
+/// The guest only sees hashed leaves, so it commits the mode as supplied by the host.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Canonicalization {
+    /// JSON with sorted object keys and no insignificant whitespace.
+    Json = 1,
+    /// ASCII letters lowercased.
+    Lowercase = 2,
+    /// Leading and trailing ASCII whitespace removed.
+    Trim = 3,
+}
+
+impl Canonicalization {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Json),
+            2 => Some(Self::Lowercase),
+            3 => Some(Self::Trim),
+            _ => None,
+        }
+    }
+}
+
+/// The result of applying a sequence of updates to the proven leaf.
+///
+/// The initial root is the committed `root`; `count` is the number of updates applied.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UpdateChain {
+    pub status: UpdateStatus,
+    pub count: u32,
+    pub final_root: Hash,
+}
+
+/// The outcome of checking a signature over the leaf, see [`crate::authorization`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SignerCommitment {
+    /// The signer's Ethereum address, or zero if the public key was malformed.
+    pub address: [u8; 20],
+    /// Whether the signature over the leaf is valid for the signer's key.
+    pub signature_valid: bool,
+    /// Whether the leaf is both a member of the tree and validly signed.
+    pub authorized: bool,
+}
+
+/// How compact an inclusion proof is: `distinct_siblings` hashes prove `leaves` leaves.
+///
+/// A batch of independent single-leaf proofs needs about `leaves * depth` siblings; the closer a
+/// multiproof gets to `depth`, the more of its siblings are shared.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProofStats {
+    pub leaves: u32,
+    pub distinct_siblings: u32,
+}
+
+impl ProofStats {
+    /// Counts the distinct 32-byte siblings of a proof of `leaves` leaves, so that a sibling listed
+    /// for several leaves counts once.
+    pub fn new(leaves: usize, proof_bytes: &[u8]) -> Self {
+        let siblings: BTreeSet<&[u8]> = proof_bytes.chunks(32).collect();
+        Self {
+            leaves: leaves as u32,
+            distinct_siblings: siblings.len() as u32,
+        }
+    }
+}
+
+/// The neighbors of the leaf in a sorted tree, see [`crate::sorted`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NeighborCommitment {
+    pub prev: Option<Hash>,
+    pub next: Option<Hash>,
+    /// Whether the leaf and both neighbors are members and `prev < leaf < next`, with neighbors
+    /// omitted only at the tree's boundaries.
+    pub ordered: bool,
+}
+
+/// The outcome of a polynomial commitment opening at the leaf's index, see [`crate::kzg`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KzgCommitment {
+    /// The compressed polynomial commitment.
+    pub commitment: [u8; 32],
+    /// The compressed `[τ]₂` of the trusted setup the opening was checked against.
+    pub tau_g2: [u8; 64],
+    /// The evaluation point, which is the leaf's index.
+    pub point: u64,
+    /// Whether the committed polynomial evaluates to the leaf at `point`.
+    pub valid: bool,
+}
+
+/// How a global forest index relates to the proven tree position, see [`crate::forest`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ForestStatus {
+    /// The global index maps to the proven leaf's tree size and local index.
+    Mapped = 0,
+    /// The global index is beyond the last tree of the forest.
+    OutOfRange = 1,
+    /// The global index maps to a different local index or tree size than the one proven.
+    Mismatch = 2,
+}
+
+impl ForestStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Mapped),
+            1 => Some(Self::OutOfRange),
+            2 => Some(Self::Mismatch),
+            _ => None,
+        }
+    }
+}
+
+/// A global forest index and the tree it maps to, see [`crate::forest`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ForestCommitment {
+    pub status: ForestStatus,
+    pub global_index: u64,
+    /// The tree the global index maps to, or zero if it is out of range.
+    pub tree: u32,
+    /// The hash of the forest's tree sizes.
+    pub sizes_hash: Hash,
+}
+
+/// The outcome of checking a BLS signature over the leaf or root, see [`crate::bls`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BlsCommitment {
+    /// The compressed, possibly aggregate, public key.
+    pub public_key: [u8; 48],
+    pub signed: SignedValue,
+    /// Whether the signature over the signed value is valid for the public key.
+    pub valid: bool,
+}
+
+/// A VDF evaluation over the proof's challenge, see [`crate::vdf`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VdfCommitment {
+    pub challenge: Hash,
+    pub iterations: u64,
+    /// The canonical output, big-endian.
+    pub output: [u8; 256],
+    /// Whether `output` is the VDF output over `challenge`.
+    pub valid: bool,
+}
+
+/// The levels at which the proven leaf's path has no sibling, see [`crate::tree::promoted_levels`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Promotions {
+    /// The number of levels below the root.
+    pub depth: u8,
+    /// Bit `i` is set if the path node at level `i`, counting up from the leaves, was promoted
+    /// without a sibling.
+    pub promoted: u64,
+}
+
+/// The node of a Merkle cap that the proven leaf descends from.
+///
+/// The cap at level `n` is the row of nodes `n` levels below the root, numbered left to right from
+/// zero, so a verifier storing the cap checks `node` against entry `position` of that row.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CapNode {
+    pub cap_level: u8,
+    pub position: u64,
+    /// The node recomputed from the leaf and the part of its path below the cap.
+    pub node: Hash,
+}
+
+/// How the proven leaf `a` and the paired leaf `b` relate, see [`crate::sorted::PairedLeaf`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PairStatus {
+    /// Both leaves are in the tree and `index_a < index_b`.
+    Ordered = 0,
+    /// Both leaves are in the tree and `index_a > index_b`.
+    Reversed = 1,
+    /// Both leaves are in the tree at the same index, so the pair does not relate two leaves.
+    Equal = 2,
+    /// At least one of the inclusion proofs is invalid.
+    Invalid = 3,
+}
+
+impl PairStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Ordered),
+            1 => Some(Self::Reversed),
+            2 => Some(Self::Equal),
+            3 => Some(Self::Invalid),
+            _ => None,
+        }
+    }
+}
+
+/// The ordering of the proven leaf, `a`, and a second leaf of the same tree, `b`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PairCommitment {
+    pub status: PairStatus,
+    pub index_a: u64,
+    pub index_b: u64,
+    /// The second leaf; the first is the core `leaf`.
+    pub leaf_b: Hash,
+}
+
+/// The outcome of the advisory bloom filter pre-check, see [`crate::bloom`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BloomCommitment {
+    /// The hash of the filter checked against, see [`crate::bloom::BloomFilter::hash`].
+    pub filter_hash: Hash,
+    pub bits: u64,
+    pub hashes: u8,
+    /// Whether the leaf passed the filter. Only `is_valid` proves inclusion.
+    pub passed: bool,
+}
+
+/// The leaf data the program hashed into the leaf itself, see [`crate::Options::preimage`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PreimageCommitment {
+    /// `sha256` of the data, whatever the scheme's leaf hash.
+    pub data_hash: Hash,
+    /// The length of the data in bytes.
+    pub length: u64,
+    /// Whether the scheme's leaf hash of the data is the proven leaf.
+    pub hashes_to_leaf: bool,
+}
+
+/// The outcome of re-deriving the leaf with a hash chain, see [`crate::transform`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TransformCommitment {
+    /// `sha256` of the canonical spec, e.g. `sha256("sha256,keccak256")`.
+    pub spec_hash: Hash,
+    /// Whether the chain applied to the supplied leaf data yields the proven leaf.
+    pub derived: bool,
+}
+
+/// The proven root's position in a checkpoint history, see [`crate::checkpoint`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CheckpointCommitment {
+    pub checkpoint_root: Hash,
+    pub index: u64,
+    pub count: u64,
+    /// Whether the root is checkpoint `index` of `count` under `checkpoint_root`.
+    pub valid: bool,
+}
+
+/// The proven leaf's absence from a blocklist tree, see [`crate::blocklist`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BlocklistCommitment {
+    pub root: Hash,
+    pub total_leaves: u64,
+    /// Whether the leaf is proven absent from the blocklist.
+    pub excluded: bool,
+}
+
+/// The size of a counted tree, see [`crate::count`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LeafCount {
+    pub total_leaves: u64,
+    /// Whether the count leaf of `total_leaves` is leaf 0 and the proven leaf is a data leaf.
+    pub verified: bool,
+}
+
+/// A sampled chunk of an erasure-coded blob, see [`crate::erasure`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ErasureSampleCommitment {
+    pub data_chunks: u16,
+    pub total_chunks: u16,
+    pub chunk_size: u32,
+    /// The index of the sampled chunk, the proven leaf's index.
+    pub index: u64,
+    /// Whether the chunk is the proven leaf of a tree of `total_chunks` leaves under valid
+    /// parameters.
+    pub sampled: bool,
+}
+
+/// A target value proven absent from a sorted tree, see [`crate::sorted::Absence`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AbsenceCommitment {
+    pub target: Hash,
+    /// Whether two adjacent leaves of the tree bracket `target`, so that it is not in the tree.
+    pub absent: bool,
+}
+
+/// The outcome of an aggregated batch of inclusion proofs, see [`crate::aggregate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregateCommitment {
+    /// The number of entries checked.
+    pub total: u64,
+    /// The number of entries whose proof verified.
+    pub verified: u64,
+    pub entries_hash: Hash,
+    /// The leaf index of every entry whose proof did not verify, in entry order.
+    pub failed: Vec<u64>,
+}
+
+/// The key and value proven against a [`Scheme::Sparse`] root.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SparseEntry {
+    pub key: Hash,
+    /// The value `key` holds, zero when `is_member` is false.
+    pub value: Hash,
+    /// Whether the proof shows that `key` holds `value` rather than no value.
+    pub is_member: bool,
+}
+
+/// Where the leaf proven against a [`Scheme::Mmr`] root is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MmrEntry {
+    /// The number of nodes of the MMR, which its root binds.
+    pub mmr_size: u64,
+    /// The leaf's node position, see [`crate::mmr::leaf_position`].
+    pub position: u64,
+}
+
+/// The root after appending leaves to the proven tree, see [`crate::append`].
+///
+/// The old root is the committed `root`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AppendCommitment {
+    /// Whether the frontier verified against the old root. When it did not, nothing is appended and
+    /// `new_root` is the old root.
+    pub valid: bool,
+    /// The number of leaves appended.
+    pub count: u32,
+    pub new_root: Hash,
+}
+
+/// The number of leaves of a range satisfying a prefix predicate, see [`crate::range`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeCommitment {
+    pub first: u64,
+    /// The last position of the range, inclusive.
+    pub last: u64,
+    /// The number of verified leaves of the range that start with `prefix`.
+    pub count: u64,
+    /// Whether every leaf of the range verified, so that no leaf went uncounted.
+    pub verified: bool,
+    pub prefix: Vec<u8>,
+}
+
+/// A value proven absent from an indexed Merkle tree, see [`crate::imt`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImtCommitment {
+    pub value: Hash,
+    /// Whether the proven leaf is a low leaf of `value`, so that `value` is not in the tree.
+    pub non_member: bool,
+}
+
+/// The phase of a commit-reveal proof, see [`crate::reveal`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RevealPhase {
+    /// Only the commitment's inclusion was proven.
+    Commit = 1,
+    /// The commitment was also opened with a salt and value.
+    Reveal = 2,
+}
+
+impl RevealPhase {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Commit),
+            2 => Some(Self::Reveal),
+            _ => None,
+        }
+    }
+}
+
+/// The phase and outcome of a commit-reveal proof, see [`crate::reveal`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RevealCommitment {
+    pub phase: RevealPhase,
+    /// Whether `SHA-256(salt || value)` is the proven leaf. Always false in the commit phase.
+    pub matches: bool,
+    /// The revealed value, empty in the commit phase.
+    pub value: Vec<u8>,
+}
+
+/// A leaf verified as part of a batch, see [`crate::batch`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BatchLeaf {
+    pub index: u64,
+    pub leaf: Hash,
+}
+
+/// A failure the guest recovered from instead of aborting, committed in the diagnostics record.
+///
+/// Each code names the check or record that could not be evaluated; the affected record is omitted,
+/// or for [`Diagnostic::MalformedProof`], [`Diagnostic::BatchUnsupported`],
+/// [`Diagnostic::IndexOutOfRange`], [`Diagnostic::PathLengthMismatch`],
+/// [`Diagnostic::InvalidParameters`], [`Diagnostic::UnsupportedFormat`] and
+/// [`Diagnostic::UnknownScheme`] `is_valid` is false, and for [`Diagnostic::FlagsUnsupported`] the
+/// results are committed as a labelled list.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Diagnostic {
+    /// The inclusion proof could not be parsed.
+    MalformedProof = 1,
+    /// Leaf updates were requested for a scheme other than sha256 or hybrid.
+    UpdatesUnsupported = 2,
+    /// Proof statistics were requested for a scheme other than sha256.
+    ProofStatsUnsupported = 3,
+    /// Promotions were requested for a k-ary tree.
+    PromotionsUnsupported = 4,
+    /// A Merkle cap was requested for a scheme other than sha256 or hybrid.
+    CapUnsupported = 5,
+    /// The cap level exceeds the tree height.
+    CapLevelTooHigh = 6,
+    /// The proof does not reach the cap level.
+    CapPathTooShort = 7,
+    /// A batch was requested for a scheme other than sha256.
+    BatchUnsupported = 8,
+    /// The leaf index is not below the number of leaves, including for an empty tree.
+    IndexOutOfRange = 9,
+    /// Appended leaves were requested for a scheme other than sha256, hybrid or keccak256.
+    AppendUnsupported = 10,
+    /// The proof does not have one sibling per level of the leaf's path in a tree of `total_leaves`
+    /// leaves.
+    PathLengthMismatch = 11,
+    /// The node length, arity or personalization of the scheme is out of range.
+    InvalidParameters = 12,
+    /// The input's format header is missing or its version is not supported.
+    UnsupportedFormat = 13,
+    /// The input names a scheme the program does not know.
+    UnknownScheme = 14,
+    /// Packed flags were requested with a result that has no bit in [`FLAG_LABELS`].
+    FlagsUnsupported = 15,
+}
+
+impl Diagnostic {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::MalformedProof),
+            2 => Some(Self::UpdatesUnsupported),
+            3 => Some(Self::ProofStatsUnsupported),
+            4 => Some(Self::PromotionsUnsupported),
+            5 => Some(Self::CapUnsupported),
+            6 => Some(Self::CapLevelTooHigh),
+            7 => Some(Self::CapPathTooShort),
+            8 => Some(Self::BatchUnsupported),
+            9 => Some(Self::IndexOutOfRange),
+            10 => Some(Self::AppendUnsupported),
+            11 => Some(Self::PathLengthMismatch),
+            12 => Some(Self::InvalidParameters),
+            13 => Some(Self::UnsupportedFormat),
+            14 => Some(Self::UnknownScheme),
+            15 => Some(Self::FlagsUnsupported),
+            _ => None,
+        }
+    }
+}
+
+/// Decoded public values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicValues {
+    pub root: Hash,
+    pub leaf: Hash,
+    pub is_valid: bool,
+    /// The index of the proven leaf.
+    pub leaf_index: u64,
+    /// The number of leaves of the tree the proof was verified against.
+    pub total_leaves: u64,
+    pub scheme: Scheme,
+    /// The node length for [`Scheme::TruncatedSha256`] trees.
+    pub hash_bytes: Option<u8>,
+    /// Whether the leaf equals the expected leaf supplied by the host.
+    pub leaf_matches: Option<bool>,
+    /// The leaf's position as a fraction of the tree size.
+    pub ordinal: Option<Ordinal>,
+    /// The root byte-reversed, for consumers using the opposite byte order.
+    pub root_reversed: Option<Hash>,
+    /// The number of children per node of a [`Scheme::Kary`] tree.
+    pub arity: Option<u8>,
+    /// Every check the guest performed, keyed by its four-byte label.
+    pub results: Option<BTreeMap<[u8; 4], u8>>,
+    /// Whether `results` is committed as a [`TAG_FLAGS`] bitfield rather than a labelled list.
+    pub results_packed: bool,
+    /// The secondary index lookup that resolved the leaf's position.
+    pub index: Option<IndexCommitment>,
+    /// The SHA-256 of the program ELF, as supplied by the host.
+    pub elf_hash: Option<Hash>,
+    /// The key and value of a [`Scheme::Patricia`] proof.
+    pub trie: Option<TrieEntry>,
+    /// The EIP-712 digest of `(root, leaf, is_valid)` under the host-supplied domain separator.
+    pub eip712_digest: Option<Hash>,
+    /// Whether `leaf` holds a hiding commitment to the leaf instead of the leaf.
+    pub leaf_hidden: bool,
+    /// The result of the leaf update chain.
+    pub updates: Option<UpdateChain>,
+    /// The signature check over the leaf.
+    pub authorization: Option<SignerCommitment>,
+    /// The size of the inclusion proof relative to the number of leaves it proves.
+    pub proof_stats: Option<ProofStats>,
+    /// The neighbors of the leaf in a sorted tree.
+    pub neighbors: Option<NeighborCommitment>,
+    /// The polynomial commitment opening at the leaf's index.
+    pub kzg: Option<KzgCommitment>,
+    /// The normalization applied to leaf data before hashing.
+    pub canonicalization: Option<Canonicalization>,
+    /// The global forest index of the proven leaf.
+    pub forest: Option<ForestCommitment>,
+    /// The BLS signature check over the leaf or root.
+    pub bls: Option<BlsCommitment>,
+    /// The VDF evaluation over the proof's challenge.
+    pub vdf: Option<VdfCommitment>,
+    /// The levels at which the proven leaf's path has no sibling.
+    pub promotions: Option<Promotions>,
+    pub cap: Option<CapNode>,
+    pub pair: Option<PairCommitment>,
+    pub bloom: Option<BloomCommitment>,
+    pub transform: Option<TransformCommitment>,
+    pub checkpoint: Option<CheckpointCommitment>,
+    pub diagnostics: Option<Vec<Diagnostic>>,
+    pub reveal: Option<RevealCommitment>,
+    pub range: Option<RangeCommitment>,
+    pub imt: Option<ImtCommitment>,
+    /// Every leaf of a batch, whose validity `is_valid` covers.
+    pub batch: Option<Vec<BatchLeaf>>,
+    /// The blocklist the leaf was checked against and whether it is absent from it.
+    pub blocklist: Option<BlocklistCommitment>,
+    /// The number of leaves bound to the root by a count leaf.
+    pub leaf_count: Option<LeafCount>,
+    /// The sampled chunk of an erasure-coded blob and its coding parameters.
+    pub erasure: Option<ErasureSampleCommitment>,
+    /// The SHA-256 of the personalization of a [`Scheme::Blake2b`] tree.
+    pub personalization_hash: Option<Hash>,
+    /// A target value checked against the sorted tree and whether it is absent from it.
+    pub absence: Option<AbsenceCommitment>,
+    /// How many of an aggregated batch of inclusion proofs verified, and which did not.
+    pub aggregate: Option<AggregateCommitment>,
+    /// The key and value of a [`Scheme::Sparse`] proof.
+    pub sparse: Option<SparseEntry>,
+    /// The root after appending leaves to the proven tree.
+    pub append: Option<AppendCommitment>,
+    /// The data the proven leaf was hashed from.
+    pub preimage: Option<PreimageCommitment>,
+    /// The size and leaf position of a [`Scheme::Mmr`] proof.
+    pub mmr: Option<MmrEntry>,
+    /// Whether only `root || leaf` is committed. `is_valid` is then always true and every other
+    /// field is ignored.
+    pub strict: bool,
+    /// Whether the core is committed as BN254 field elements. Every other field is then ignored.
+    pub field_packed: bool,
+    /// Whether the regular public values are committed ABI-encoded, see [`crate::abi`].
+    pub abi_encoded: bool,
+}
+
+/// Why a byte string could not be decoded as [`PublicValues`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of the core or of an extension record.
+    Truncated,
+    /// An extension record used a tag this version does not know.
+    UnknownTag(u8),
+    /// An extension record had a payload that is invalid for its tag.
+    InvalidRecord(u8),
+    /// A word of a field-packed proof was out of range for its value.
+    InvalidFieldElement(usize),
+    /// ABI-encoded public values did not decode as a `PublicValuesStruct`.
+    InvalidAbi,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "public values are truncated"),
+            Self::UnknownTag(tag) => write!(f, "unknown extension tag 0x{tag:02x}"),
+            Self::InvalidRecord(tag) => write!(f, "invalid payload for extension tag 0x{tag:02x}"),
+            Self::InvalidFieldElement(word) => {
+                write!(f, "field element {word} is out of range for its value")
+            }
+            Self::InvalidAbi => write!(f, "public values are not an ABI-encoded struct"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+impl PublicValues {
+    pub fn new(root: Hash, leaf: Hash, is_valid: bool) -> Self {
+        Self {
+            root,
+            leaf,
+            is_valid,
+            leaf_index: 0,
+            total_leaves: 0,
+            scheme: Scheme::Sha256,
+            hash_bytes: None,
+            leaf_matches: None,
+            ordinal: None,
+            root_reversed: None,
+            arity: None,
+            results: None,
+            results_packed: false,
+            index: None,
+            elf_hash: None,
+            trie: None,
+            eip712_digest: None,
+            leaf_hidden: false,
+            updates: None,
+            authorization: None,
+            proof_stats: None,
+            neighbors: None,
+            kzg: None,
+            canonicalization: None,
+            forest: None,
+            bls: None,
+            vdf: None,
+            promotions: None,
+            cap: None,
+            pair: None,
+            bloom: None,
+            transform: None,
+            checkpoint: None,
+            diagnostics: None,
+            reveal: None,
+            range: None,
+            imt: None,
+            batch: None,
+            blocklist: None,
+            leaf_count: None,
+            erasure: None,
+            personalization_hash: None,
+            absence: None,
+            aggregate: None,
+            sparse: None,
+            append: None,
+            preimage: None,
+            mmr: None,
+            strict: false,
+            field_packed: false,
+            abi_encoded: false,
+        }
+    }
+
+    /// Serializes the values into the layout described in the module docs.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if self.abi_encoded && !self.strict && !self.field_packed {
+            let regular = Self {
+                abi_encoded: false,
+                ..self.clone()
+            };
+            return abi::encode(&regular.to_bytes());
+        }
+        if self.field_packed && !self.strict {
+            return field_elements(self);
+        }
+        let mut out = Vec::with_capacity(CORE_LEN);
+        out.extend_from_slice(&self.root);
+        out.extend_from_slice(&self.leaf);
+        if !self.strict {
+            out.push(self.is_valid as u8);
+        }
+        out.extend_from_slice(&self.leaf_index.to_be_bytes());
+        out.extend_from_slice(&self.total_leaves.to_be_bytes());
+        if self.strict {
+            return out;
+        }
+
+        if self.scheme != Scheme::Sha256 {
+            push_record(&mut out, TAG_SCHEME, &[self.scheme as u8]);
+        }
+        if let Some(hash_bytes) = self.hash_bytes {
+            push_record(&mut out, TAG_HASH_BYTES, &[hash_bytes]);
+        }
+        if let Some(leaf_matches) = self.leaf_matches {
+            push_record(&mut out, TAG_LEAF_MATCH, &[leaf_matches as u8]);
+        }
+        if let Some(ordinal) = self.ordinal {
+            let mut payload = ordinal.value.to_be_bytes().to_vec();
+            payload.extend_from_slice(&ordinal.scale.to_be_bytes());
+            push_record(&mut out, TAG_ORDINAL, &payload);
+        }
+        if let Some(root_reversed) = self.root_reversed {
+            push_record(&mut out, TAG_ROOT_REVERSED, &root_reversed);
+        }
+        if let Some(arity) = self.arity {
+            push_record(&mut out, TAG_ARITY, &[arity]);
+        }
+        if let (Some(results), true) = (&self.results, self.results_packed) {
+            let mut flags = 0u8;
+            for (label, &result) in results {
+                let bit = FLAG_LABELS
+                    .iter()
+                    .position(|flag| flag == label)
+                    .expect("result label has no flag bit");
+                flags |= ((result != 0) as u8) << bit;
+            }
+            push_record(&mut out, TAG_FLAGS, &[flags]);
+        } else if let Some(results) = &self.results {
+            let payload: Vec<u8> = results
+                .iter()
+                .flat_map(|(label, result)| label.iter().chain([result]).copied())
+                .collect();
+            push_record(&mut out, TAG_RESULTS, &payload);
+        }
+        if let Some(index) = &self.index {
+            let mut payload = index.root.to_vec();
+            payload.push(index.valid as u8);
+            payload.extend_from_slice(&index.position.to_be_bytes());
+            payload.extend_from_slice(&index.key);
+            push_record(&mut out, TAG_INDEX, &payload);
+        }
+        if let Some(elf_hash) = self.elf_hash {
+            push_record(&mut out, TAG_ELF_HASH, &elf_hash);
+        }
+        if let Some(trie) = &self.trie {
+            let key_len = u16::try_from(trie.key.len()).expect("trie key exceeds u16::MAX");
+            let mut payload = key_len.to_be_bytes().to_vec();
+            payload.extend_from_slice(&trie.key);
+            payload.extend_from_slice(&trie.value);
+            push_record(&mut out, TAG_TRIE, &payload);
+        }
+        if let Some(eip712_digest) = self.eip712_digest {
+            push_record(&mut out, TAG_EIP712, &eip712_digest);
+        }
+        if self.leaf_hidden {
+            push_record(&mut out, TAG_LEAF_HIDDEN, &[]);
+        }
+        if let Some(updates) = self.updates {
+            let mut payload = vec![updates.status as u8];
+            payload.extend_from_slice(&updates.count.to_be_bytes());
+            payload.extend_from_slice(&updates.final_root);
+            push_record(&mut out, TAG_UPDATES, &payload);
+        }
+        if let Some(signer) = self.authorization {
+            let mut payload = signer.address.to_vec();
+            payload.push(signer.signature_valid as u8);
+            payload.push(signer.authorized as u8);
+            push_record(&mut out, TAG_AUTHORIZATION, &payload);
+        }
+        if let Some(stats) = self.proof_stats {
+            let mut payload = stats.leaves.to_be_bytes().to_vec();
+            payload.extend_from_slice(&stats.distinct_siblings.to_be_bytes());
+            push_record(&mut out, TAG_PROOF_STATS, &payload);
+        }
+        if let Some(neighbors) = self.neighbors {
+            let present = neighbors.prev.is_some() as u8 | (neighbors.next.is_some() as u8) << 1;
+            let mut payload = vec![present, neighbors.ordered as u8];
+            payload.extend(neighbors.prev.iter().chain(&neighbors.next).flatten());
+            push_record(&mut out, TAG_NEIGHBORS, &payload);
+        }
+        if let Some(kzg) = self.kzg {
+            let mut payload = kzg.commitment.to_vec();
+            payload.extend_from_slice(&kzg.tau_g2);
+            payload.extend_from_slice(&kzg.point.to_be_bytes());
+            payload.push(kzg.valid as u8);
+            push_record(&mut out, TAG_KZG, &payload);
+        }
+        if let Some(canonicalization) = self.canonicalization {
+            push_record(&mut out, TAG_CANONICALIZATION, &[canonicalization as u8]);
+        }
+        if let Some(forest) = self.forest {
+            let mut payload = vec![forest.status as u8];
+            payload.extend_from_slice(&forest.global_index.to_be_bytes());
+            payload.extend_from_slice(&forest.tree.to_be_bytes());
+            payload.extend_from_slice(&forest.sizes_hash);
+            push_record(&mut out, TAG_FOREST, &payload);
+        }
+        if let Some(bls) = self.bls {
+            let mut payload = bls.public_key.to_vec();
+            payload.extend_from_slice(&[bls.signed as u8, bls.valid as u8]);
+            push_record(&mut out, TAG_BLS, &payload);
+        }
+        if let Some(vdf) = self.vdf {
+            let mut payload = vdf.challenge.to_vec();
+            payload.extend_from_slice(&vdf.iterations.to_be_bytes());
+            payload.extend_from_slice(&vdf.output);
+            payload.push(vdf.valid as u8);
+            push_record(&mut out, TAG_VDF, &payload);
+        }
+        if let Some(promotions) = self.promotions {
+            let mut payload = vec![promotions.depth];
+            payload.extend_from_slice(&promotions.promoted.to_be_bytes());
+            push_record(&mut out, TAG_PROMOTIONS, &payload);
+        }
+        if let Some(cap) = self.cap {
+            let mut payload = vec![cap.cap_level];
+            payload.extend_from_slice(&cap.position.to_be_bytes());
+            payload.extend_from_slice(&cap.node);
+            push_record(&mut out, TAG_CAP, &payload);
+        }
+        if let Some(pair) = self.pair {
+            let mut payload = vec![pair.status as u8];
+            payload.extend_from_slice(&pair.index_a.to_be_bytes());
+            payload.extend_from_slice(&pair.index_b.to_be_bytes());
+            payload.extend_from_slice(&pair.leaf_b);
+            push_record(&mut out, TAG_PAIR, &payload);
+        }
+        if let Some(bloom) = self.bloom {
+            let mut payload = bloom.filter_hash.to_vec();
+            payload.extend_from_slice(&bloom.bits.to_be_bytes());
+            payload.extend_from_slice(&[bloom.hashes, bloom.passed as u8]);
+            push_record(&mut out, TAG_BLOOM, &payload);
+        }
+        if let Some(transform) = self.transform {
+            let mut payload = transform.spec_hash.to_vec();
+            payload.push(transform.derived as u8);
+            push_record(&mut out, TAG_TRANSFORM, &payload);
+        }
+        if let Some(checkpoint) = self.checkpoint {
+            let mut payload = checkpoint.checkpoint_root.to_vec();
+            payload.extend_from_slice(&checkpoint.index.to_be_bytes());
+            payload.extend_from_slice(&checkpoint.count.to_be_bytes());
+            payload.push(checkpoint.valid as u8);
+            push_record(&mut out, TAG_CHECKPOINT, &payload);
+        }
+        if let Some(diagnostics) = &self.diagnostics {
+            let payload: Vec<u8> = diagnostics.iter().map(|&code| code as u8).collect();
+            push_record(&mut out, TAG_DIAGNOSTICS, &payload);
+        }
+        if let Some(reveal) = &self.reveal {
+            let mut payload = vec![reveal.phase as u8, reveal.matches as u8];
+            payload.extend_from_slice(&reveal.value);
+            push_record(&mut out, TAG_REVEAL, &payload);
+        }
+        if let Some(range) = &self.range {
+            let mut payload = range.first.to_be_bytes().to_vec();
+            payload.extend_from_slice(&range.last.to_be_bytes());
+            payload.extend_from_slice(&range.count.to_be_bytes());
+            payload.push(range.verified as u8);
+            payload.extend_from_slice(&range.prefix);
+            push_record(&mut out, TAG_RANGE, &payload);
+        }
+        if let Some(imt) = self.imt {
+            let mut payload = imt.value.to_vec();
+            payload.push(imt.non_member as u8);
+            push_record(&mut out, TAG_IMT, &payload);
+        }
+        if let Some(batch) = &self.batch {
+            let payload: Vec<u8> = batch
+                .iter()
+                .flat_map(|entry| entry.index.to_be_bytes().into_iter().chain(entry.leaf))
+                .collect();
+            push_record(&mut out, TAG_BATCH, &payload);
+        }
+        if let Some(blocklist) = self.blocklist {
+            let mut payload = blocklist.root.to_vec();
+            payload.extend_from_slice(&blocklist.total_leaves.to_be_bytes());
+            payload.push(blocklist.excluded as u8);
+            push_record(&mut out, TAG_BLOCKLIST, &payload);
+        }
+        if let Some(count) = self.leaf_count {
+            let mut payload = count.total_leaves.to_be_bytes().to_vec();
+            payload.push(count.verified as u8);
+            push_record(&mut out, TAG_LEAF_COUNT, &payload);
+        }
+        if let Some(erasure) = self.erasure {
+            let mut payload = erasure.data_chunks.to_be_bytes().to_vec();
+            payload.extend_from_slice(&erasure.total_chunks.to_be_bytes());
+            payload.extend_from_slice(&erasure.chunk_size.to_be_bytes());
+            payload.extend_from_slice(&erasure.index.to_be_bytes());
+            payload.push(erasure.sampled as u8);
+            push_record(&mut out, TAG_ERASURE, &payload);
+        }
+        if let Some(personalization_hash) = self.personalization_hash {
+            push_record(&mut out, TAG_PERSONALIZATION, &personalization_hash);
+        }
+        if let Some(absence) = self.absence {
+            let mut payload = absence.target.to_vec();
+            payload.push(absence.absent as u8);
+            push_record(&mut out, TAG_ABSENCE, &payload);
+        }
+        if let Some(aggregate) = &self.aggregate {
+            let mut payload = aggregate.total.to_be_bytes().to_vec();
+            payload.extend_from_slice(&aggregate.verified.to_be_bytes());
+            payload.extend_from_slice(&aggregate.entries_hash);
+            for index in &aggregate.failed {
+                payload.extend_from_slice(&index.to_be_bytes());
+            }
+            push_record(&mut out, TAG_AGGREGATE, &payload);
+        }
+        if let Some(sparse) = self.sparse {
+            let mut payload = sparse.key.to_vec();
+            payload.extend_from_slice(&sparse.value);
+            payload.push(sparse.is_member as u8);
+            push_record(&mut out, TAG_SPARSE, &payload);
+        }
+        if let Some(append) = self.append {
+            let mut payload = vec![append.valid as u8];
+            payload.extend_from_slice(&append.count.to_be_bytes());
+            payload.extend_from_slice(&append.new_root);
+            push_record(&mut out, TAG_APPEND, &payload);
+        }
+        if let Some(preimage) = self.preimage {
+            let mut payload = preimage.data_hash.to_vec();
+            payload.extend_from_slice(&preimage.length.to_be_bytes());
+            payload.push(preimage.hashes_to_leaf as u8);
+            push_record(&mut out, TAG_PREIMAGE, &payload);
+        }
+        if let Some(mmr) = self.mmr {
+            let mut payload = mmr.mmr_size.to_be_bytes().to_vec();
+            payload.extend_from_slice(&mmr.position.to_be_bytes());
+            push_record(&mut out, TAG_MMR, &payload);
+        }
+
+        out
+    }
+
+    /// Parses bytes produced by [`PublicValues::to_bytes`] with `abi_encoded` set.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let regular = abi::decode(bytes).ok_or(DecodeError::InvalidAbi)?;
+        let mut values = Self::from_bytes(&regular)?;
+        values.abi_encoded = true;
+        Ok(values)
+    }
+
+    /// Parses bytes produced by [`PublicValues::to_bytes`] without `abi_encoded`, which
+    /// [`PublicValues::from_abi_bytes`] parses instead.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() == STRICT_LEN {
+            let mut values = Self::new(
+                bytes[0..32].try_into().unwrap(),
+                bytes[32..64].try_into().unwrap(),
+                true,
+            );
+            values.leaf_index = u64::from_be_bytes(bytes[64..72].try_into().unwrap());
+            values.total_leaves = u64::from_be_bytes(bytes[72..80].try_into().unwrap());
+            values.strict = true;
+            return Ok(values);
+        }
+        if bytes.len() == FIELD_PACKED_LEN && bytes[64..88].iter().all(|&b| b == 0) {
+            return from_field_elements(bytes);
+        }
+        if bytes.len() < CORE_LEN {
+            return Err(DecodeError::Truncated);
+        }
+        let mut values = Self::new(
+            bytes[0..32].try_into().unwrap(),
+            bytes[32..64].try_into().unwrap(),
+            bytes[64] != 0,
+        );
+        values.leaf_index = u64::from_be_bytes(bytes[65..73].try_into().unwrap());
+        values.total_leaves = u64::from_be_bytes(bytes[73..81].try_into().unwrap());
+
+        let mut rest = &bytes[CORE_LEN..];
+        while !rest.is_empty() {
+            if rest.len() < 3 {
+                return Err(DecodeError::Truncated);
+            }
+            let tag = rest[0];
+            let len = u16::from_be_bytes([rest[1], rest[2]]) as usize;
+            let payload = rest.get(3..3 + len).ok_or(DecodeError::Truncated)?;
+            rest = &rest[3 + len..];
+
+            match tag {
+                TAG_SCHEME => {
+                    let scheme = byte(tag, payload)?;
+                    values.scheme =
+                        Scheme::from_u8(scheme).ok_or(DecodeError::InvalidRecord(tag))?;
+                }
+                TAG_HASH_BYTES => values.hash_bytes = Some(byte(tag, payload)?),
+                TAG_LEAF_MATCH => values.leaf_matches = Some(flag(tag, payload)?),
+                TAG_ORDINAL => {
+                    let payload = array::<8>(tag, payload)?;
+                    values.ordinal = Some(Ordinal {
+                        value: u32::from_be_bytes(payload[0..4].try_into().unwrap()),
+                        scale: u32::from_be_bytes(payload[4..8].try_into().unwrap()),
+                    });
+                }
+                TAG_ROOT_REVERSED => values.root_reversed = Some(array(tag, payload)?),
+                TAG_ARITY => values.arity = Some(byte(tag, payload)?),
+                TAG_RESULTS => values.results = Some(results(tag, payload)?),
+                TAG_FLAGS => {
+                    values.results = Some(flags(tag, payload)?);
+                    values.results_packed = true;
+                }
+                TAG_INDEX => values.index = Some(index(tag, payload)?),
+                TAG_ELF_HASH => values.elf_hash = Some(array(tag, payload)?),
+                TAG_TRIE => values.trie = Some(trie(tag, payload)?),
+                TAG_EIP712 => values.eip712_digest = Some(array(tag, payload)?),
+                TAG_LEAF_HIDDEN => {
+                    array::<0>(tag, payload)?;
+                    values.leaf_hidden = true;
+                }
+                TAG_UPDATES => {
+                    let payload = array::<37>(tag, payload)?;
+                    values.updates = Some(UpdateChain {
+                        status: UpdateStatus::from_u8(payload[0])
+                            .ok_or(DecodeError::InvalidRecord(tag))?,
+                        count: u32::from_be_bytes(payload[1..5].try_into().unwrap()),
+                        final_root: payload[5..].try_into().unwrap(),
+                    });
+                }
+                TAG_AUTHORIZATION => {
+                    let payload = array::<22>(tag, payload)?;
+                    values.authorization = Some(SignerCommitment {
+                        address: payload[..20].try_into().unwrap(),
+                        signature_valid: flag(tag, &payload[20..21])?,
+                        authorized: flag(tag, &payload[21..22])?,
+                    });
+                }
+                TAG_PROOF_STATS => {
+                    let payload = array::<8>(tag, payload)?;
+                    values.proof_stats = Some(ProofStats {
+                        leaves: u32::from_be_bytes(payload[0..4].try_into().unwrap()),
+                        distinct_siblings: u32::from_be_bytes(payload[4..8].try_into().unwrap()),
+                    });
+                }
+                TAG_NEIGHBORS => values.neighbors = Some(neighbors(tag, payload)?),
+                TAG_KZG => {
+                    let payload = array::<105>(tag, payload)?;
+                    values.kzg = Some(KzgCommitment {
+                        commitment: payload[..32].try_into().unwrap(),
+                        tau_g2: payload[32..96].try_into().unwrap(),
+                        point: u64::from_be_bytes(payload[96..104].try_into().unwrap()),
+                        valid: flag(tag, &payload[104..])?,
+                    });
+                }
+                TAG_CANONICALIZATION => {
+                    let canonicalization = byte(tag, payload)?;
+                    values.canonicalization = Some(
+                        Canonicalization::from_u8(canonicalization)
+                            .ok_or(DecodeError::InvalidRecord(tag))?,
+                    );
+                }
+                TAG_FOREST => {
+                    let payload = array::<45>(tag, payload)?;
+                    values.forest = Some(ForestCommitment {
+                        status: ForestStatus::from_u8(payload[0])
+                            .ok_or(DecodeError::InvalidRecord(tag))?,
+                        global_index: u64::from_be_bytes(payload[1..9].try_into().unwrap()),
+                        tree: u32::from_be_bytes(payload[9..13].try_into().unwrap()),
+                        sizes_hash: payload[13..].try_into().unwrap(),
+                    });
+                }
+                TAG_BLS => {
+                    let payload = array::<50>(tag, payload)?;
+                    values.bls = Some(BlsCommitment {
+                        public_key: payload[..48].try_into().unwrap(),
+                        signed: SignedValue::from_u8(payload[48])
+                            .ok_or(DecodeError::InvalidRecord(tag))?,
+                        valid: flag(tag, &payload[49..])?,
+                    });
+                }
+                TAG_VDF => {
+                    let payload = array::<297>(tag, payload)?;
+                    values.vdf = Some(VdfCommitment {
+                        challenge: payload[..32].try_into().unwrap(),
+                        iterations: u64::from_be_bytes(payload[32..40].try_into().unwrap()),
+                        output: payload[40..296].try_into().unwrap(),
+                        valid: flag(tag, &payload[296..])?,
+                    });
+                }
+                TAG_PROMOTIONS => {
+                    let payload = array::<9>(tag, payload)?;
+                    values.promotions = Some(Promotions {
+                        depth: payload[0],
+                        promoted: u64::from_be_bytes(payload[1..].try_into().unwrap()),
+                    });
+                }
+                TAG_CAP => {
+                    let payload = array::<41>(tag, payload)?;
+                    values.cap = Some(CapNode {
+                        cap_level: payload[0],
+                        position: u64::from_be_bytes(payload[1..9].try_into().unwrap()),
+                        node: payload[9..].try_into().unwrap(),
+                    });
+                }
+                TAG_PAIR => {
+                    let payload = array::<49>(tag, payload)?;
+                    values.pair = Some(PairCommitment {
+                        status: PairStatus::from_u8(payload[0])
+                            .ok_or(DecodeError::InvalidRecord(tag))?,
+                        index_a: u64::from_be_bytes(payload[1..9].try_into().unwrap()),
+                        index_b: u64::from_be_bytes(payload[9..17].try_into().unwrap()),
+                        leaf_b: payload[17..].try_into().unwrap(),
+                    });
+                }
+                TAG_BLOOM => {
+                    let payload = array::<42>(tag, payload)?;
+                    values.bloom = Some(BloomCommitment {
+                        filter_hash: payload[..32].try_into().unwrap(),
+                        bits: u64::from_be_bytes(payload[32..40].try_into().unwrap()),
+                        hashes: payload[40],
+                        passed: flag(tag, &payload[41..])?,
+                    });
+                }
+                TAG_TRANSFORM => {
+                    let payload = array::<33>(tag, payload)?;
+                    values.transform = Some(TransformCommitment {
+                        spec_hash: payload[..32].try_into().unwrap(),
+                        derived: flag(tag, &payload[32..])?,
+                    });
+                }
+                TAG_CHECKPOINT => {
+                    let payload = array::<49>(tag, payload)?;
+                    values.checkpoint = Some(CheckpointCommitment {
+                        checkpoint_root: payload[..32].try_into().unwrap(),
+                        index: u64::from_be_bytes(payload[32..40].try_into().unwrap()),
+                        count: u64::from_be_bytes(payload[40..48].try_into().unwrap()),
+                        valid: flag(tag, &payload[48..])?,
+                    });
+                }
+                TAG_DIAGNOSTICS => {
+                    values.diagnostics = Some(
+                        payload
+                            .iter()
+                            .map(|&code| Diagnostic::from_u8(code))
+                            .collect::<Option<_>>()
+                            .ok_or(DecodeError::InvalidRecord(tag))?,
+                    );
+                }
+                TAG_REVEAL => values.reveal = Some(reveal(tag, payload)?),
+                TAG_RANGE => values.range = Some(range(tag, payload)?),
+                TAG_BLOCKLIST => {
+                    let payload = array::<41>(tag, payload)?;
+                    values.blocklist = Some(BlocklistCommitment {
+                        root: payload[..32].try_into().unwrap(),
+                        total_leaves: u64::from_be_bytes(payload[32..40].try_into().unwrap()),
+                        excluded: flag(tag, &payload[40..])?,
+                    });
+                }
+                TAG_PERSONALIZATION => {
+                    values.personalization_hash = Some(array::<32>(tag, payload)?)
+                }
+                TAG_APPEND => {
+                    let payload = array::<37>(tag, payload)?;
+                    values.append = Some(AppendCommitment {
+                        valid: flag(tag, &payload[..1])?,
+                        count: u32::from_be_bytes(payload[1..5].try_into().unwrap()),
+                        new_root: payload[5..].try_into().unwrap(),
+                    });
+                }
+                TAG_PREIMAGE => {
+                    let payload = array::<41>(tag, payload)?;
+                    values.preimage = Some(PreimageCommitment {
+                        data_hash: payload[..32].try_into().unwrap(),
+                        length: u64::from_be_bytes(payload[32..40].try_into().unwrap()),
+                        hashes_to_leaf: flag(tag, &payload[40..])?,
+                    });
+                }
+                TAG_MMR => {
+                    let payload = array::<16>(tag, payload)?;
+                    values.mmr = Some(MmrEntry {
+                        mmr_size: u64::from_be_bytes(payload[..8].try_into().unwrap()),
+                        position: u64::from_be_bytes(payload[8..].try_into().unwrap()),
+                    });
+                }
+                TAG_SPARSE => {
+                    let payload = array::<65>(tag, payload)?;
+                    values.sparse = Some(SparseEntry {
+                        key: payload[..32].try_into().unwrap(),
+                        value: payload[32..64].try_into().unwrap(),
+                        is_member: flag(tag, &payload[64..])?,
+                    });
+                }
+                TAG_AGGREGATE => values.aggregate = Some(aggregate(tag, payload)?),
+                TAG_ABSENCE => {
+                    let payload = array::<33>(tag, payload)?;
+                    values.absence = Some(AbsenceCommitment {
+                        target: payload[..32].try_into().unwrap(),
+                        absent: flag(tag, &payload[32..])?,
+                    });
+                }
+                TAG_ERASURE => {
+                    let payload = array::<17>(tag, payload)?;
+                    values.erasure = Some(ErasureSampleCommitment {
+                        data_chunks: u16::from_be_bytes(payload[..2].try_into().unwrap()),
+                        total_chunks: u16::from_be_bytes(payload[2..4].try_into().unwrap()),
+                        chunk_size: u32::from_be_bytes(payload[4..8].try_into().unwrap()),
+                        index: u64::from_be_bytes(payload[8..16].try_into().unwrap()),
+                        sampled: flag(tag, &payload[16..])?,
+                    });
+                }
+                TAG_LEAF_COUNT => {
+                    let payload = array::<9>(tag, payload)?;
+                    values.leaf_count = Some(LeafCount {
+                        total_leaves: u64::from_be_bytes(payload[..8].try_into().unwrap()),
+                        verified: flag(tag, &payload[8..])?,
+                    });
+                }
+                TAG_BATCH => {
+                    if payload.len() % 40 != 0 {
+                        return Err(DecodeError::InvalidRecord(tag));
+                    }
+                    values.batch = Some(
+                        payload
+                            .chunks_exact(40)
+                            .map(|entry| BatchLeaf {
+                                index: u64::from_be_bytes(entry[..8].try_into().unwrap()),
+                                leaf: entry[8..].try_into().unwrap(),
+                            })
+                            .collect(),
+                    );
+                }
+                TAG_IMT => {
+                    let payload = array::<33>(tag, payload)?;
+                    values.imt = Some(ImtCommitment {
+                        value: payload[..32].try_into().unwrap(),
+                        non_member: flag(tag, &payload[32..])?,
+                    });
+                }
+                _ => return Err(DecodeError::UnknownTag(tag)),
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// Packs the core into the seven words of a field-packed proof.
+fn field_elements(values: &PublicValues) -> Vec<u8> {
+    let mut out = Vec::with_capacity(FIELD_PACKED_LEN);
+    let mut push_word = |value: &[u8]| {
+        out.resize(out.len() + 32 - value.len(), 0);
+        out.extend_from_slice(value);
+    };
+    push_word(&values.root[..16]);
+    push_word(&values.root[16..]);
+    push_word(&values.leaf_index.to_be_bytes());
+    push_word(&values.total_leaves.to_be_bytes());
+    push_word(&values.leaf[..16]);
+    push_word(&values.leaf[16..]);
+    push_word(&[values.is_valid as u8]);
+    out
+}
+
+/// Decodes a field-packed proof, rejecting words outside the range of their value.
+fn from_field_elements(bytes: &[u8]) -> Result<PublicValues, DecodeError> {
+    let words: Vec<&[u8]> = bytes.chunks_exact(32).collect();
+    if let Some(word) = words
+        .iter()
+        .position(|word| word[..16].iter().any(|&b| b != 0))
+    {
+        return Err(DecodeError::InvalidFieldElement(word));
+    }
+    for word in [2, 3] {
+        if words[word][16..24].iter().any(|&b| b != 0) {
+            return Err(DecodeError::InvalidFieldElement(word));
+        }
+    }
+    if words[6][16..31].iter().any(|&b| b != 0) || words[6][31] > 1 {
+        return Err(DecodeError::InvalidFieldElement(6));
+    }
+    let half = |word: usize| -> &[u8] { &words[word][16..] };
+    let count = |word: usize| u64::from_be_bytes(words[word][24..].try_into().unwrap());
+    let mut values = PublicValues::new(
+        [half(0), half(1)].concat().try_into().unwrap(),
+        [half(4), half(5)].concat().try_into().unwrap(),
+        words[6][31] == 1,
+    );
+    values.leaf_index = count(2);
+    values.total_leaves = count(3);
+    values.field_packed = true;
+    Ok(values)
+}
+
+fn push_record(out: &mut Vec<u8>, tag: u8, payload: &[u8]) {
+    let len = u16::try_from(payload.len()).expect("extension payload exceeds u16::MAX");
+    out.push(tag);
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(payload);
+}
+
+fn byte(tag: u8, payload: &[u8]) -> Result<u8, DecodeError> {
+    match payload {
+        [value] => Ok(*value),
+        _ => Err(DecodeError::InvalidRecord(tag)),
+    }
+}
+
+fn flag(tag: u8, payload: &[u8]) -> Result<bool, DecodeError> {
+    match byte(tag, payload)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(DecodeError::InvalidRecord(tag)),
+    }
+}
+
+fn array<const N: usize>(tag: u8, payload: &[u8]) -> Result<[u8; N], DecodeError> {
+    payload
+        .try_into()
+        .map_err(|_| DecodeError::InvalidRecord(tag))
+}
+
+fn flags(tag: u8, payload: &[u8]) -> Result<BTreeMap<[u8; 4], u8>, DecodeError> {
+    let flags = byte(tag, payload)?;
+    Ok(FLAG_LABELS
+        .iter()
+        .enumerate()
+        .map(|(bit, &label)| (label, flags >> bit & 1))
+        .collect())
+}
+
+fn results(tag: u8, payload: &[u8]) -> Result<BTreeMap<[u8; 4], u8>, DecodeError> {
+    if payload.len() % 5 != 0 {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    let mut results = BTreeMap::new();
+    for entry in payload.chunks_exact(5) {
+        let label: [u8; 4] = entry[..4].try_into().unwrap();
+        if results.insert(label, entry[4]).is_some() {
+            return Err(DecodeError::InvalidRecord(tag));
+        }
+    }
+    Ok(results)
+}
+
+fn index(tag: u8, payload: &[u8]) -> Result<IndexCommitment, DecodeError> {
+    if payload.len() < 41 {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    Ok(IndexCommitment {
+        root: payload[0..32].try_into().unwrap(),
+        valid: flag(tag, &payload[32..33])?,
+        position: u64::from_be_bytes(payload[33..41].try_into().unwrap()),
+        key: payload[41..].to_vec(),
+    })
+}
+
+fn range(tag: u8, payload: &[u8]) -> Result<RangeCommitment, DecodeError> {
+    let (fixed, prefix) = payload
+        .split_first_chunk::<25>()
+        .ok_or(DecodeError::InvalidRecord(tag))?;
+    if prefix.len() > crate::range::MAX_PREFIX_LEN {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    Ok(RangeCommitment {
+        first: u64::from_be_bytes(fixed[0..8].try_into().unwrap()),
+        last: u64::from_be_bytes(fixed[8..16].try_into().unwrap()),
+        count: u64::from_be_bytes(fixed[16..24].try_into().unwrap()),
+        verified: flag(tag, &fixed[24..])?,
+        prefix: prefix.to_vec(),
+    })
+}
+
+fn aggregate(tag: u8, payload: &[u8]) -> Result<AggregateCommitment, DecodeError> {
+    let (fixed, failed) = payload
+        .split_first_chunk::<48>()
+        .ok_or(DecodeError::InvalidRecord(tag))?;
+    let total = u64::from_be_bytes(fixed[..8].try_into().unwrap());
+    let verified = u64::from_be_bytes(fixed[8..16].try_into().unwrap());
+    if failed.len() % 8 != 0 || verified.checked_add(failed.len() as u64 / 8) != Some(total) {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    Ok(AggregateCommitment {
+        total,
+        verified,
+        entries_hash: fixed[16..].try_into().unwrap(),
+        failed: failed
+            .chunks_exact(8)
+            .map(|index| u64::from_be_bytes(index.try_into().unwrap()))
+            .collect(),
+    })
+}
+
+fn reveal(tag: u8, payload: &[u8]) -> Result<RevealCommitment, DecodeError> {
+    let [phase, matches, value @ ..] = payload else {
+        return Err(DecodeError::InvalidRecord(tag));
+    };
+    let phase = RevealPhase::from_u8(*phase).ok_or(DecodeError::InvalidRecord(tag))?;
+    let matches = flag(tag, &[*matches])?;
+    if phase == RevealPhase::Commit && (matches || !value.is_empty()) {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    Ok(RevealCommitment {
+        phase,
+        matches,
+        value: value.to_vec(),
+    })
+}
+
+fn trie(tag: u8, payload: &[u8]) -> Result<TrieEntry, DecodeError> {
+    let (key_len, rest) = payload
+        .split_first_chunk::<2>()
+        .ok_or(DecodeError::InvalidRecord(tag))?;
+    let key_len = u16::from_be_bytes(*key_len) as usize;
+    if rest.len() < key_len {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    let (key, value) = rest.split_at(key_len);
+    Ok(TrieEntry {
+        key: key.to_vec(),
+        value: value.to_vec(),
+    })
+}
+
+fn neighbors(tag: u8, payload: &[u8]) -> Result<NeighborCommitment, DecodeError> {
+    let [present, ordered, rest @ ..] = payload else {
+        return Err(DecodeError::InvalidRecord(tag));
+    };
+    if *present > 0b11 {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    let mut hashes = rest.chunks(32);
+    let mut take = |is_present: bool| -> Result<Option<Hash>, DecodeError> {
+        if !is_present {
+            return Ok(None);
+        }
+        let hash = hashes.next().ok_or(DecodeError::InvalidRecord(tag))?;
+        array(tag, hash).map(Some)
+    };
+    let prev = take(present & 1 != 0)?;
+    let next = take(present & 2 != 0)?;
+    if hashes.next().is_some() {
+        return Err(DecodeError::InvalidRecord(tag));
+    }
+    Ok(NeighborCommitment {
+        prev,
+        next,
+        ordered: flag(tag, &[*ordered])?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rs_merkle::{Hasher, MerkleTree};
+
+    use super::*;
+    use crate::sha256::Sha256Hasher;
+
+    #[test]
+    fn proof_stats_count_shared_siblings_once() {
+        let leaves: Vec<Hash> = (0u8..8).map(|i| Sha256Hasher::hash(&[i])).collect();
+        let tree = MerkleTree::<Sha256Hasher>::from_leaves(&leaves);
+
+        // Leaves 0 and 1 are siblings, so their multiproof only needs the two subtrees beside them.
+        let multiproof = tree.proof(&[0, 1]).to_bytes();
+        assert_eq!(
+            ProofStats::new(2, &multiproof),
+            ProofStats {
+                leaves: 2,
+                distinct_siblings: 2,
+            }
+        );
+
+        // Their single-leaf proofs list the same two subtrees, which are only counted once.
+        let concatenated = [tree.proof(&[0]).to_bytes(), tree.proof(&[1]).to_bytes()].concat();
+        assert_eq!(concatenated.len(), 6 * 32);
+        assert_eq!(
+            ProofStats::new(2, &concatenated),
+            ProofStats {
+                leaves: 2,
+                distinct_siblings: 4,
+            }
+        );
+    }
+
+    /// Public values with every extension record set.
+    fn every_record() -> PublicValues {
+        let mut values = PublicValues::new([1; 32], [2; 32], true);
+        values.leaf_index = 5;
+        values.total_leaves = 9;
+        values.scheme = Scheme::Kary;
+        values.hash_bytes = Some(20);
+        values.leaf_matches = Some(true);
+        values.ordinal = Some(Ordinal::new(5, 9));
+        values.root_reversed = Some([3; 32]);
+        values.arity = Some(4);
+        values.results = Some(BTreeMap::from([(RESULT_INCLUSION, 1), (RESULT_BLS, 0)]));
+        values.index = Some(IndexCommitment {
+            root: [4; 32],
+            valid: true,
+            position: 5,
+            key: b"key".to_vec(),
+        });
+        values.elf_hash = Some([5; 32]);
+        values.trie = Some(TrieEntry {
+            key: b"trie key".to_vec(),
+            value: b"trie value".to_vec(),
+        });
+        values.eip712_digest = Some([6; 32]);
+        values.leaf_hidden = true;
+        values.updates = Some(UpdateChain {
+            status: UpdateStatus::Inconsistent,
+            count: 3,
+            final_root: [7; 32],
+        });
+        values.authorization = Some(SignerCommitment {
+            address: [8; 20],
+            signature_valid: true,
+            authorized: false,
+        });
+        values.proof_stats = Some(ProofStats {
+            leaves: 2,
+            distinct_siblings: 5,
+        });
+        values.neighbors = Some(NeighborCommitment {
+            prev: None,
+            next: Some([9; 32]),
+            ordered: true,
+        });
+        values.kzg = Some(KzgCommitment {
+            commitment: [10; 32],
+            tau_g2: [11; 64],
+            point: 5,
+            valid: true,
+        });
+        values.canonicalization = Some(Canonicalization::Trim);
+        values.forest = Some(ForestCommitment {
+            status: ForestStatus::Mismatch,
+            global_index: 12,
+            tree: 1,
+            sizes_hash: [12; 32],
+        });
+        values.bls = Some(BlsCommitment {
+            public_key: [13; 48],
+            signed: SignedValue::Root,
+            valid: false,
+        });
+        values.vdf = Some(VdfCommitment {
+            challenge: [14; 32],
+            iterations: 1000,
+            output: [15; 256],
+            valid: true,
+        });
+        values.promotions = Some(Promotions {
+            depth: 4,
+            promoted: 0b1010,
+        });
+        values.cap = Some(CapNode {
+            cap_level: 2,
+            position: 1,
+            node: [16; 32],
+        });
+        values.pair = Some(PairCommitment {
+            status: PairStatus::Reversed,
+            index_a: 5,
+            index_b: 2,
+            leaf_b: [17; 32],
+        });
+        values.bloom = Some(BloomCommitment {
+            filter_hash: [18; 32],
+            bits: 1024,
+            hashes: 3,
+            passed: true,
+        });
+        values.transform = Some(TransformCommitment {
+            spec_hash: [19; 32],
+            derived: true,
+        });
+        values.checkpoint = Some(CheckpointCommitment {
+            checkpoint_root: [20; 32],
+            index: 2,
+            count: 3,
+            valid: true,
+        });
+        values.diagnostics = Some(vec![
+            Diagnostic::CapUnsupported,
+            Diagnostic::FlagsUnsupported,
+        ]);
+        values.reveal = Some(RevealCommitment {
+            phase: RevealPhase::Reveal,
+            matches: true,
+            value: b"revealed".to_vec(),
+        });
+        values.range = Some(RangeCommitment {
+            first: 1,
+            last: 6,
+            count: 2,
+            verified: true,
+            prefix: vec![0xab],
+        });
+        values.imt = Some(ImtCommitment {
+            value: [21; 32],
+            non_member: true,
+        });
+        values.batch = Some(vec![
+            BatchLeaf {
+                index: 5,
+                leaf: [2; 32],
+            },
+            BatchLeaf {
+                index: 7,
+                leaf: [22; 32],
+            },
+        ]);
+        values.blocklist = Some(BlocklistCommitment {
+            root: [23; 32],
+            total_leaves: 4,
+            excluded: true,
+        });
+        values.leaf_count = Some(LeafCount {
+            total_leaves: 8,
+            verified: true,
+        });
+        values.erasure = Some(ErasureSampleCommitment {
+            data_chunks: 4,
+            total_chunks: 9,
+            chunk_size: 64,
+            index: 5,
+            sampled: true,
+        });
+        values.personalization_hash = Some([24; 32]);
+        values.absence = Some(AbsenceCommitment {
+            target: [25; 32],
+            absent: false,
+        });
+        values.aggregate = Some(AggregateCommitment {
+            total: 3,
+            verified: 1,
+            entries_hash: [26; 32],
+            failed: vec![0, 2],
+        });
+        values.sparse = Some(SparseEntry {
+            key: [27; 32],
+            value: [0; 32],
+            is_member: false,
+        });
+        values.append = Some(AppendCommitment {
+            valid: true,
+            count: 2,
+            new_root: [28; 32],
+        });
+        values.preimage = Some(PreimageCommitment {
+            data_hash: [29; 32],
+            length: 11,
+            hashes_to_leaf: true,
+        });
+        values.mmr = Some(MmrEntry {
+            mmr_size: 16,
+            position: 8,
+        });
+        values
+    }
+
+    #[test]
+    fn every_record_round_trips() {
+        let values = every_record();
+        assert_eq!(PublicValues::from_bytes(&values.to_bytes()), Ok(values));
+    }
+
+    #[test]
+    fn each_record_round_trips_alone() {
+        // Clearing all but one record at a time catches a decoder that relies on a neighbour.
+        let full = every_record();
+        let core = PublicValues {
+            leaf_index: full.leaf_index,
+            total_leaves: full.total_leaves,
+            ..PublicValues::new(full.root, full.leaf, full.is_valid)
+        };
+        let bytes = full.to_bytes();
+        let mut rest = &bytes[CORE_LEN..];
+        while !rest.is_empty() {
+            let len = 3 + u16::from_be_bytes([rest[1], rest[2]]) as usize;
+            let alone = [&core.to_bytes()[..], &rest[..len]].concat();
+            let decoded = PublicValues::from_bytes(&alone).unwrap();
+            assert_eq!(decoded.to_bytes(), alone, "tag 0x{:02x}", rest[0]);
+            rest = &rest[len..];
+        }
+    }
+
+    #[test]
+    fn strict_and_field_packed_round_trip_the_core() {
+        let mut values = PublicValues::new([1; 32], [2; 32], true);
+        values.leaf_index = 5;
+        values.total_leaves = 9;
+        values.strict = true;
+        assert_eq!(values.to_bytes().len(), STRICT_LEN);
+        assert_eq!(
+            PublicValues::from_bytes(&values.to_bytes()),
+            Ok(values.clone())
+        );
+
+        values.strict = false;
+        values.field_packed = true;
+        values.is_valid = false;
+        assert_eq!(values.to_bytes().len(), FIELD_PACKED_LEN);
+        assert_eq!(PublicValues::from_bytes(&values.to_bytes()), Ok(values));
+    }
+
+    #[test]
+    fn abi_encoding_round_trips() {
+        let mut values = every_record();
+        values.abi_encoded = true;
+        assert_eq!(PublicValues::from_abi_bytes(&values.to_bytes()), Ok(values));
+        assert_eq!(
+            PublicValues::from_abi_bytes(&[0; 31]),
+            Err(DecodeError::InvalidAbi)
+        );
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() {
+        // One byte short of the core is a strict proof, so cut two.
+        let bytes = every_record().to_bytes();
+        assert_eq!(
+            PublicValues::from_bytes(&bytes[..CORE_LEN - 2]),
+            Err(DecodeError::Truncated)
+        );
+        // A record header cut short, then a payload cut short.
+        assert_eq!(
+            PublicValues::from_bytes(&bytes[..CORE_LEN + 2]),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(
+            PublicValues::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::Truncated)
+        );
+    }
+
+    #[test]
+    fn unknown_tag_is_rejected() {
+        let mut bytes = PublicValues::new([1; 32], [2; 32], true).to_bytes();
+        bytes.extend_from_slice(&[0xff, 0, 1, 0]);
+        assert_eq!(
+            PublicValues::from_bytes(&bytes),
+            Err(DecodeError::UnknownTag(0xff))
+        );
+    }
+
+    #[test]
+    fn length_mismatch_is_rejected() {
+        let mut bytes = PublicValues::new([1; 32], [2; 32], true).to_bytes();
+        // An ELF hash record one byte short of a hash.
+        bytes.extend_from_slice(&[TAG_ELF_HASH, 0, 31]);
+        bytes.extend_from_slice(&[5; 31]);
+        assert_eq!(
+            PublicValues::from_bytes(&bytes),
+            Err(DecodeError::InvalidRecord(TAG_ELF_HASH))
+        );
+    }
+
+    #[test]
+    fn each_flag_bit_round_trips() {
+        for (bit, label) in FLAG_LABELS.iter().enumerate() {
+            let mut values = PublicValues::new([1; 32], [2; 32], true);
+            values.results = Some(BTreeMap::from([(*label, 1)]));
+            values.results_packed = true;
+            let bytes = values.to_bytes();
+            assert_eq!(&bytes[CORE_LEN..], &[TAG_FLAGS, 0, 1, 1 << bit]);
+
+            // The decoded map has every label, the unset ones as 0.
+            let decoded = PublicValues::from_bytes(&bytes).unwrap();
+            let results = decoded.results.unwrap();
+            assert_eq!(results.len(), FLAG_LABELS.len());
+            for (other, &result) in &results {
+                assert_eq!(result, (other == label) as u8);
+            }
+            assert!(decoded.results_packed);
+        }
+    }
+
+    #[test]
+    fn flags_record_must_be_one_byte() {
+        let mut bytes = PublicValues::new([1; 32], [2; 32], true).to_bytes();
+        bytes.extend_from_slice(&[TAG_FLAGS, 0, 2, 1, 0]);
+        assert_eq!(
+            PublicValues::from_bytes(&bytes),
+            Err(DecodeError::InvalidRecord(TAG_FLAGS))
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Counting the leaves of a contiguous range that satisfy a byte-prefix predicate.
+//!
+//! The host proves every leaf at positions `first..=last` in the tree's scheme and names a prefix of
+//! at most [`MAX_PREFIX_LEN`] bytes. The guest verifies each proof and counts the verified leaves
+//! whose bytes start with the prefix, so the committed count attests that at least that many leaves
+//! of the range satisfy the predicate, without revealing them. An empty prefix matches every leaf.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Hash;
+
+/// The longest prefix a predicate can test, the length of a leaf.
+pub const MAX_PREFIX_LEN: usize = 32;
+
+/// The leaves of a range with their inclusion proofs, passed to the guest in
+/// [`crate::Options::range_count`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeCount {
+    /// The position of the first leaf of the range.
+    pub first: u64,
+    /// The leaves at `first, first + 1, ...`, one per position of the range.
+    pub leaves: Vec<Hash>,
+    /// The inclusion proof of each leaf.
+    pub proofs: Vec<Vec<u8>>,
+    /// The prefix a leaf must start with to be counted.
+    pub prefix: Vec<u8>,
+}
+
+impl RangeCount {
+    /// The position of the last leaf of the range, inclusive.
+    pub fn last(&self) -> u64 {
+        (self.first + self.leaves.len() as u64).saturating_sub(1)
+    }
+
+    /// Returns whether `leaf` satisfies the predicate.
+    pub fn matches(&self, leaf: &Hash) -> bool {
+        leaf.starts_with(&self.prefix)
+    }
+}
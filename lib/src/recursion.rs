@@ -0,0 +1,69 @@
+//! The public values of the aggregation program, which verifies compressed proofs of the Merkle
+//! program with SP1 recursion and commits their public values together.
+//!
+//! The aggregation program commits:
+//!
+//! ```text
+//! vkey_digest (32) || count (4) || (length (4) || public_values)*
+//! ```
+//!
+//! `vkey_digest` is the Merkle program's verifying key digest as eight big-endian words, as
+//! returned by `SP1VerifyingKey::hash_u32`, and each child's public values are the bytes its proof
+//! committed, as decoded by [`crate::PublicValues::from_bytes`], in the order they were verified.
+//! Counts and lengths are big-endian. A verifier of the aggregated proof must also check that
+//! `vkey_digest` is the Merkle program it trusts, or the children could be proofs of any program.
+
+use alloc::vec::Vec;
+
+use crate::public_values::DecodeError;
+
+/// The decoded public values of the aggregation program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregatedValues {
+    pub vkey_digest: [u32; 8],
+    pub public_values: Vec<Vec<u8>>,
+}
+
+impl AggregatedValues {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out: Vec<u8> = self
+            .vkey_digest
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        out.extend_from_slice(&(self.public_values.len() as u32).to_be_bytes());
+        for values in &self.public_values {
+            out.extend_from_slice(&(values.len() as u32).to_be_bytes());
+            out.extend_from_slice(values);
+        }
+        out
+    }
+
+    /// Parses bytes produced by [`AggregatedValues::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut rest = bytes;
+        let mut take = |len: usize| {
+            if rest.len() < len {
+                return Err(DecodeError::Truncated);
+            }
+            let (head, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(head)
+        };
+        let mut vkey_digest = [0; 8];
+        for word in &mut vkey_digest {
+            *word = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        }
+        let count = u32::from_be_bytes(take(4)?.try_into().unwrap());
+        let public_values = (0..count)
+            .map(|_| {
+                let len = u32::from_be_bytes(take(4)?.try_into().unwrap());
+                Ok(take(len as usize)?.to_vec())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            vkey_digest,
+            public_values,
+        })
+    }
+}
@@ -0,0 +1,41 @@
+//! Two-phase commit-reveal over tree leaves.
+//!
+//! Each leaf is a commitment
+//!
+//! ```text
+//! commitment = SHA-256(salt || value)
+//! ```
+//!
+//! to a value with a 32-byte salt. The salt has a fixed length, so the split between salt and value
+//! is unambiguous, and since a commitment is the default leaf hash of the data `salt || value`, a
+//! tree of commitments is an ordinary tree over that data. In the commit phase the guest proves a
+//! commitment is in the tree without learning anything about it. In the reveal phase it is also
+//! given the salt and value, checks that they open the proven leaf, and commits the value.
+//!
+//! The commitment hides the value only as long as the salt is uniformly random and kept secret until
+//! the reveal; without it, low-entropy values could be found by hashing candidates. A salt must not
+//! be reused across commitments. The salt is a private input and is never committed, but once a
+//! value is revealed, anyone holding the salt and value can recompute the commitment.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// A salt and value opening a commitment, passed to the guest in [`crate::Options::reveal`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reveal {
+    pub salt: Hash,
+    pub value: Vec<u8>,
+}
+
+impl Reveal {
+    /// Returns `SHA-256(salt || value)`.
+    pub fn commitment(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt);
+        hasher.update(&self.value);
+        hasher.finalize()
+    }
+}
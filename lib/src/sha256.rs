@@ -0,0 +1,236 @@
+//! SHA-256 over a pluggable message schedule and compression function.
+//!
+//! SP1 accelerates SHA-256 with two precompiles, one extending a block's 16 words into the 64-word
+//! message schedule and one running the 64 rounds on the state, matching [`extend`] and
+//! [`compress`]. [`digest`] pads the message and drives either pair; with the functions here it
+//! agrees with the `sha2` crate.
+//!
+//! Every SHA-256 of this crate and the guest goes through [`hash`] and [`Sha256`], which drive the
+//! precompiles in the zkVM and run `sha2` elsewhere, so the guest is accelerated wherever it
+//! hashes.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rs_merkle::Hasher;
+
+use crate::Hash;
+
+/// Whether [`hash`] runs in software in the zkVM, set by [`set_software`].
+static SOFTWARE: AtomicBool = AtomicBool::new(false);
+
+/// Makes [`hash`] and [`Sha256`] run on `sha2` rather than the precompiles in the zkVM, so that the
+/// cycles the precompiles save can be measured. Has no effect elsewhere.
+pub fn set_software(software: bool) {
+    SOFTWARE.store(software, Ordering::Relaxed);
+}
+
+/// Returns the SHA-256 digest of `data`, on the precompiles in the zkVM unless [`set_software`]
+/// turned them off.
+pub fn hash(data: &[u8]) -> Hash {
+    #[cfg(target_os = "zkvm")]
+    if !SOFTWARE.load(Ordering::Relaxed) {
+        // SAFETY: the precompiles only read and write the words and state they are given.
+        return digest(
+            data,
+            |w| unsafe { sp1_lib::syscall_sha256_extend(w) },
+            |w, state| unsafe { sp1_lib::syscall_sha256_compress(w, state) },
+        );
+    }
+    <sha2::Sha256 as sha2::Digest>::digest(data).into()
+}
+
+/// The `rs_merkle` hasher of the SHA-256 tree, shared by the guest and the host.
+#[derive(Clone)]
+pub struct Sha256Hasher;
+
+impl Hasher for Sha256Hasher {
+    type Hash = Hash;
+
+    fn hash(data: &[u8]) -> Hash {
+        hash(data)
+    }
+}
+
+/// An incremental SHA-256 with the methods of `sha2::Sha256` this crate uses, hashing as [`hash`]
+/// does. In the zkVM the message is buffered, since the precompiles are driven over all of it.
+#[derive(Clone, Default)]
+pub struct Sha256 {
+    #[cfg(target_os = "zkvm")]
+    data: alloc::vec::Vec<u8>,
+    #[cfg(not(target_os = "zkvm"))]
+    hasher: sha2::Sha256,
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the SHA-256 digest of `data`.
+    pub fn digest(data: impl AsRef<[u8]>) -> Hash {
+        hash(data.as_ref())
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        #[cfg(target_os = "zkvm")]
+        self.data.extend_from_slice(data.as_ref());
+        #[cfg(not(target_os = "zkvm"))]
+        sha2::Digest::update(&mut self.hasher, data);
+    }
+
+    pub fn chain_update(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.update(data);
+        self
+    }
+
+    pub fn finalize(self) -> Hash {
+        #[cfg(target_os = "zkvm")]
+        return hash(&self.data);
+        #[cfg(not(target_os = "zkvm"))]
+        sha2::Digest::finalize(self.hasher).into()
+    }
+}
+
+/// The initial hash value.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Extends the first 16 words of `w` into the full message schedule.
+pub fn extend(w: &mut [u32; 64]) {
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+}
+
+/// Runs the 64 rounds of a block's message schedule on `state` and adds the result to it.
+pub fn compress(w: &mut [u32; 64], state: &mut [u32; 8]) {
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+/// Returns the SHA-256 digest of `data`, computed with the given schedule extension and
+/// compression.
+pub fn digest(
+    data: &[u8],
+    mut extend: impl FnMut(&mut [u32; 64]),
+    mut compress: impl FnMut(&mut [u32; 64], &mut [u32; 8]),
+) -> Hash {
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    padded.resize((padded.len() + 8).div_ceil(64) * 64 - 8, 0);
+    padded.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    let mut state = IV;
+    for block in padded.chunks_exact(64) {
+        let mut w = [0; 64];
+        for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        extend(&mut w);
+        compress(&mut w, &mut state);
+    }
+
+    let mut digest = [0; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(&state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    /// The one-block, empty and two-block messages of FIPS 180-2, with their digests.
+    const VECTORS: [(&[u8], &str); 3] = [
+        (
+            b"abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            b"",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+    ];
+
+    #[test]
+    fn digest_matches_known_answers() {
+        for (message, expected) in VECTORS {
+            let expected = hex::decode(expected).unwrap();
+            assert_eq!(digest(message, extend, compress).as_slice(), expected);
+            assert_eq!(hash(message).as_slice(), expected);
+        }
+        let million = vec![b'a'; 1_000_000];
+        assert_eq!(
+            hex::encode(digest(&million, extend, compress)),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn padding_boundaries_match_sha2() {
+        // Lengths around the point where the length field no longer fits in the last block.
+        for len in [55, 56, 63, 64, 65, 119, 120, 128] {
+            let data = vec![0x5a; len];
+            assert_eq!(
+                digest(&data, extend, compress),
+                hash(&data),
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn incremental_hashing_matches_hash() {
+        let (message, _) = VECTORS[2];
+        let (head, tail) = message.split_at(13);
+        assert_eq!(
+            Sha256::new()
+                .chain_update(head)
+                .chain_update(tail)
+                .finalize(),
+            hash(message)
+        );
+        assert_eq!(Sha256::digest(message), hash(message));
+    }
+}
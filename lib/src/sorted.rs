@@ -0,0 +1,95 @@
+//! Neighbor proofs for trees whose leaves are sorted in ascending byte order.
+//!
+//! Proving the leaves at `index - 1` and `index + 1` next to the target and checking
+//! `prev < leaf < next` attests that the target sits at its correct position in the sorted set. The
+//! first leaf has no `prev` and the last has no `next`; a neighbor may only be omitted at those
+//! boundaries.
+//!
+//! The same ordering proves a value absent: two adjacent leaves with `low < target < high` leave no
+//! room for `target` between them. A target below the first leaf has no `low`, and one above the
+//! last leaf has no `high`.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Hash;
+
+/// A neighboring leaf and its inclusion proof in the tree's scheme.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbor {
+    pub leaf: Hash,
+    pub proof_bytes: Vec<u8>,
+}
+
+/// The neighbors of the proven leaf, passed to the guest in [`crate::Options::neighbors`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbors {
+    /// The leaf at `index - 1`, absent for the first leaf.
+    pub prev: Option<Neighbor>,
+    /// The leaf at `index + 1`, absent for the last leaf.
+    pub next: Option<Neighbor>,
+}
+
+/// A second leaf of the tree and its inclusion proof, for attesting that it comes after the proven
+/// leaf, passed to the guest in [`crate::Options::pair`], or for bracketing an [`Absence`].
+///
+/// Unlike neighbors, the pair is ordered by index rather than by value, so the leaves need not be
+/// sorted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairedLeaf {
+    pub index: u64,
+    pub leaf: Hash,
+    pub proof_bytes: Vec<u8>,
+}
+
+/// A target value and the leaves bracketing it, passed to the guest in [`crate::Options::absence`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Absence {
+    pub target: Hash,
+    /// The largest leaf below `target`, absent when `target` is below the first leaf.
+    pub low: Option<PairedLeaf>,
+    /// The smallest leaf above `target`, absent when `target` is above the last leaf.
+    pub high: Option<PairedLeaf>,
+}
+
+impl Absence {
+    /// Returns whether the bracketing leaves are strictly ordered around `target` at consecutive
+    /// indices, and only omitted at the boundaries of the tree. Their inclusion is not checked.
+    pub fn brackets(&self, total_leaves: usize) -> bool {
+        let total_leaves = total_leaves as u64;
+        match (&self.low, &self.high) {
+            (Some(low), Some(high)) => {
+                low.index.checked_add(1) == Some(high.index)
+                    && high.index < total_leaves
+                    && low.leaf < self.target
+                    && self.target < high.leaf
+            }
+            (None, Some(high)) => high.index == 0 && total_leaves > 0 && self.target < high.leaf,
+            (Some(low), None) => {
+                low.index.checked_add(1) == Some(total_leaves) && low.leaf < self.target
+            }
+            (None, None) => false,
+        }
+    }
+}
+
+/// Returns whether the neighbors are strictly ordered around `leaf` and only omitted at the
+/// boundaries of the tree.
+pub fn is_ordered(
+    prev: Option<&Hash>,
+    leaf: &Hash,
+    next: Option<&Hash>,
+    index: usize,
+    total_leaves: usize,
+) -> bool {
+    let prev_ok = match prev {
+        Some(prev) => index > 0 && prev < leaf,
+        None => index == 0,
+    };
+    let next_ok = match next {
+        Some(next) => index + 1 < total_leaves && leaf < next,
+        None => index + 1 == total_leaves,
+    };
+    prev_ok && next_ok
+}
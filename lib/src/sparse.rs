@@ -0,0 +1,259 @@
+//! Sparse Merkle trees over 256-bit keys, for key-value commitments.
+//!
+//! A sparse tree has one leaf per 256-bit key, reached by the key's bits from the most significant
+//! one: bit `d` picks the child at depth `d`, `0` for the left one. Only keys holding a value have a
+//! nonzero leaf, and every empty subtree hashes to zero:
+//!
+//! ```text
+//! leaf(key, value) = SHA-256(0x00 || key || value)
+//! parent(l, r)     = 0                          if l = r = 0
+//!                  = SHA-256(0x01 || l || r)    otherwise
+//! ```
+//!
+//! The empty tree therefore has the zero root and building a tree costs [`DEPTH`] hashes per entry.
+//! A proof holds the [`DEPTH`] siblings of the key's path, compressed as a bitmap with bit `d` set
+//! when the sibling at depth `d` is nonzero, followed by the nonzero siblings from the root down.
+//! The same proof with the zero leaf shows that a key holds no value.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// The depth of the tree, one level per key bit.
+pub const DEPTH: usize = 256;
+
+/// Returns bit `depth` of `key`, counting from the most significant.
+fn bit(key: &Hash, depth: usize) -> bool {
+    key[depth / 8] >> (7 - depth % 8) & 1 == 1
+}
+
+/// Returns the leaf of `key` holding `value`.
+pub fn leaf(key: &Hash, value: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([0x00])
+        .chain_update(key)
+        .chain_update(value)
+        .finalize()
+}
+
+/// Hashes two child nodes into their parent.
+pub fn parent(left: &Hash, right: &Hash) -> Hash {
+    if *left == Hash::default() && *right == Hash::default() {
+        return Hash::default();
+    }
+    Sha256::new()
+        .chain_update([0x01])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+}
+
+/// The siblings of a key's path, passed to the guest after the key and value.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseProof {
+    /// Bit `d`, counting from the most significant, is set when the sibling at depth `d` is
+    /// nonzero.
+    pub bitmap: Hash,
+    /// The nonzero siblings, from the root down.
+    pub siblings: Vec<Hash>,
+}
+
+impl SparseProof {
+    /// Returns the root of the tree in which `key` has the leaf `leaf`, or `None` if the bitmap
+    /// does not match the number of siblings.
+    pub fn root(&self, key: &Hash, leaf: Hash) -> Option<Hash> {
+        let nonzero = self
+            .bitmap
+            .iter()
+            .map(|byte| byte.count_ones())
+            .sum::<u32>();
+        if nonzero as usize != self.siblings.len() {
+            return None;
+        }
+        let mut siblings = self.siblings.iter().rev();
+        let mut node = leaf;
+        for depth in (0..DEPTH).rev() {
+            let sibling = if bit(&self.bitmap, depth) {
+                *siblings.next()?
+            } else {
+                Hash::default()
+            };
+            node = if bit(key, depth) {
+                parent(&sibling, &node)
+            } else {
+                parent(&node, &sibling)
+            };
+        }
+        Some(node)
+    }
+}
+
+/// Verifies that `key` holds `value` under `root`, or holds no value when `value` is `None`.
+pub fn verify(root: Hash, key: &Hash, value: Option<&Hash>, proof: &SparseProof) -> bool {
+    let leaf = value.map_or(Hash::default(), |value| leaf(key, value));
+    proof.root(key, leaf) == Some(root)
+}
+
+/// A sparse tree built on the host, holding its entries sorted by key.
+pub struct SparseTree {
+    entries: Vec<(Hash, Hash)>,
+}
+
+impl SparseTree {
+    /// Builds a tree over `(key, value)` entries, whose keys must be distinct.
+    pub fn from_entries(mut entries: Vec<(Hash, Hash)>) -> Self {
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        assert!(
+            entries.windows(2).all(|pair| pair[0].0 != pair[1].0),
+            "sparse tree keys must be distinct"
+        );
+        Self { entries }
+    }
+
+    pub fn root(&self) -> Hash {
+        subtree(&self.entries, 0)
+    }
+
+    /// Returns the value `key` holds, if any.
+    pub fn get(&self, key: &Hash) -> Option<&Hash> {
+        self.entries
+            .binary_search_by_key(key, |&(key, _)| key)
+            .ok()
+            .map(|index| &self.entries[index].1)
+    }
+
+    /// Returns the proof of `key`'s path, whether or not it holds a value.
+    pub fn proof(&self, key: &Hash) -> SparseProof {
+        let mut proof = SparseProof {
+            bitmap: Hash::default(),
+            siblings: Vec::new(),
+        };
+        let mut entries = &self.entries[..];
+        for depth in 0..DEPTH {
+            let (left, right) = entries.split_at(entries.partition_point(|(k, _)| !bit(k, depth)));
+            let (path, other) = if bit(key, depth) {
+                (right, left)
+            } else {
+                (left, right)
+            };
+            let sibling = subtree(other, depth + 1);
+            if sibling != Hash::default() {
+                proof.bitmap[depth / 8] |= 0x80 >> (depth % 8);
+                proof.siblings.push(sibling);
+            }
+            entries = path;
+        }
+        proof
+    }
+}
+
+/// Returns the root of the subtree at `depth` holding `entries`, which share its path and are
+/// sorted by key.
+fn subtree(entries: &[(Hash, Hash)], depth: usize) -> Hash {
+    match entries {
+        [] => Hash::default(),
+        [(key, value), ..] if depth == DEPTH => leaf(key, value),
+        _ => {
+            let (left, right) = entries.split_at(entries.partition_point(|(k, _)| !bit(k, depth)));
+            parent(&subtree(left, depth + 1), &subtree(right, depth + 1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn key(first: u8, last: u8) -> Hash {
+        let mut key = [0; 32];
+        key[0] = first;
+        key[31] = last;
+        key
+    }
+
+    fn tree() -> SparseTree {
+        // Two keys that only differ in their last bit, and two far apart.
+        SparseTree::from_entries(vec![
+            (key(0x00, 0x00), [1; 32]),
+            (key(0x00, 0x01), [2; 32]),
+            (key(0x80, 0x00), [3; 32]),
+            (key(0xff, 0xff), [4; 32]),
+        ])
+    }
+
+    #[test]
+    fn single_entry_root_walks_up_the_key_bits() {
+        let k = key(0xa5, 0x3c);
+        let mut node = leaf(&k, &[7; 32]);
+        for depth in (0..DEPTH).rev() {
+            node = match bit(&k, depth) {
+                true => parent(&Hash::default(), &node),
+                false => parent(&node, &Hash::default()),
+            };
+        }
+        assert_eq!(SparseTree::from_entries(vec![(k, [7; 32])]).root(), node);
+        assert_eq!(SparseTree::from_entries(vec![]).root(), Hash::default());
+    }
+
+    #[test]
+    fn presence_and_absence_proofs_verify() {
+        let tree = tree();
+        let root = tree.root();
+        for k in [
+            key(0x00, 0x00),
+            key(0x00, 0x01),
+            key(0x80, 0x00),
+            key(0xff, 0xff),
+        ] {
+            let value = tree.get(&k).unwrap();
+            assert!(verify(root, &k, Some(value), &tree.proof(&k)));
+        }
+        for k in [key(0x00, 0x02), key(0x40, 0x00), key(0xff, 0xfe)] {
+            assert_eq!(tree.get(&k), None);
+            assert!(verify(root, &k, None, &tree.proof(&k)));
+        }
+
+        let empty = SparseTree::from_entries(vec![]);
+        let proof = empty.proof(&key(1, 1));
+        assert!(proof.siblings.is_empty());
+        assert!(verify(Hash::default(), &key(1, 1), None, &proof));
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let tree = tree();
+        let root = tree.root();
+        let k = key(0x00, 0x01);
+        let proof = tree.proof(&k);
+
+        // Another value, or claiming the key is empty.
+        assert!(!verify(root, &k, Some(&[1; 32]), &proof));
+        assert!(!verify(root, &k, None, &proof));
+        // Another key's path, or a value for an empty key.
+        assert!(!verify(root, &key(0x00, 0x00), Some(&[2; 32]), &proof));
+        let absent = key(0x40, 0x00);
+        assert!(!verify(root, &absent, Some(&[0; 32]), &tree.proof(&absent)));
+        // A corrupted sibling.
+        let mut corrupted = proof.clone();
+        corrupted.siblings[0][0] ^= 1;
+        assert!(!verify(root, &k, Some(&[2; 32]), &corrupted));
+        // A bitmap that does not match the siblings, or that moves the last one up a level.
+        let mut mismatched = proof.clone();
+        mismatched.bitmap[16] ^= 1;
+        assert_eq!(mismatched.root(&k, leaf(&k, &[2; 32])), None);
+        let mut moved = proof;
+        let last = moved.bitmap[31];
+        moved.bitmap[31] = last.rotate_left(1);
+        assert!(!verify(root, &k, Some(&[2; 32]), &moved));
+    }
+
+    #[test]
+    #[should_panic(expected = "sparse tree keys must be distinct")]
+    fn duplicate_keys_are_rejected() {
+        SparseTree::from_entries(vec![(key(1, 1), [1; 32]), (key(1, 1), [2; 32])]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Leaf derivations as chains of hashes, e.g. `sha256,keccak256,sha256`.
+//!
+//! By default a leaf is `sha256(data)`. A [`LeafTransform`] instead applies each step of its chain
+//! to the previous step's output, starting from the leaf data, so
+//! `sha256,keccak256` derives `keccak256(sha256(data))`. The guest applies the same chain to the
+//! leaf data it is given and commits whether the result is the proven leaf, next to the hash of the
+//! canonical spec, so verifiers know which derivation was checked.
+
+use alloc::{format, string::String, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256, Sha3_256};
+
+use crate::{sha256::Sha256, Hash};
+
+/// A hash function a transform step can apply.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashStep {
+    Sha256,
+    Keccak256,
+    Sha3_256,
+}
+
+impl HashStep {
+    /// Returns the step for a spec name; `keccak` is accepted for `keccak256`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sha256" => Some(Self::Sha256),
+            "keccak256" | "keccak" => Some(Self::Keccak256),
+            "sha3-256" => Some(Self::Sha3_256),
+            _ => None,
+        }
+    }
+
+    /// The canonical spec name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Keccak256 => "keccak256",
+            Self::Sha3_256 => "sha3-256",
+        }
+    }
+
+    fn hash(self, data: &[u8]) -> Hash {
+        match self {
+            Self::Sha256 => Sha256::digest(data),
+            Self::Keccak256 => Keccak256::digest(data).into(),
+            Self::Sha3_256 => Sha3_256::digest(data).into(),
+        }
+    }
+}
+
+/// A non-empty chain of hash steps, applied first to last.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeafTransform {
+    pub steps: Vec<HashStep>,
+}
+
+impl LeafTransform {
+    /// Parses a comma-separated list of step names.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let steps = spec
+            .split(',')
+            .map(|name| {
+                let name = name.trim();
+                HashStep::from_name(name).ok_or_else(|| {
+                    format!(
+                        "unsupported transform step `{}`, expected sha256, keccak256 or sha3-256",
+                        name
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(LeafTransform { steps })
+    }
+
+    /// Applies the chain to `data`. An empty chain, which [`Self::parse`] never produces, hashes
+    /// nothing and returns zero.
+    pub fn apply(&self, data: &[u8]) -> Hash {
+        let Some((first, rest)) = self.steps.split_first() else {
+            return Hash::default();
+        };
+        rest.iter()
+            .fold(first.hash(data), |digest, step| step.hash(&digest))
+    }
+
+    /// The canonical spec: the step names joined with commas.
+    pub fn spec(&self) -> String {
+        let names: Vec<&str> = self.steps.iter().map(|step| step.name()).collect();
+        names.join(",")
+    }
+
+    /// Returns `sha256(spec)`, as committed by the guest.
+    pub fn spec_hash(&self) -> Hash {
+        Sha256::digest(self.spec())
+    }
+}
+
+/// A transform and the data of the proven leaf, passed to the guest in
+/// [`crate::Options::leaf_transform`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransformedLeaf {
+    pub transform: LeafTransform,
+    /// The leaf data, after any canonicalization.
+    pub data: Vec<u8>,
+}
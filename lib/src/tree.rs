@@ -0,0 +1,148 @@
+//! Scheme-agnostic binary tree helpers.
+//!
+//! The shape matches `rs_merkle`: nodes are paired left-to-right and an unpaired last node is
+//! promoted to the next level unchanged. Only the parent function differs between schemes.
+
+use alloc::{vec, vec::Vec};
+
+use crate::{sha256::Sha256, Hash};
+
+/// The parent function of the standard scheme, `sha256(left || right)`, as used by `rs_merkle`.
+pub fn sha256_parent(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
+}
+
+/// Builds every level of the tree bottom-up, starting with the leaves and ending with the root.
+pub fn build_levels(leaves: Vec<Hash>, parent: impl Fn(&Hash, &Hash) -> Hash) -> Vec<Vec<Hash>> {
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => parent(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Returns the sibling hashes needed to recompute the root from the leaf at `index`, bottom-up.
+///
+/// Levels where the node is promoted without a sibling contribute no hash, so the path has the same
+/// layout as the hashes of a single-leaf `rs_merkle` proof.
+pub fn proof_path(levels: &[Vec<Hash>], mut index: usize) -> Vec<Hash> {
+    let mut path = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            path.push(*sibling);
+        }
+        index /= 2;
+    }
+    path
+}
+
+/// Returns, bottom-up, whether the node on the path from the leaf at `index` is promoted without a
+/// sibling at each level below the root.
+pub fn promoted_levels(mut index: usize, total_leaves: usize) -> Vec<bool> {
+    let mut promoted = Vec::new();
+    let mut width = total_leaves;
+    while width > 1 {
+        promoted.push((index ^ 1) >= width);
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    promoted
+}
+
+/// Returns the number of siblings on the path from the leaf at `index`, one per level at which its
+/// node is not promoted.
+pub fn path_len(index: usize, total_leaves: usize) -> usize {
+    promoted_levels(index, total_leaves)
+        .into_iter()
+        .filter(|&promoted| !promoted)
+        .count()
+}
+
+/// Returns the number of levels above the leaves in a tree of `total_leaves` leaves.
+pub fn height(total_leaves: usize) -> usize {
+    total_leaves.next_power_of_two().trailing_zeros() as usize
+}
+
+/// Recomputes the node `height` levels above a leaf, where node `index >> height` of that level
+/// sits, from the leaf and the bottom of its sibling path.
+///
+/// Only the siblings below that level are used; any further hashes in `path` are ignored. Returns
+/// `None` if the index is out of range or the path is too short.
+pub fn node_at_height(
+    leaf: Hash,
+    mut index: usize,
+    total_leaves: usize,
+    path: &[Hash],
+    height: usize,
+    parent: impl Fn(&Hash, &Hash) -> Hash,
+) -> Option<Hash> {
+    if index >= total_leaves {
+        return None;
+    }
+
+    let mut node = leaf;
+    let mut width = total_leaves;
+    let mut siblings = path.iter();
+    for _ in 0..height {
+        let sibling_index = index ^ 1;
+        if sibling_index < width {
+            let sibling = siblings.next()?;
+            node = if index % 2 == 0 {
+                parent(&node, sibling)
+            } else {
+                parent(sibling, &node)
+            };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    Some(node)
+}
+
+/// Recomputes the root from a leaf, its index and its sibling path.
+///
+/// Returns `None` if the index is out of range or the path does not have exactly one hash per
+/// level that has a sibling.
+pub fn root_from_path(
+    leaf: Hash,
+    mut index: usize,
+    total_leaves: usize,
+    path: &[Hash],
+    parent: impl Fn(&Hash, &Hash) -> Hash,
+) -> Option<Hash> {
+    if index >= total_leaves {
+        return None;
+    }
+
+    let mut node = leaf;
+    let mut width = total_leaves;
+    let mut siblings = path.iter();
+    while width > 1 {
+        let sibling_index = index ^ 1;
+        if sibling_index < width {
+            let sibling = siblings.next()?;
+            node = if index % 2 == 0 {
+                parent(&node, sibling)
+            } else {
+                parent(sibling, &node)
+            };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+
+    siblings.next().is_none().then_some(node)
+}
@@ -0,0 +1,100 @@
+//! SHA-256 trees whose nodes are truncated to `n` bytes.
+//!
+//! Every node, leaves included, keeps only the first `n` bytes of its SHA-256 digest:
+//!
+//! ```text
+//! parent(l, r) = sha256(l || r)[0..n]
+//! ```
+//!
+//! where `l` and `r` are the `n`-byte children. Outside of hashing, nodes are carried as 32-byte
+//! values left-padded with zeros, which is also how the leaf and root are committed. Proofs pack
+//! each sibling as its `n` meaningful bytes.
+//!
+//! Truncation trades security for space. An `n`-byte node offers roughly `8n` bits of
+//! second-preimage resistance but only `4n` bits of collision resistance, and a prover who builds
+//! the tree can use a collision to prove inclusion of a leaf that was never inserted. 16 bytes
+//! (`2^64` work) is within reach of a well-funded attacker; 20 bytes (`2^80`) is a practical
+//! minimum when tree builders are untrusted.
+
+use alloc::vec::Vec;
+
+use crate::{sha256::Sha256, tree, Hash};
+
+/// Returns whether `n` is a supported node length.
+pub fn is_valid_len(n: u8) -> bool {
+    (1..=32).contains(&n)
+}
+
+/// Truncates a full digest to `n` bytes and left-pads it back to 32 bytes.
+pub fn truncate(digest: &Hash, n: u8) -> Hash {
+    let n = n as usize;
+    let mut node = [0u8; 32];
+    node[32 - n..].copy_from_slice(&digest[..n]);
+    node
+}
+
+/// Hashes two left-padded `n`-byte children into their left-padded parent.
+pub fn parent(left: &Hash, right: &Hash, n: u8) -> Hash {
+    let start = 32 - n as usize;
+    let mut hasher = Sha256::new();
+    hasher.update(&left[start..]);
+    hasher.update(&right[start..]);
+    truncate(&hasher.finalize(), n)
+}
+
+/// A truncated tree built on the host, keeping every level for proof generation.
+pub struct TruncatedTree {
+    levels: Vec<Vec<Hash>>,
+    n: u8,
+}
+
+impl TruncatedTree {
+    /// Builds a tree over leaves that are already truncated with [`truncate`].
+    pub fn from_leaves(leaves: &[Hash], n: u8) -> Self {
+        assert!(
+            is_valid_len(n),
+            "hash length must be between 1 and 32 bytes"
+        );
+        Self {
+            levels: tree::build_levels(leaves.to_vec(), |l, r| parent(l, r, n)),
+            n,
+        }
+    }
+
+    pub fn root(&self) -> Option<Hash> {
+        self.levels.last()?.first().copied()
+    }
+
+    /// Returns the sibling path for `index`, each sibling packed as its `n` meaningful bytes.
+    pub fn proof_bytes(&self, index: usize) -> Vec<u8> {
+        let start = 32 - self.n as usize;
+        tree::proof_path(&self.levels, index)
+            .iter()
+            .flat_map(|node| node[start..].to_vec())
+            .collect()
+    }
+}
+
+/// Verifies a proof produced by [`TruncatedTree::proof_bytes`].
+pub fn verify(
+    root: Hash,
+    leaf: Hash,
+    proof_bytes: &[u8],
+    index: usize,
+    total_leaves: usize,
+    n: u8,
+) -> bool {
+    if !is_valid_len(n) || proof_bytes.len() % n as usize != 0 {
+        return false;
+    }
+    let path: Vec<Hash> = proof_bytes
+        .chunks_exact(n as usize)
+        .map(|chunk| {
+            let mut node = [0u8; 32];
+            node[32 - chunk.len()..].copy_from_slice(chunk);
+            node
+        })
+        .collect();
+
+    tree::root_from_path(leaf, index, total_leaves, &path, |l, r| parent(l, r, n)) == Some(root)
+}
@@ -0,0 +1,55 @@
+//! A chain of sequential updates to a single leaf.
+//!
+//! Replacing one leaf leaves every sibling on its path unchanged, so the same sibling path serves
+//! every root in the chain `root_0 -> root_1 -> ... -> root_k`. Each step first checks that the
+//! current leaf recomputes the current root, then replaces the leaf and recomputes the root.
+
+use crate::{
+    public_values::{UpdateChain, UpdateStatus},
+    tree, Hash,
+};
+
+/// Applies `updates` in order to the leaf at `index`, starting from `root` and `leaf`.
+///
+/// Stops at the first step whose current leaf does not recompute the current root, in which case
+/// the committed final root is the last root that was verified, or `root` if the initial
+/// verification failed.
+pub fn apply(
+    root: Hash,
+    leaf: Hash,
+    index: usize,
+    total_leaves: usize,
+    path: &[Hash],
+    updates: &[Hash],
+    parent: impl Fn(&Hash, &Hash) -> Hash,
+) -> UpdateChain {
+    let recompute = |leaf: Hash| tree::root_from_path(leaf, index, total_leaves, path, &parent);
+
+    let (mut current_root, mut current_leaf) = (root, leaf);
+    for (count, update) in updates.iter().enumerate() {
+        if recompute(current_leaf) != Some(current_root) {
+            let status = match count {
+                0 => UpdateStatus::InvalidInitialRoot,
+                _ => UpdateStatus::Inconsistent,
+            };
+            return UpdateChain {
+                status,
+                count: count as u32,
+                final_root: current_root,
+            };
+        }
+        current_root = recompute(*update).expect("path was verified");
+        current_leaf = *update;
+    }
+
+    let status = match recompute(current_leaf) == Some(current_root) {
+        true => UpdateStatus::Applied,
+        false if updates.is_empty() => UpdateStatus::InvalidInitialRoot,
+        false => UpdateStatus::Inconsistent,
+    };
+    UpdateChain {
+        status,
+        count: updates.len() as u32,
+        final_root: current_root,
+    }
+}
@@ -0,0 +1,296 @@
+//! Wesolowski verifiable delay functions over the RSA-2048 group.
+//!
+//! Evaluating the VDF for `T` iterations computes `y = x^(2^T) mod N` with `T` sequential squarings,
+//! which cannot be parallelized without factoring `N`. The modulus is the RSA-2048 challenge number,
+//! whose factorization is unknown, so no trusted setup is needed. The input is bound to the proof:
+//!
+//! ```text
+//! challenge = SHA-256(seed || root || leaf)
+//! x         = challenge, read as a big-endian integer
+//! ```
+//!
+//! where `seed` is host-supplied, e.g. a recent block hash, so that the delay starts no earlier than
+//! the seed was known. Verification is cheap: with the prime `l` derived from `x`, `y` and `T` by
+//! [`challenge_prime`] and `r = 2^T mod l`, the proof `π = x^floor(2^T / l)` is valid when
+//! `π^l · x^r = ±y mod N`, which takes two exponentiations by 128-bit exponents whatever `T` is.
+//!
+//! An element and its negation cannot be told apart without knowing the group order, so outputs are
+//! taken up to sign: the canonical output is the smaller of `y` and `N - y`.
+
+use alloc::{vec, vec::Vec};
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256::Sha256, Hash};
+
+/// The RSA-2048 challenge number, big-endian.
+pub const MODULUS: [u8; 256] = hex_modulus();
+
+/// The byte length of group elements.
+pub const ELEMENT_LEN: usize = 256;
+
+/// A VDF evaluation over the proof's challenge, with its proof of correctness.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VdfProof {
+    pub seed: Hash,
+    pub iterations: u64,
+    /// The canonical output, big-endian.
+    pub output: Vec<u8>,
+    /// `π`, big-endian.
+    pub proof: Vec<u8>,
+}
+
+impl VdfProof {
+    /// Evaluates the VDF over the challenge for `root` and `leaf`. This takes `2 * iterations`
+    /// sequential squarings modulo `N`.
+    pub fn evaluate(seed: Hash, root: &Hash, leaf: &Hash, iterations: u64) -> Self {
+        let n = modulus();
+        let x = BigUint::from_bytes_be(&challenge(&seed, root, leaf));
+
+        let mut y = x.clone();
+        for _ in 0..iterations {
+            y = &y * &y % &n;
+        }
+        let y = canonical(y, &n);
+        let l = challenge_prime(&x, &y, iterations);
+
+        // π = x^floor(2^T / l), computed by long division of 2^T by l one bit at a time.
+        let two = BigUint::from(2u32);
+        let mut proof = BigUint::from(1u32);
+        let mut remainder = BigUint::from(1u32);
+        for _ in 0..iterations {
+            remainder *= &two;
+            proof = &proof * &proof % &n;
+            if remainder >= l {
+                remainder -= &l;
+                proof = proof * &x % &n;
+            }
+        }
+
+        VdfProof {
+            seed,
+            iterations,
+            output: to_element(&y),
+            proof: to_element(&proof),
+        }
+    }
+
+    /// Returns whether the output is the canonical VDF output over the challenge for `root` and
+    /// `leaf`. Cheap regardless of the number of iterations.
+    pub fn verify(&self, root: &Hash, leaf: &Hash) -> bool {
+        let n = modulus();
+        let x = BigUint::from_bytes_be(&challenge(&self.seed, root, leaf));
+        let y = BigUint::from_bytes_be(&self.output);
+        let proof = BigUint::from_bytes_be(&self.proof);
+        if self.output.len() != ELEMENT_LEN || y >= n || y != canonical(y.clone(), &n) || proof >= n
+        {
+            return false;
+        }
+
+        let l = challenge_prime(&x, &y, self.iterations);
+        let r = BigUint::from(2u32).modpow(&BigUint::from(self.iterations), &l);
+        let lhs = proof.modpow(&l, &n) * x.modpow(&r, &n) % &n;
+        canonical(lhs, &n) == y
+    }
+}
+
+/// Returns the challenge the VDF input is derived from.
+pub fn challenge(seed: &Hash, root: &Hash, leaf: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update(root);
+    hasher.update(leaf);
+    hasher.finalize()
+}
+
+/// Derives the 128-bit Fiat-Shamir prime `l`: the first prime at or above
+/// `SHA-256(x || y || T)[..16] | 1 << 127 | 1`, with `x` and `y` as 256-byte big-endian integers
+/// and `T` as 8 bytes, big-endian.
+pub fn challenge_prime(x: &BigUint, y: &BigUint, iterations: u64) -> BigUint {
+    let mut hasher = Sha256::new();
+    hasher.update(to_element(x));
+    hasher.update(to_element(y));
+    hasher.update(iterations.to_be_bytes());
+    let digest: Hash = hasher.finalize();
+
+    let mut candidate = digest[..16].to_vec();
+    candidate[0] |= 0x80;
+    candidate[15] |= 0x01;
+    let mut candidate = BigUint::from_bytes_be(&candidate);
+    let two = BigUint::from(2u32);
+    while !is_probable_prime(&candidate) {
+        candidate += &two;
+    }
+    candidate
+}
+
+/// Miller-Rabin with the primes up to 61 as bases.
+fn is_probable_prime(n: &BigUint) -> bool {
+    const BASES: [u32; 18] = [
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61,
+    ];
+    let one = BigUint::from(1u32);
+    let n_minus_one = n - &one;
+    let shift = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> shift;
+
+    BASES.iter().all(|&base| {
+        let base = BigUint::from(base);
+        if &base >= n {
+            return true;
+        }
+        let mut x = base.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            return true;
+        }
+        for _ in 1..shift {
+            x = &x * &x % n;
+            if x == n_minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+fn modulus() -> BigUint {
+    BigUint::from_bytes_be(&MODULUS)
+}
+
+/// Returns the smaller of `y` and `N - y`.
+fn canonical(y: BigUint, n: &BigUint) -> BigUint {
+    let negated = n - &y;
+    y.min(negated)
+}
+
+/// Encodes a group element as 256 bytes, big-endian.
+fn to_element(value: &BigUint) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut element = vec![0u8; ELEMENT_LEN - bytes.len()];
+    element.extend_from_slice(&bytes);
+    element
+}
+
+const fn hex_modulus() -> [u8; 256] {
+    const HEX: &[u8; 512] = b"\
+        c7970ceedcc3b0754490201a7aa613cd73911081c790f5f1a8726f463550bb5b\
+        7ff0db8e1ea1189ec72f93d1650011bd721aeeacc2acde32a04107f0648c2813\
+        a31f5b0b7765ff8b44b4b6ffc93384b646eb09c7cf5e8592d40ea33c80039f35\
+        b4f14a04b51f7bfd781be4d1673164ba8eb991c2c4d730bbbe35f592bdef524a\
+        f7e8daefd26c66fc02c479af89d64d373f442709439de66ceb955f3ea37d5159\
+        f6135809f85334b5cb1813addc80cd05609f10ac6a95ad65872c909525bdad32\
+        bc729592642920f24c61dc5b3c3b7923e56b16a4d9d373d8721f24a3fc0f1b31\
+        31f55615172866bccc30f95054c824e733a5eb6817f7bc16399d48c6361cc7e5";
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            _ => c - b'a' + 10,
+        }
+    }
+    let mut bytes = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        bytes[i] = nibble(HEX[2 * i]) << 4 | nibble(HEX[2 * i + 1]);
+        i += 1;
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: Hash = [1; 32];
+    const ROOT: Hash = [2; 32];
+    const LEAF: Hash = [3; 32];
+
+    #[test]
+    fn modulus_is_parsed_from_hex() {
+        assert_eq!(modulus().bits(), 2048);
+        assert_eq!(MODULUS[..4], [0xc7, 0x97, 0x0c, 0xee]);
+        assert_eq!(MODULUS[252..], [0x36, 0x1c, 0xc7, 0xe5]);
+    }
+
+    #[test]
+    fn evaluations_verify() {
+        for iterations in [0, 1, 2, 16, 100] {
+            let proof = VdfProof::evaluate(SEED, &ROOT, &LEAF, iterations);
+            assert!(proof.verify(&ROOT, &LEAF), "{} iterations", iterations);
+
+            // The output is x^(2^T) up to sign.
+            let n = modulus();
+            let x = BigUint::from_bytes_be(&challenge(&SEED, &ROOT, &LEAF));
+            let y = x.modpow(&(BigUint::from(1u32) << iterations as usize), &n);
+            assert_eq!(to_element(&canonical(y, &n)), proof.output);
+        }
+    }
+
+    #[test]
+    fn tampered_evaluations_are_rejected() {
+        let proof = VdfProof::evaluate(SEED, &ROOT, &LEAF, 64);
+        assert!(proof.verify(&ROOT, &LEAF));
+
+        // Another root, leaf, seed or iteration count.
+        assert!(!proof.verify(&LEAF, &LEAF));
+        assert!(!proof.verify(&ROOT, &ROOT));
+        assert!(!VdfProof {
+            seed: [9; 32],
+            ..proof.clone()
+        }
+        .verify(&ROOT, &LEAF));
+        assert!(!VdfProof {
+            iterations: 63,
+            ..proof.clone()
+        }
+        .verify(&ROOT, &LEAF));
+        // The negated output, which is not canonical.
+        let n = modulus();
+        let negated = &n - BigUint::from_bytes_be(&proof.output);
+        assert!(!VdfProof {
+            output: to_element(&negated),
+            ..proof.clone()
+        }
+        .verify(&ROOT, &LEAF));
+        // A corrupted proof, or an output that is not a full element.
+        let mut corrupted = proof.proof.clone();
+        corrupted[255] ^= 1;
+        assert!(!VdfProof {
+            proof: corrupted,
+            ..proof.clone()
+        }
+        .verify(&ROOT, &LEAF));
+        assert!(!VdfProof {
+            output: BigUint::from_bytes_be(&proof.output).to_bytes_be()[1..].to_vec(),
+            ..proof
+        }
+        .verify(&ROOT, &LEAF));
+    }
+
+    #[test]
+    fn miller_rabin_separates_primes_from_composites() {
+        let mersenne_127 = (BigUint::from(1u32) << 127usize) - BigUint::from(1u32);
+        for prime in [2u64, 3, 61, 65_537, 2_147_483_647] {
+            assert!(is_probable_prime(&BigUint::from(prime)), "{}", prime);
+        }
+        assert!(is_probable_prime(&mersenne_127));
+        // Including the Carmichael numbers 561 and 41041.
+        for composite in [9u64, 561, 41_041, 4_294_967_297] {
+            assert!(
+                !is_probable_prime(&BigUint::from(composite)),
+                "{}",
+                composite
+            );
+        }
+        assert!(!is_probable_prime(&(&mersenne_127 + BigUint::from(2u32))));
+    }
+
+    #[test]
+    fn challenge_primes_are_128_bit_primes() {
+        let x = BigUint::from_bytes_be(&challenge(&SEED, &ROOT, &LEAF));
+        let l = challenge_prime(&x, &x, 10);
+        assert_eq!(l.bits(), 128);
+        assert!(is_probable_prime(&l));
+        assert_ne!(l, challenge_prime(&x, &x, 11));
+    }
+}
@@ -1,38 +1,678 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
+use std::collections::BTreeMap;
+
+use goldinals_lib::{
+    bitcoin::{self, SpvProof},
+    blake2b, blake3,
+    bls::SignedValue,
+    count, eip712, erasure, format, hiding, hybrid,
+    inclusion::{self, Parameters},
+    keccak,
+    mmr::{self, MmrProof},
+    patricia,
+    public_values::{
+        AbsenceCommitment, AggregateCommitment, BatchLeaf, BlocklistCommitment, BloomCommitment,
+        BlsCommitment, CapNode, CheckpointCommitment, Diagnostic, ErasureSampleCommitment,
+        ForestStatus, ImtCommitment, IndexCommitment, KzgCommitment, LeafCount, MmrEntry,
+        NeighborCommitment, Ordinal, PairCommitment, PairStatus, PreimageCommitment, Promotions,
+        ProofStats, RangeCommitment, RevealCommitment, RevealPhase, SignerCommitment, SparseEntry,
+        TransformCommitment, TrieEntry, VdfCommitment, FLAG_LABELS, RESULT_AUTHORIZATION,
+        RESULT_BLOOM, RESULT_BLS, RESULT_CHECKPOINT, RESULT_FOREST, RESULT_IMT, RESULT_INCLUSION,
+        RESULT_INDEX, RESULT_KZG, RESULT_LEAF_MATCH, RESULT_ORDERED, RESULT_PAIR, RESULT_RANGE,
+        RESULT_REVEAL, RESULT_TRANSFORM, RESULT_VDF,
+    },
+    sha256::{self, Sha256Hasher},
+    sorted::{self, PairedLeaf},
+    sparse::{self, SparseProof},
+    tree, truncated, updates, vdf, Options, PublicValues, Scheme,
+};
 use rs_merkle::{Hasher, MerkleProof};
 
-#[derive(Clone)]
-struct Sha256Hasher;
+pub fn main() {
+    if format::check_header(&sp1_zkvm::io::read_vec(), format::SUPPORTED_VERSIONS).is_err() {
+        return commit_unreadable(Diagnostic::UnsupportedFormat);
+    }
+    let scheme: u8 = sp1_zkvm::io::read();
+    let Some(scheme) = Scheme::from_u8(scheme) else {
+        return commit_unreadable(Diagnostic::UnknownScheme);
+    };
+    let output = match scheme {
+        Scheme::Patricia => verify_trie(),
+        Scheme::Sparse => verify_sparse(),
+        Scheme::Bitcoin => verify_bitcoin(),
+        Scheme::Mmr => verify_mmr(),
+        _ => verify_tree(scheme),
+    };
+    if output.strict {
+        assert!(output.is_valid, "merkle proof invalid");
+    }
+
+    sp1_zkvm::io::commit_slice(&output.to_bytes());
+}
+
+/// Commits an invalid result with zero root and leaf for an input whose rest cannot be read.
+fn commit_unreadable(diagnostic: Diagnostic) {
+    let mut output = PublicValues::new([0; 32], [0; 32], false);
+    output.diagnostics = Some(vec![diagnostic]);
+    sp1_zkvm::io::commit_slice(&output.to_bytes());
+}
+
+/// Reads the options, which end every input, and switches SHA-256 to software if they ask for it.
+fn read_options() -> Options {
+    let options: Options = sp1_zkvm::io::read();
+    sha256::set_software(options.software_sha256);
+    options
+}
+
+/// Verifies a Merkle-Patricia trie proof, read as root, key, value and proof nodes.
+fn verify_trie() -> PublicValues {
+    let root: [u8; 32] = sp1_zkvm::io::read();
+    let key: Vec<u8> = sp1_zkvm::io::read();
+    let value: Vec<u8> = sp1_zkvm::io::read();
+    let proof: Vec<Vec<u8>> = sp1_zkvm::io::read();
+    let options = read_options();
+
+    let is_valid = patricia::verify(root, &key, &value, &proof);
+
+    let mut output = PublicValues::new(root, patricia::keccak256(&value), is_valid);
+    output.scheme = Scheme::Patricia;
+    output.trie = Some(TrieEntry { key, value });
+    apply_core_options(&mut output, &options);
+    output
+}
+
+/// Verifies a sparse Merkle tree proof that a key holds a value, or no value.
+fn verify_sparse() -> PublicValues {
+    let root: [u8; 32] = sp1_zkvm::io::read();
+    let key: [u8; 32] = sp1_zkvm::io::read();
+    let value: Option<[u8; 32]> = sp1_zkvm::io::read();
+    let proof: SparseProof = sp1_zkvm::io::read();
+    let options = read_options();
+
+    let is_valid = sparse::verify(root, &key, value.as_ref(), &proof);
+
+    let leaf = value.map_or([0; 32], |value| sparse::leaf(&key, &value));
+    let mut output = PublicValues::new(root, leaf, is_valid);
+    output.scheme = Scheme::Sparse;
+    output.sparse = Some(SparseEntry {
+        key,
+        value: value.unwrap_or_default(),
+        is_member: value.is_some(),
+    });
+    apply_core_options(&mut output, &options);
+    output
+}
+
+/// Verifies a Bitcoin SPV proof that a transaction is in the block with the given header, committing
+/// the header's Merkle root and the txid in internal byte order.
+fn verify_bitcoin() -> PublicValues {
+    let header: Vec<u8> = sp1_zkvm::io::read();
+    let txid: [u8; 32] = sp1_zkvm::io::read();
+    let proof: SpvProof = sp1_zkvm::io::read();
+    let options = read_options();
+
+    let is_valid = bitcoin::verify(&header, &txid, &proof);
+
+    let root = bitcoin::merkle_root(&header).unwrap_or_default();
+    let mut output = PublicValues::new(root, txid, is_valid);
+    output.leaf_index = proof.index.into();
+    output.total_leaves = proof.total_transactions.into();
+    output.scheme = Scheme::Bitcoin;
+    apply_core_options(&mut output, &options);
+    output
+}
+
+/// Verifies a Merkle Mountain Range inclusion proof, committing the MMR's size and the leaf's node
+/// position.
+fn verify_mmr() -> PublicValues {
+    let root: [u8; 32] = sp1_zkvm::io::read();
+    let leaf: [u8; 32] = sp1_zkvm::io::read();
+    let proof: MmrProof = sp1_zkvm::io::read();
+    let options = read_options();
+
+    let is_valid = mmr::verify(&root, &leaf, &proof);
 
-impl Hasher for Sha256Hasher {
-    type Hash = [u8; 32];
+    let mut output = PublicValues::new(root, leaf, is_valid);
+    output.leaf_index = proof.leaf_index;
+    output.total_leaves = mmr::leaf_count(proof.mmr_size).unwrap_or_default();
+    output.scheme = Scheme::Mmr;
+    output.mmr = Some(MmrEntry {
+        mmr_size: proof.mmr_size,
+        position: proof.position(),
+    });
+    apply_core_options(&mut output, &options);
+    output
+}
 
-    fn hash(data: &[u8]) -> Self::Hash {
-        use sha2::Digest;
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(data);
-        hasher.finalize().into()
+/// Applies the options that only depend on the core values, which are all that the trie, sparse,
+/// Bitcoin and MMR modes support.
+fn apply_core_options(output: &mut PublicValues, options: &Options) {
+    if options.commit_root_reversed {
+        let mut reversed = output.root;
+        reversed.reverse();
+        output.root_reversed = Some(reversed);
     }
+    output.elf_hash = options.elf_hash;
+    output.eip712_digest = options.eip712_domain.map(|domain| {
+        eip712::typed_data_hash(&domain, &output.root, &output.leaf, output.is_valid)
+    });
+    if options.tagged_results || options.pack_flags {
+        output.results = Some(BTreeMap::from([(RESULT_INCLUSION, output.is_valid as u8)]));
+        output.results_packed = options.pack_flags;
+    }
+    output.strict = options.strict;
+    output.field_packed = options.field_packed;
+    output.abi_encoded = options.abi_encoded;
 }
 
-pub fn main() {
+/// Verifies an inclusion proof in a binary, truncated, hybrid, k-ary, keccak, blake2b or blake3
+/// tree.
+fn verify_tree(scheme: Scheme) -> PublicValues {
+    let hash_bytes: Option<u8> = match scheme {
+        Scheme::TruncatedSha256 => Some(sp1_zkvm::io::read()),
+        _ => None,
+    };
+    let arity: Option<u8> = match scheme {
+        Scheme::Kary => Some(sp1_zkvm::io::read()),
+        _ => None,
+    };
+    let personalization: Option<Vec<u8>> = match scheme {
+        Scheme::Blake2b => Some(sp1_zkvm::io::read()),
+        _ => None,
+    };
     let root: [u8; 32] = sp1_zkvm::io::read();
     let leaf: [u8; 32] = sp1_zkvm::io::read();
     let proof_bytes: Vec<u8> = sp1_zkvm::io::read();
     let leaf_index: usize = sp1_zkvm::io::read();
     let total_leaves: usize = sp1_zkvm::io::read();
+    let options = read_options();
 
-    let proof =
-        MerkleProof::<Sha256Hasher>::from_bytes(&proof_bytes).expect("Failed to parse proof");
-
-    let is_valid = proof.verify(root, &[leaf_index], &[leaf], total_leaves);
+    // Failures that only affect one check are recorded here rather than aborting the proof.
+    let mut diagnostics = Vec::new();
 
-    let mut output = Vec::new();
-    output.extend_from_slice(&root);
-    output.extend_from_slice(&leaf);
-    output.push(is_valid as u8);
+    let parameters = Parameters {
+        hash_bytes,
+        arity,
+        personalization: personalization.as_deref().unwrap_or_default(),
+    };
+    // Hashing the derived and transformed leaves with invalid parameters would abort the proof, so
+    // only the core values are committed, with the leaf still hidden when asked to.
+    if !parameters.is_valid(scheme) {
+        let mut output = PublicValues::new(root, leaf, false);
+        output.leaf_index = leaf_index as u64;
+        output.total_leaves = total_leaves as u64;
+        output.scheme = scheme;
+        output.hash_bytes = hash_bytes;
+        output.arity = arity;
+        output.personalization_hash = personalization.as_deref().map(Sha256Hasher::hash);
+        if let Some(randomness) = options.leaf_randomness {
+            output.leaf = hiding::commit(&leaf, &randomness);
+            output.leaf_hidden = true;
+        }
+        output.diagnostics = Some(vec![Diagnostic::InvalidParameters]);
+        apply_core_options(&mut output, &options);
+        return output;
+    }
+    let verify = |leaf: [u8; 32], proof_bytes: &[u8], index: usize| {
+        inclusion::verify(
+            scheme,
+            parameters,
+            root,
+            leaf,
+            proof_bytes,
+            index,
+            total_leaves,
+        )
+    };
+    let is_valid = verify(leaf, &proof_bytes, leaf_index).unwrap_or_else(|diagnostic| {
+        diagnostics.push(diagnostic);
+        false
+    });
+    // A batch widens `is_valid` to every leaf of the batch, which must include the proven leaf.
+    let is_valid = match &options.batch {
+        None => is_valid,
+        Some(_) if scheme != Scheme::Sha256 => {
+            diagnostics.push(Diagnostic::BatchUnsupported);
+            false
+        }
+        Some(batch) => {
+            let indices: Vec<usize> = batch.indices.iter().map(|&i| i as usize).collect();
+            let batch_valid = batch.is_canonical(total_leaves)
+                && batch.contains(leaf_index, &leaf)
+                && match MerkleProof::<Sha256Hasher>::from_bytes(&batch.proof_bytes) {
+                    Ok(proof) => proof.verify(root, &indices, &batch.leaves, total_leaves),
+                    Err(_) => {
+                        diagnostics.push(Diagnostic::MalformedProof);
+                        false
+                    }
+                };
+            is_valid && batch_valid
+        }
+    };
+    let verify = |leaf: [u8; 32], proof_bytes: &[u8], index: usize| {
+        verify(leaf, proof_bytes, index).unwrap_or(false)
+    };
+    // The parent function of the binary schemes whose proofs are plain sibling paths.
+    let parent = match scheme {
+        Scheme::Sha256 => Some(tree::sha256_parent as fn(&[u8; 32], &[u8; 32]) -> [u8; 32]),
+        Scheme::Hybrid => Some(hybrid::parent as fn(&[u8; 32], &[u8; 32]) -> [u8; 32]),
+        Scheme::Keccak256 => Some(keccak::parent as fn(&[u8; 32], &[u8; 32]) -> [u8; 32]),
+        _ => None,
+    };
+    let path: Vec<[u8; 32]> = proof_bytes
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
 
-    sp1_zkvm::io::commit_slice(&output);
+    let mut output = PublicValues::new(root, leaf, is_valid);
+    output.leaf_index = leaf_index as u64;
+    output.total_leaves = total_leaves as u64;
+    output.scheme = scheme;
+    output.hash_bytes = hash_bytes;
+    output.arity = arity;
+    output.personalization_hash = personalization.as_deref().map(Sha256Hasher::hash);
+    output.leaf_matches = options.expect_leaf.map(|expected| leaf == expected);
+    output.authorization = options.authorization.as_ref().map(|authorization| {
+        let (address, signature_valid) = authorization.verify(&leaf);
+        SignerCommitment {
+            address: address.unwrap_or_default(),
+            signature_valid,
+            authorized: is_valid && signature_valid,
+        }
+    });
+    output.bls = options.bls_signature.as_ref().map(|bls| BlsCommitment {
+        public_key: bls.public_key.as_slice().try_into().unwrap_or([0; 48]),
+        signed: bls.signed,
+        valid: bls.verify(match bls.signed {
+            SignedValue::Leaf => &leaf,
+            SignedValue::Root => &root,
+        }),
+    });
+    output.vdf = options.vdf.as_ref().map(|vdf| VdfCommitment {
+        challenge: vdf::challenge(&vdf.seed, &root, &leaf),
+        iterations: vdf.iterations,
+        output: vdf.output.as_slice().try_into().unwrap_or([0; 256]),
+        valid: vdf.verify(&root, &leaf),
+    });
+    if let Some(randomness) = options.leaf_randomness {
+        output.leaf = hiding::commit(&leaf, &randomness);
+        output.leaf_hidden = true;
+    }
+    if let Some(new_leaves) = &options.updates {
+        match parent {
+            Some(parent) => {
+                output.updates = Some(updates::apply(
+                    root,
+                    leaf,
+                    leaf_index,
+                    total_leaves,
+                    &path,
+                    new_leaves,
+                    parent,
+                ))
+            }
+            None => diagnostics.push(Diagnostic::UpdatesUnsupported),
+        }
+    }
+    if let Some(append) = &options.append {
+        match parent {
+            Some(parent) => output.append = Some(append.apply(root, total_leaves, parent)),
+            None => diagnostics.push(Diagnostic::AppendUnsupported),
+        }
+    }
+    if let Some(neighbors) = &options.neighbors {
+        let prev_valid = neighbors.prev.as_ref().map_or(true, |prev| {
+            leaf_index > 0 && verify(prev.leaf, &prev.proof_bytes, leaf_index - 1)
+        });
+        let next_valid = neighbors.next.as_ref().map_or(true, |next| {
+            leaf_index < usize::MAX && verify(next.leaf, &next.proof_bytes, leaf_index + 1)
+        });
+        let prev = neighbors.prev.as_ref().map(|prev| prev.leaf);
+        let next = neighbors.next.as_ref().map(|next| next.leaf);
+        let ordered = sorted::is_ordered(
+            prev.as_ref(),
+            &leaf,
+            next.as_ref(),
+            leaf_index,
+            total_leaves,
+        );
+        output.neighbors = Some(NeighborCommitment {
+            prev,
+            next,
+            ordered: is_valid && prev_valid && next_valid && ordered,
+        });
+    }
+    output.checkpoint = options
+        .checkpoint
+        .as_ref()
+        .map(|checkpoint| CheckpointCommitment {
+            checkpoint_root: checkpoint.checkpoint_root,
+            index: checkpoint.index,
+            count: checkpoint.count,
+            valid: checkpoint.verify(&root),
+        });
+    output.transform = options.leaf_transform.as_ref().map(|transformed| {
+        let derived = transformed.transform.apply(&transformed.data);
+        let derived = match hash_bytes {
+            Some(n) => truncated::truncate(&derived, n),
+            None => derived,
+        };
+        TransformCommitment {
+            spec_hash: transformed.transform.spec_hash(),
+            derived: derived == leaf,
+        }
+    });
+    output.preimage = options.preimage.as_ref().map(|data| {
+        let derived = match scheme {
+            Scheme::Keccak256 => keccak::hash(data),
+            Scheme::Blake2b => blake2b::hash(data, personalization.as_deref().unwrap()),
+            Scheme::Blake3 => blake3::hash(data),
+            _ => sha256::hash(data),
+        };
+        let derived = match hash_bytes {
+            Some(n) => truncated::truncate(&derived, n),
+            None => derived,
+        };
+        PreimageCommitment {
+            data_hash: sha256::hash(data),
+            length: data.len() as u64,
+            hashes_to_leaf: derived == leaf,
+        }
+    });
+    output.reveal = match (&options.reveal, options.commit_phase) {
+        (Some(reveal), _) => {
+            let commitment = reveal.commitment();
+            let commitment = match hash_bytes {
+                Some(n) => truncated::truncate(&commitment, n),
+                None => commitment,
+            };
+            Some(RevealCommitment {
+                phase: RevealPhase::Reveal,
+                matches: commitment == leaf,
+                value: reveal.value.clone(),
+            })
+        }
+        (None, true) => Some(RevealCommitment {
+            phase: RevealPhase::Commit,
+            matches: false,
+            value: Vec::new(),
+        }),
+        (None, false) => None,
+    };
+    output.bloom = options.bloom_filter.as_ref().map(|filter| BloomCommitment {
+        filter_hash: filter.hash(),
+        bits: filter.len,
+        hashes: filter.hashes,
+        passed: filter.contains(&leaf),
+    });
+    if let Some(pair) = &options.pair {
+        let index_a = leaf_index as u64;
+        let status = if !is_valid || !verify(pair.leaf, &pair.proof_bytes, pair.index as usize) {
+            PairStatus::Invalid
+        } else if pair.index == index_a {
+            PairStatus::Equal
+        } else if index_a < pair.index {
+            PairStatus::Ordered
+        } else {
+            PairStatus::Reversed
+        };
+        output.pair = Some(PairCommitment {
+            status,
+            index_a,
+            index_b: pair.index,
+            leaf_b: pair.leaf,
+        });
+    }
+    output.batch = options
+        .batch
+        .as_ref()
+        .filter(|_| scheme == Scheme::Sha256)
+        .map(|batch| {
+            batch
+                .indices
+                .iter()
+                .zip(&batch.leaves)
+                .map(|(&index, &leaf)| BatchLeaf { index, leaf })
+                .collect()
+        });
+    output.imt = options.imt.map(|query| {
+        let low_leaf = query.low_leaf.hash();
+        let low_leaf = match hash_bytes {
+            Some(n) => truncated::truncate(&low_leaf, n),
+            None => low_leaf,
+        };
+        ImtCommitment {
+            value: query.value,
+            non_member: is_valid && low_leaf == leaf && query.low_leaf.excludes(&query.value),
+        }
+    });
+    output.range = options.range_count.as_ref().map(|range| {
+        let mut verified = !range.leaves.is_empty() && range.leaves.len() == range.proofs.len();
+        let mut count = 0;
+        for (position, (leaf, proof_bytes)) in range.leaves.iter().zip(&range.proofs).enumerate() {
+            if !verify(*leaf, proof_bytes, range.first as usize + position) {
+                verified = false;
+            } else if range.matches(leaf) {
+                count += 1;
+            }
+        }
+        RangeCommitment {
+            first: range.first,
+            last: range.last(),
+            count,
+            verified,
+            prefix: range.prefix.clone(),
+        }
+    });
+    if let Some(opening) = &options.kzg_opening {
+        output.kzg = Some(KzgCommitment {
+            commitment: opening.commitment,
+            tau_g2: opening.tau_g2.as_slice().try_into().unwrap_or([0; 64]),
+            point: leaf_index as u64,
+            valid: opening.verify(&leaf, leaf_index as u64),
+        });
+    }
+    if options.commit_proof_stats && scheme != Scheme::Sha256 {
+        diagnostics.push(Diagnostic::ProofStatsUnsupported);
+    } else if options.commit_proof_stats {
+        // A batch's multiproof is what proves every leaf that `is_valid` covers.
+        output.proof_stats = Some(match &options.batch {
+            Some(batch) => ProofStats::new(batch.leaves.len(), &batch.proof_bytes),
+            None => ProofStats::new(1, &proof_bytes),
+        });
+    }
+    output.forest = options
+        .forest
+        .as_ref()
+        .map(|forest| forest.commit(leaf_index, total_leaves));
+    if options.commit_promotions && scheme == Scheme::Kary {
+        diagnostics.push(Diagnostic::PromotionsUnsupported);
+    } else if options.commit_promotions {
+        let levels = tree::promoted_levels(leaf_index, total_leaves);
+        output.promotions = Some(Promotions {
+            depth: levels.len() as u8,
+            promoted: levels
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (level, &promoted)| {
+                    bits | (promoted as u64) << level
+                }),
+        });
+    }
+    if let Some(cap_level) = options.cap_level {
+        let height = tree::height(total_leaves).checked_sub(cap_level as usize);
+        match (parent, height) {
+            (None, _) => diagnostics.push(Diagnostic::CapUnsupported),
+            (_, None) => diagnostics.push(Diagnostic::CapLevelTooHigh),
+            (Some(parent), Some(height)) => {
+                match tree::node_at_height(leaf, leaf_index, total_leaves, &path, height, parent) {
+                    Some(node) => {
+                        output.cap = Some(CapNode {
+                            cap_level,
+                            position: (leaf_index >> height) as u64,
+                            node,
+                        })
+                    }
+                    None => diagnostics.push(Diagnostic::CapPathTooShort),
+                }
+            }
+        }
+    }
+    if options.commit_ordinal {
+        output.ordinal = Some(Ordinal::new(leaf_index, total_leaves));
+    }
+    output.blocklist = options
+        .blocklist
+        .as_ref()
+        .map(|blocklist| BlocklistCommitment {
+            root: blocklist.root,
+            total_leaves: blocklist.total_leaves,
+            excluded: blocklist.excludes(&leaf),
+        });
+    output.aggregate = options.aggregate.as_ref().map(|aggregate| {
+        let mut failed = Vec::new();
+        for (position, entry) in aggregate.entries.iter().enumerate() {
+            if !verify(entry.leaf, &entry.proof_bytes, entry.index as usize) {
+                assert!(
+                    !aggregate.require_all,
+                    "aggregated proof {} of leaf {} invalid",
+                    position, entry.index
+                );
+                failed.push(entry.index);
+            }
+        }
+        AggregateCommitment {
+            total: aggregate.entries.len() as u64,
+            verified: (aggregate.entries.len() - failed.len()) as u64,
+            entries_hash: aggregate.entries_hash(),
+            failed,
+        }
+    });
+    output.absence = options.absence.as_ref().map(|absence| {
+        let bracket_valid = |bracket: &Option<PairedLeaf>| {
+            bracket.as_ref().map_or(true, |bracket| {
+                verify(bracket.leaf, &bracket.proof_bytes, bracket.index as usize)
+            })
+        };
+        AbsenceCommitment {
+            target: absence.target,
+            absent: absence.brackets(total_leaves)
+                && bracket_valid(&absence.low)
+                && bracket_valid(&absence.high),
+        }
+    });
+    output.erasure = options.erasure.as_ref().map(|sample| {
+        let chunk_leaf = erasure::leaf(&sample.chunk);
+        let chunk_leaf = match hash_bytes {
+            Some(n) => truncated::truncate(&chunk_leaf, n),
+            None => chunk_leaf,
+        };
+        ErasureSampleCommitment {
+            data_chunks: sample.coding.data_chunks,
+            total_chunks: sample.coding.total_chunks,
+            chunk_size: sample.chunk.len() as u32,
+            index: leaf_index as u64,
+            sampled: is_valid
+                && sample.coding.is_valid()
+                && sample.coding.total_chunks as usize == total_leaves
+                && chunk_leaf == leaf,
+        }
+    });
+    output.leaf_count = options.leaf_count.as_ref().map(|proof| {
+        let count_leaf = count::leaf(total_leaves as u64);
+        let count_leaf = match hash_bytes {
+            Some(n) => truncated::truncate(&count_leaf, n),
+            None => count_leaf,
+        };
+        LeafCount {
+            total_leaves: total_leaves as u64,
+            verified: leaf_index as u64 != count::INDEX
+                && verify(count_leaf, &proof.proof_bytes, count::INDEX as usize),
+        }
+    });
+    if options.commit_root_reversed {
+        let mut reversed = root;
+        reversed.reverse();
+        output.root_reversed = Some(reversed);
+    }
+    if let Some(lookup) = &options.index_lookup {
+        output.index = Some(IndexCommitment {
+            root: lookup.root,
+            valid: lookup.verify() && lookup.position == leaf_index as u64,
+            position: lookup.position,
+            key: lookup.key.clone(),
+        });
+    }
+    output.diagnostics = (!diagnostics.is_empty()).then_some(diagnostics);
+    output.elf_hash = options.elf_hash;
+    output.canonicalization = options.canonicalization;
+    output.eip712_digest = options
+        .eip712_domain
+        .map(|domain| eip712::typed_data_hash(&domain, &output.root, &output.leaf, is_valid));
+    if options.tagged_results || options.pack_flags {
+        let mut results = BTreeMap::from([(RESULT_INCLUSION, is_valid as u8)]);
+        if let Some(leaf_matches) = output.leaf_matches {
+            results.insert(RESULT_LEAF_MATCH, leaf_matches as u8);
+        }
+        if let Some(index) = &output.index {
+            results.insert(RESULT_INDEX, index.valid as u8);
+        }
+        if let Some(signer) = &output.authorization {
+            results.insert(RESULT_AUTHORIZATION, signer.signature_valid as u8);
+        }
+        if let Some(neighbors) = &output.neighbors {
+            results.insert(RESULT_ORDERED, neighbors.ordered as u8);
+        }
+        if let Some(checkpoint) = &output.checkpoint {
+            results.insert(RESULT_CHECKPOINT, checkpoint.valid as u8);
+        }
+        if let Some(reveal) = &output.reveal {
+            if reveal.phase == RevealPhase::Reveal {
+                results.insert(RESULT_REVEAL, reveal.matches as u8);
+            }
+        }
+        if let Some(transform) = &output.transform {
+            results.insert(RESULT_TRANSFORM, transform.derived as u8);
+        }
+        if let Some(bloom) = &output.bloom {
+            results.insert(RESULT_BLOOM, bloom.passed as u8);
+        }
+        if let Some(imt) = &output.imt {
+            results.insert(RESULT_IMT, imt.non_member as u8);
+        }
+        if let Some(range) = &output.range {
+            results.insert(RESULT_RANGE, range.verified as u8);
+        }
+        if let Some(pair) = &output.pair {
+            results.insert(RESULT_PAIR, (pair.status == PairStatus::Ordered) as u8);
+        }
+        if let Some(bls) = &output.bls {
+            results.insert(RESULT_BLS, bls.valid as u8);
+        }
+        if let Some(vdf) = &output.vdf {
+            results.insert(RESULT_VDF, vdf.valid as u8);
+        }
+        if let Some(forest) = &output.forest {
+            results.insert(RESULT_FOREST, (forest.status == ForestStatus::Mapped) as u8);
+        }
+        if let Some(kzg) = &output.kzg {
+            results.insert(RESULT_KZG, kzg.valid as u8);
+        }
+        // Results without a flag bit do not fit the bitfield, so they keep the labelled list.
+        let packable = results.keys().all(|label| FLAG_LABELS.contains(label));
+        if options.pack_flags && !packable {
+            output
+                .diagnostics
+                .get_or_insert_with(Vec::new)
+                .push(Diagnostic::FlagsUnsupported);
+        }
+        output.results = Some(results);
+        output.results_packed = options.pack_flags && packable;
+    }
+    output.strict = options.strict;
+    output.field_packed = options.field_packed;
+    output.abi_encoded = options.abi_encoded;
+    output
 }
@@ -1,5 +1,10 @@
 use sp1_helper::build_program_with_args;
 
 fn main() {
-    build_program_with_args("../program", Default::default())
+    build_program_with_args("../program", Default::default());
+    // The aggregation program needs sp1-zkvm's `verify` feature, so it is only built for the
+    // `aggregate` binary.
+    if std::env::var_os("CARGO_FEATURE_AGGREGATION").is_some() {
+        build_program_with_args("../aggregation", Default::default());
+    }
 }
@@ -0,0 +1,204 @@
+//! Proves several Merkle claims with compressed proofs, verifies them all in the aggregation
+//! program with SP1 recursion, and wraps the aggregated proof for on-chain verification, so that a
+//! single Groth16 or PLONK proof covers every claim.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --features aggregation --bin aggregate -- --claims 4
+//! ```
+//! or, to prove a large batch in chunks of bounded size:
+//! ```shell
+//! RUST_LOG=info cargo run --release --features aggregation --bin aggregate -- \
+//!     --claims 1 --batch-size 100000 --chunk-size 1000
+//! ```
+
+use clap::{Parser, ValueEnum};
+use goldinals_lib::recursion::AggregatedValues;
+use goldinals_script::{
+    decode_public_values, print_extensions, prover_client, setup_inputs, split_aggregate,
+    use_mock_prover, ShardArgs, ThreadArgs, TreeArgs, MERKLE_ELF,
+};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{include_elf, HashableKey, SP1Proof, SP1Stdin};
+use std::path::PathBuf;
+
+/// The ELF file for the aggregation program.
+const AGGREGATION_ELF: &[u8] = include_elf!("goldinals-aggregation");
+
+/// The arguments for the aggregate command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct AggregateArgs {
+    #[clap(flatten)]
+    tree: TreeArgs,
+
+    #[clap(flatten)]
+    threads: ThreadArgs,
+
+    #[clap(flatten)]
+    shards: ShardArgs,
+
+    /// The number of claims to prove and aggregate. Each claim builds its inputs from the tree
+    /// arguments, so without a fixed leaf every claim proves a different random leaf.
+    #[clap(long, default_value = "2", value_parser = clap::value_parser!(u32).range(1..))]
+    claims: u32,
+
+    /// Prove the `--batch-size` proofs of each claim in chunks of at most this many, each in its
+    /// own execution, and aggregate every chunk as a child proof, so that no single execution
+    /// grows with the batch.
+    #[clap(long, requires = "batch_size", value_parser = clap::value_parser!(u64).range(1..))]
+    chunk_size: Option<u64>,
+
+    #[clap(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+}
+
+/// Enum representing the available proof systems
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+enum ProofSystem {
+    Plonk,
+    Groth16,
+}
+
+/// A fixture of an aggregated proof, for testing its verification inside Solidity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SP1AggregationProofFixture {
+    /// The verifying key of the aggregation program, which the proof verifies against.
+    vkey: String,
+    /// The verifying key of the Merkle program, which every aggregated claim was proven with.
+    merkle_vkey: String,
+    public_values: String,
+    proof: String,
+}
+
+fn main() {
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+
+    // Parse the command line arguments.
+    let args = AggregateArgs::parse();
+
+    // Setup the prover client.
+    args.threads.init_prove_pool();
+    let client = prover_client();
+
+    // Setup the programs.
+    let (merkle_pk, merkle_vk) = client.setup(MERKLE_ELF);
+    let (aggregation_pk, aggregation_vk) = client.setup(AGGREGATION_ELF);
+
+    // Prove every claim, or every chunk of one, with a compressed proof, the form that recursion
+    // verifies.
+    let mut stdin = SP1Stdin::new();
+    let mut public_values = Vec::new();
+    let mut proofs = Vec::new();
+    for claim in 0..args.claims {
+        let claim_stdin = args
+            .threads
+            .build(|| setup_inputs(&args.tree))
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+        let chunks = match args.chunk_size {
+            Some(size) => split_aggregate(&claim_stdin, size as usize).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }),
+            None => vec![claim_stdin],
+        };
+        let total_chunks = chunks.len();
+        for (chunk, chunk_stdin) in chunks.into_iter().enumerate() {
+            let proof = args
+                .shards
+                .apply(client.prove(&merkle_pk, chunk_stdin))
+                .compressed()
+                .run()
+                .expect("failed to generate compressed proof");
+            let values = decode_public_values(proof.public_values.as_slice())
+                .expect("failed to decode public values");
+            match args.chunk_size {
+                Some(_) => println!("Claim {}, Chunk {} of {}:", claim, chunk + 1, total_chunks),
+                None => println!("Claim {}:", claim),
+            }
+            println!("  Merkle Root: 0x{}", hex::encode(values.root));
+            println!("  Leaf: 0x{}", hex::encode(values.leaf));
+            println!("  Is Valid: {}", values.is_valid);
+            if let Some(aggregate) = &values.aggregate {
+                println!("  Verified: {} of {}", aggregate.verified, aggregate.total);
+            }
+
+            public_values.push(proof.public_values.as_slice().to_vec());
+            let SP1Proof::Compressed(proof) = proof.proof else {
+                unreachable!("compressed() produces compressed proofs");
+            };
+            proofs.push(*proof);
+        }
+    }
+
+    // The program reads the child verifying key digest and public values, then verifies one
+    // written proof per claim.
+    let vkey_digest = merkle_vk.hash_u32();
+    stdin.write(&vkey_digest);
+    stdin.write(&public_values);
+    for proof in proofs {
+        stdin.write_proof(proof, merkle_vk.vk.clone());
+    }
+
+    println!("Proof System: {:?}", args.system);
+    let prove = args.shards.apply(client.prove(&aggregation_pk, stdin));
+    let proof = match args.system {
+        ProofSystem::Plonk => prove.plonk().run(),
+        ProofSystem::Groth16 => prove.groth16().run(),
+    }
+    .expect("failed to generate aggregated proof");
+    client
+        .verify(&proof, &aggregation_vk)
+        .expect("failed to verify aggregated proof");
+
+    let output = proof.public_values.as_slice();
+    let aggregated =
+        AggregatedValues::from_bytes(output).expect("failed to decode aggregated public values");
+    assert_eq!(aggregated.vkey_digest, vkey_digest);
+    let (mut verified, mut total) = (0, 0);
+    for (claim, values) in aggregated.public_values.iter().enumerate() {
+        let values = decode_public_values(values).expect("failed to decode public values");
+        println!("Aggregated Claim {}: is_valid = {}", claim, values.is_valid);
+        print_extensions(&values);
+        if let Some(aggregate) = &values.aggregate {
+            verified += aggregate.verified;
+            total += aggregate.total;
+        }
+    }
+    if args.chunk_size.is_some() {
+        println!("Aggregated Proofs Verified: {} of {}", verified, total);
+    }
+
+    let fixture = SP1AggregationProofFixture {
+        vkey: aggregation_vk.bytes32().to_string(),
+        merkle_vkey: merkle_vk.bytes32().to_string(),
+        public_values: format!("0x{}", hex::encode(output)),
+        proof: format!("0x{}", hex::encode(proof.bytes())),
+    };
+
+    // Save the fixture, marking mock fixtures as such in the file name.
+    let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../contracts/src/fixtures");
+    let prefix = if use_mock_prover() {
+        "insecure-mock-"
+    } else {
+        ""
+    };
+    std::fs::create_dir_all(&fixture_path).expect("failed to create fixture path");
+    std::fs::write(
+        fixture_path
+            .join(format!("{}{:?}-aggregation-fixture.json", prefix, args.system).to_lowercase()),
+        serde_json::to_string_pretty(&fixture).unwrap(),
+    )
+    .expect("failed to write fixture");
+
+    println!("Aggregated Claims: {}", aggregated.public_values.len());
+    println!("Verification Key: {}", fixture.vkey);
+    println!("Merkle Verification Key: {}", fixture.merkle_vkey);
+    println!("Public Values: {}", fixture.public_values);
+    println!("Proof Bytes: {}", fixture.proof);
+}
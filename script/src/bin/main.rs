@@ -9,42 +9,241 @@
 //! ```shell
 //! RUST_LOG=info cargo run --release -- --prove
 //! ```
+//! or, to verify a proof saved by `--prove`:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- --verify merkle.proof
+//! ```
+//! or, to prove on the prover network and fetch the proof later:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- --prove --prover network --no-wait
+//! RUST_LOG=info cargo run --release -- --fetch <request id>
+//! ```
+//! or, to record what proving trees of 2^10 to 2^16 leaves costs:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- --prove --bench 10,12,14,16 --report-path bench.json
+//! ```
+//! or, to check the environment first:
+//! ```shell
+//! cargo run --release -- doctor
+//! ```
+//! or, to build the tree once and prove, verify and wrap its leaves in separate invocations:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- build-tree --tree-path merkle.tree
+//! RUST_LOG=info cargo run --release -- prove --tree-path merkle.tree --leaf-index 7 --out proof.bin
+//! RUST_LOG=info cargo run --release -- verify --proof proof.bin
+//! RUST_LOG=info cargo run --release -- fixture --tree-path merkle.tree --leaf-index 7
+//! ```
 
-use clap::Parser;
-use rand::Rng;
-use rs_merkle::{Hasher, MerkleTree};
-use sha2::{Digest, Sha256};
-use sp1_sdk::{include_elf, ProverClient, SP1Stdin};
-
-/// The ELF file for the Merkle Tree program
-pub const MERKLE_ELF: &[u8] = include_elf!("goldinals-merkle-tree");
-
-#[derive(Clone)]
-struct Sha256Hasher;
-
-impl Hasher for Sha256Hasher {
-    type Hash = [u8; 32];
-
-    fn hash(data: &[u8]) -> Self::Hash {
-        use sha2::Digest;
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(data);
-        hasher.finalize().into()
-    }
-}
+use clap::{Parser, Subcommand};
+use goldinals_script::{
+    check_public_values_size, decode_public_values, doctor, elf_hash,
+    fixture::{self, FixtureArgs},
+    layout_schema,
+    network::{self, ProofMode, ProverArgs},
+    print_extensions, print_key_info, redacted_args,
+    report::{self, BenchReport, ReportFormat, RunReport},
+    setup_inputs, with_software_sha256,
+    witness::Witness,
+    write_public_values, ShardArgs, ThreadArgs, TreeArgs, MERKLE_ELF,
+};
+use serde::Serialize;
+use sp1_sdk::{HashableKey, ProverClient, SP1ProofWithPublicValues, SP1VerifyingKey};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// The arguments for the command.
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     #[clap(long)]
     execute: bool,
 
     #[clap(long)]
     prove: bool,
 
-    #[clap(long, default_value = "40000000")]
-    total_leaves: usize,
+    /// In prove mode, save the proof with `SP1ProofWithPublicValues::save` to this path, and its
+    /// provenance next to it with the extension `.meta.json`.
+    #[clap(long, default_value = "merkle.proof")]
+    proof_file: PathBuf,
+
+    /// Load the proof saved at this path, print its public values and verify it, without proving.
+    #[clap(long)]
+    verify: Option<PathBuf>,
+
+    /// Verify every proof saved with `SP1ProofWithPublicValues::save` in this directory.
+    #[clap(long)]
+    verify_dir: Option<PathBuf>,
+
+    /// Execute or prove every witness in this file, one JSON witness per line, instead of building
+    /// a tree.
+    #[clap(long, requires = "results_file")]
+    witnesses_file: Option<PathBuf>,
+
+    /// Write one JSON result per `--witnesses-file` line to this file.
+    #[clap(long, requires = "witnesses_file")]
+    results_file: Option<PathBuf>,
+
+    /// Stop `--witnesses-file` at the first witness that fails or proves an invalid membership,
+    /// instead of processing every witness.
+    #[clap(long, requires = "witnesses_file")]
+    fail_fast: bool,
+
+    /// Serve `POST /prove` and `GET /proof/{id}` on this address instead of running once.
+    #[cfg(feature = "serve")]
+    #[clap(long)]
+    serve: Option<std::net::SocketAddr>,
+
+    #[cfg(feature = "serve")]
+    #[clap(flatten)]
+    service: goldinals_script::serve::ServeArgs,
+
+    /// Try every supported scheme on this JSON witness, ignoring its `scheme`, and print those it
+    /// verifies under, then exit.
+    #[clap(long)]
+    auto_detect_scheme: Option<PathBuf>,
+
+    #[clap(flatten)]
+    tree: TreeArgs,
+
+    #[clap(flatten)]
+    threads: ThreadArgs,
+
+    #[clap(flatten)]
+    shards: ShardArgs,
+
+    #[clap(flatten)]
+    prover: ProverArgs,
+
+    /// Print metadata about the proving and verifying keys after setup.
+    #[clap(long)]
+    dump_pk_vk_info: bool,
+
+    /// Write the raw committed public values to this file.
+    #[clap(long)]
+    output_public_values: Option<PathBuf>,
+
+    /// Exit with an error if the committed public values are larger than this many bytes.
+    #[clap(long)]
+    max_public_values_bytes: Option<usize>,
+
+    /// In execute mode, also execute with software SHA-256 and report the cycles the SHA-256
+    /// precompiles save.
+    #[clap(long)]
+    compare_software_sha256: bool,
+
+    /// In prove mode, exit non-zero instead of proving when the leaf is not a member of the tree.
+    #[clap(long)]
+    abort_on_invalid: bool,
+
+    /// Write a report of the run's cycles, syscall counts, phase timings, peak memory and, in prove
+    /// mode, proof size to this file.
+    #[clap(
+        long,
+        conflicts_with_all = ["verify", "verify_dir", "witnesses_file", "no_wait", "status"]
+    )]
+    report_path: Option<PathBuf>,
+
+    /// The encoding of `--report-path`.
+    #[clap(long, value_enum, default_value = "json", requires = "report_path")]
+    report_format: ReportFormat,
+
+    /// Execute or prove a synthetic tree of 2^k leaves for each of these comma-separated k, print a
+    /// table of their costs and write every run's report to `--report-path`.
+    #[clap(
+        long,
+        value_name = "LOG2_LEAVES",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u32).range(0..=32),
+        requires = "report_path",
+        conflicts_with_all = [
+            "fetch",
+            "input_file",
+            "tree_path",
+            "dataset",
+            "global_index",
+            "source",
+        ]
+    )]
+    bench: Vec<u32>,
+}
+
+/// The steps of a pipeline that builds a tree once and proves, verifies and wraps its leaves in
+/// separate invocations, and the commands that only inspect the build. Without a subcommand, the
+/// flags select the mode.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check the toolchain, prover configuration and program ELF.
+    Doctor,
+
+    /// Print the public values layout as JSON.
+    Schema,
+
+    /// Build the tree store at `--tree-path`, or check an existing one, without proving.
+    BuildTree {
+        #[clap(flatten)]
+        tree: TreeArgs,
+
+        #[clap(flatten)]
+        threads: ThreadArgs,
+    },
+
+    /// Prove the leaf and save the proof to `--out`, as `--prove --proof-file` does.
+    Prove {
+        /// Save the proof with `SP1ProofWithPublicValues::save` to this path, and its provenance
+        /// next to it with the extension `.meta.json`.
+        #[clap(long, default_value = "merkle.proof")]
+        out: PathBuf,
+
+        #[clap(flatten)]
+        tree: TreeArgs,
+
+        #[clap(flatten)]
+        threads: ThreadArgs,
+
+        #[clap(flatten)]
+        shards: ShardArgs,
+
+        #[clap(flatten)]
+        prover: ProverArgs,
+
+        /// Exit non-zero instead of proving when the leaf is not a member of the tree.
+        #[clap(long)]
+        abort_on_invalid: bool,
+
+        /// Write the raw committed public values to this file.
+        #[clap(long)]
+        output_public_values: Option<PathBuf>,
+
+        /// Exit with an error if the committed public values are larger than this many bytes.
+        #[clap(long)]
+        max_public_values_bytes: Option<usize>,
+
+        /// Write a report of the run's cycles, syscall counts, phase timings, peak memory and proof
+        /// size to this file.
+        #[clap(long, conflicts_with = "no_wait")]
+        report_path: Option<PathBuf>,
+
+        /// The encoding of `--report-path`.
+        #[clap(long, value_enum, default_value = "json", requires = "report_path")]
+        report_format: ReportFormat,
+    },
+
+    /// Load a proof saved by `prove`, print its public values and verify it, as `--verify` does.
+    Verify {
+        /// The saved proof.
+        #[clap(long)]
+        proof: PathBuf,
+
+        #[clap(flatten)]
+        prover: ProverArgs,
+    },
+
+    /// Prove the leaf with Groth16 or PLONK and write its Solidity fixture, as the `evm` binary
+    /// does.
+    Fixture(FixtureArgs),
 }
 
 fn main() {
@@ -52,70 +251,717 @@ fn main() {
     sp1_sdk::utils::setup_logger();
 
     // Parse the command line arguments.
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    // The subcommands run the same modes as their flags.
+    match args.command.take() {
+        Some(Command::Doctor) => {
+            let ok = doctor::run();
+            std::process::exit(if ok { 0 } else { 1 });
+        }
+        Some(Command::Schema) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&layout_schema()).unwrap()
+            );
+            return;
+        }
+        Some(Command::BuildTree { tree, threads }) => {
+            build_tree(&tree, &threads);
+            return;
+        }
+        Some(Command::Prove {
+            out,
+            tree,
+            threads,
+            shards,
+            prover,
+            abort_on_invalid,
+            output_public_values,
+            max_public_values_bytes,
+            report_path,
+            report_format,
+        }) => {
+            args.prove = true;
+            args.proof_file = out;
+            args.tree = tree;
+            args.threads = threads;
+            args.shards = shards;
+            args.prover = prover;
+            args.abort_on_invalid = abort_on_invalid;
+            args.output_public_values = output_public_values;
+            args.max_public_values_bytes = max_public_values_bytes;
+            args.report_path = report_path;
+            args.report_format = report_format;
+        }
+        Some(Command::Verify { proof, prover }) => {
+            args.verify = Some(proof);
+            args.prover = prover;
+        }
+        Some(Command::Fixture(fixture)) => {
+            fixture::run(&fixture);
+            return;
+        }
+        None => {}
+    }
+
+    #[cfg(feature = "serve")]
+    if let Some(addr) = args.serve {
+        args.threads.init_prove_pool();
+        let client = args.prover.client().unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        goldinals_script::serve::run(addr, client, &args.service);
+        return;
+    }
 
-    if args.execute == args.prove {
-        eprintln!("Error: You must specify either --execute or --prove");
+    if let Some(path) = &args.auto_detect_scheme {
+        let ok = detect_scheme(path);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    if let Some(id) = &args.prover.status {
+        let ok = network::print_status(id).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        std::process::exit(if ok { 0 } else { 2 });
+    }
+
+    let modes = [
+        args.execute,
+        args.prove,
+        args.prover.fetch.is_some(),
+        args.verify.is_some(),
+        args.verify_dir.is_some(),
+    ];
+    if modes.iter().filter(|&&mode| mode).count() != 1 {
+        eprintln!(
+            "Error: You must specify either --execute, --prove, --fetch, --verify or --verify-dir, or \
+             a subcommand"
+        );
+        std::process::exit(1);
+    }
+    if args.prover.no_wait
+        && (!args.prove || args.witnesses_file.is_some() || !args.bench.is_empty())
+    {
+        eprintln!("Error: --no-wait only applies to a single --prove");
         std::process::exit(1);
     }
 
     // Setup the prover client.
-    let client = ProverClient::new();
+    args.threads.init_prove_pool();
+    let client = args
+        .prover
+        .check()
+        .and_then(|_| args.prover.client())
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
 
-    // Setup the inputs
-    let leaves: Vec<[u8; 32]> = (0..args.total_leaves)
-        .map(|i| {
-            let mut hasher = Sha256::new();
-            hasher.update(i.to_le_bytes());
-            hasher.finalize().into()
-        })
-        .collect();
+    if let Some(path) = &args.verify {
+        let ok = verify_file(&client, path);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
 
-    let tree = MerkleTree::<Sha256Hasher>::from_leaves(&leaves);
-    let root = tree.root().expect("Failed to get root");
-    let leaf_index = rand::thread_rng().gen_range(0..args.total_leaves);
-    let leaf = leaves[leaf_index];
-    let proof = tree.proof(&[leaf_index]);
-    let proof_bytes = proof.to_bytes();
+    if let Some(dir) = &args.verify_dir {
+        let (_, vk) = client.setup(MERKLE_ELF);
+        let failed = verify_dir(&client, &vk, dir);
+        std::process::exit(if failed == 0 { 0 } else { 1 });
+    }
+
+    if let Some(id) = &args.prover.fetch {
+        let (_, vk) = client.setup(MERKLE_ELF);
+        let proof = network::fetch(id).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        println!("Successfully fetched proof!");
+        // The inputs were given to the run that submitted the request.
+        let report = args.report_path.is_some().then(RunReport::default);
+        save_proof(&args, &client, &vk, &proof, None, report);
+        return;
+    }
+
+    if let (Some(witnesses), Some(results)) = (&args.witnesses_file, &args.results_file) {
+        let failed = run_witnesses(
+            &client,
+            &args.shards,
+            args.prove,
+            args.fail_fast,
+            witnesses,
+            results,
+        );
+        std::process::exit(match failed {
+            0 => 0,
+            _ if args.fail_fast => 2,
+            _ => 1,
+        });
+    }
+
+    if !args.bench.is_empty() {
+        run_bench(&mut args, &client);
+        return;
+    }
 
     // Setup the inputs
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&root);
-    stdin.write(&leaf);
-    stdin.write(&proof_bytes);
-    stdin.write(&leaf_index);
-    stdin.write(&args.total_leaves);
+    let mut run = RunReport::default();
+    let (stdin, build_ms) = report::timed(|| args.threads.build(|| setup_inputs(&args.tree)));
+    let stdin = stdin.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    run.timings.build_ms = Some(build_ms);
 
-    println!("Total Leaves: {}", args.total_leaves);
+    if let Ok(scheme) = args.tree.scheme() {
+        println!("Scheme: {:?}", scheme);
+    }
 
     if args.execute {
+        if args.dump_pk_vk_info {
+            let (pk, vk) = client.setup(MERKLE_ELF);
+            print_key_info(&pk, &vk);
+        }
+
+        let software_stdin = args.compare_software_sha256.then(|| {
+            with_software_sha256(&stdin).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            })
+        });
+
         // Execute the program
-        let (output, report) = client.execute(MERKLE_ELF, stdin).run().unwrap();
+        let ((output, report), execute_ms) =
+            report::timed(|| client.execute(MERKLE_ELF, stdin).run().unwrap());
         println!("Program executed successfully.");
+        run.timings.execute_ms = Some(execute_ms);
+        run.record_execution(&report, output.as_slice());
+        check_public_values_size(output.as_slice(), args.max_public_values_bytes);
+        if let Some(path) = &args.output_public_values {
+            write_public_values(path, output.as_slice());
+        }
 
         // Read the output
-        let root = &output.as_slice()[0..32];
-        let leaf = &output.as_slice()[32..64];
-        let is_valid = output.as_slice()[64] != 0;
-
-        println!("Merkle Root: 0x{}", hex::encode(root));
-        println!("Leaf: 0x{}", hex::encode(leaf));
-        println!("Is Valid: {}", is_valid);
-        println!("Number of cycles: {}", report.total_instruction_count());
+        let output_bytes = output.as_slice().to_vec();
+        let output = decode_public_values(&output_bytes).expect("failed to decode public values");
+
+        println!("Merkle Root: 0x{}", hex::encode(output.root));
+        println!("Leaf: 0x{}", hex::encode(output.leaf));
+        println!("Is Valid: {}", output.is_valid);
+        print_extensions(&output);
+        let cycles = report.total_instruction_count();
+        println!("Number of cycles: {}", cycles);
+        for (syscall, &count) in report.syscall_counts.iter() {
+            if count > 0 {
+                println!("  {:?}: {}", syscall, count);
+            }
+        }
+
+        if let Some(stdin) = software_stdin {
+            let (software_output, software_report) =
+                client.execute(MERKLE_ELF, stdin).run().unwrap();
+            if software_output.as_slice() != output_bytes {
+                eprintln!("Error: software SHA-256 committed different public values");
+                std::process::exit(1);
+            }
+            let software_cycles = software_report.total_instruction_count();
+            println!("Number of cycles (software SHA-256): {}", software_cycles);
+            println!(
+                "SHA-256 precompiles saved {} cycles ({:.1}%)",
+                software_cycles as i64 - cycles as i64,
+                100.0 * (software_cycles as f64 - cycles as f64) / software_cycles as f64
+            );
+        }
+
+        if let Some(path) = &args.report_path {
+            write_report(path, args.report_format, run);
+        }
     } else {
+        // Execute first so an invalid membership is caught before spending time on the proof, and
+        // so the report has the cycles the proof covers.
+        if args.abort_on_invalid || args.report_path.is_some() {
+            let ((output, report), execute_ms) =
+                report::timed(|| client.execute(MERKLE_ELF, stdin.clone()).run().unwrap());
+            if args.abort_on_invalid {
+                abort_if_invalid(output.as_slice());
+            }
+            run.timings.execute_ms = Some(execute_ms);
+            run.record_execution(&report, output.as_slice());
+        }
+
         // Setup the program for proving.
-        let (pk, vk) = client.setup(MERKLE_ELF);
+        let ((pk, vk), setup_ms) = report::timed(|| client.setup(MERKLE_ELF));
+        run.timings.setup_ms = Some(setup_ms);
+        if args.dump_pk_vk_info {
+            print_key_info(&pk, &vk);
+        }
+
+        if args.prover.no_wait {
+            let id = network::submit(MERKLE_ELF, stdin, ProofMode::Core).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            println!("Proof Request: {}", id);
+            println!("Fetch the proof with --fetch {}", id);
+            return;
+        }
 
         // Generate the proof
-        let proof = client
-            .prove(&pk, stdin)
-            .run()
-            .expect("failed to generate proof");
+        let (proof, prove_ms) = report::timed(|| args.shards.apply(client.prove(&pk, stdin)).run());
+        let proof = proof.expect("failed to generate proof");
+        run.timings.prove_ms = Some(prove_ms);
 
         println!("Successfully generated proof!");
+        let scheme = match &args.tree.input_file {
+            Some(_) => None,
+            None => args
+                .tree
+                .scheme()
+                .ok()
+                .map(|scheme| format!("{:?}", scheme)),
+        };
+        let report = args.report_path.is_some().then_some(run);
+        save_proof(&args, &client, &vk, &proof, scheme, report);
+    }
+}
+
+/// Builds the tree store at `--tree-path`, or checks an existing one against the arguments, so that
+/// later runs read their proofs from it.
+fn build_tree(tree: &TreeArgs, threads: &ThreadArgs) {
+    let Some(path) = &tree.tree_path else {
+        eprintln!("Error: build-tree needs --tree-path");
+        std::process::exit(1);
+    };
+    if let Err(e) = threads.build(|| setup_inputs(tree)) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    println!("Tree store ready at {}", path.display());
+}
+
+/// Checks and verifies a generated or fetched proof, then saves it to `--proof-file` with its
+/// provenance, and writes `report` to `--report-path` with the verification time and proof size.
+fn save_proof(
+    args: &Args,
+    client: &ProverClient,
+    vk: &SP1VerifyingKey,
+    proof: &SP1ProofWithPublicValues,
+    scheme: Option<String>,
+    report: Option<RunReport>,
+) {
+    if args.abort_on_invalid {
+        abort_if_invalid(proof.public_values.as_slice());
+    }
+    check_public_values_size(proof.public_values.as_slice(), args.max_public_values_bytes);
+    if let Some(path) = &args.output_public_values {
+        write_public_values(path, proof.public_values.as_slice());
+    }
+
+    // Verify the proof.
+    let (verified, verify_ms) = report::timed(|| client.verify(proof, vk));
+    verified.expect("failed to verify proof");
+    println!("Successfully verified proof!");
+
+    proof.save(&args.proof_file).expect("failed to save proof");
+    println!("Proof saved to {}", args.proof_file.display());
+
+    let metadata = ProofMetadata {
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock before the Unix epoch")
+            .as_secs(),
+        version: env!("CARGO_PKG_VERSION"),
+        elf_hash: format!("0x{}", hex::encode(elf_hash())),
+        vkey: vk.bytes32(),
+        scheme,
+        args: redacted_args(std::env::args().skip(1)),
+    };
+    let path = metadata_path(&args.proof_file);
+    std::fs::write(&path, serde_json::to_string_pretty(&metadata).unwrap())
+        .expect("failed to write proof metadata");
+    println!("Proof metadata saved to {}", path.display());
+
+    if let (Some(path), Some(mut report)) = (&args.report_path, report) {
+        report.timings.verify_ms = Some(verify_ms);
+        report.proof_bytes = Some(proof_bytes(proof));
+        write_report(path, args.report_format, report);
+    }
+}
+
+/// Returns the length of the bincode-serialized proof with its public values.
+fn proof_bytes(proof: &SP1ProofWithPublicValues) -> usize {
+    bincode::serialized_size(proof).expect("failed to serialize proof") as usize
+}
+
+/// Records the peak memory of the finished run and writes its report to `path`.
+fn write_report(path: &Path, format: ReportFormat, mut run: RunReport) {
+    run.finish();
+    if let Err(e) = report::write(path, format, &run) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Executes, or in prove mode proves and verifies, a synthetic tree of `2^k` leaves for every
+/// `--bench` k, prints their costs and writes their reports to `--report-path`.
+///
+/// The keys are set up once, so every prove-mode report shares one setup time. Peak memory is the
+/// process's peak so far, so it only grows from one tree to the next.
+fn run_bench(args: &mut Args, client: &ProverClient) {
+    let exit = |e: String| -> ! {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    };
+    let keys = args
+        .prove
+        .then(|| report::timed(|| client.setup(MERKLE_ELF)));
+
+    let mut points = Vec::new();
+    for log2_leaves in args.bench.clone() {
+        args.tree.total_leaves = 1 << log2_leaves;
+        let mut run = RunReport {
+            total_leaves: Some(args.tree.total_leaves),
+            ..Default::default()
+        };
+
+        let (stdin, build_ms) = report::timed(|| args.threads.build(|| setup_inputs(&args.tree)));
+        let stdin = stdin.unwrap_or_else(|e| exit(e));
+        run.timings.build_ms = Some(build_ms);
+
+        let (executed, execute_ms) =
+            report::timed(|| client.execute(MERKLE_ELF, stdin.clone()).run());
+        let (output, report) =
+            executed.unwrap_or_else(|e| exit(format!("failed to execute: {}", e)));
+        run.timings.execute_ms = Some(execute_ms);
+        run.record_execution(&report, output.as_slice());
+
+        if let Some(((pk, vk), setup_ms)) = &keys {
+            run.timings.setup_ms = Some(*setup_ms);
+            let (proof, prove_ms) =
+                report::timed(|| args.shards.apply(client.prove(pk, stdin)).run());
+            let proof = proof.unwrap_or_else(|e| exit(format!("failed to generate proof: {}", e)));
+            let (verified, verify_ms) = report::timed(|| client.verify(&proof, vk));
+            verified.unwrap_or_else(|e| exit(format!("failed to verify proof: {}", e)));
+            run.timings.prove_ms = Some(prove_ms);
+            run.timings.verify_ms = Some(verify_ms);
+            run.proof_bytes = Some(proof_bytes(&proof));
+        }
+        run.finish();
+        points.push(run);
+    }
+
+    print_bench(&points);
+    let bench = BenchReport {
+        mode: if args.prove { "prove" } else { "execute" }.to_string(),
+        points,
+    };
+    let path = args
+        .report_path
+        .as_ref()
+        .expect("--bench requires --report-path");
+    if let Err(e) = report::write(path, args.report_format, &bench) {
+        exit(e);
+    }
+}
+
+/// Prints the `--bench` reports as a table, with `-` for phases that did not run.
+fn print_bench(points: &[RunReport]) {
+    let ms = |ms: Option<u128>| ms.map_or("-".to_string(), |ms| ms.to_string());
+    println!(
+        "{:>10}  {:>12}  {:>10}  {:>12}  {:>10}  {:>11}  {:>11}",
+        "Leaves",
+        "Cycles",
+        "Build (ms)",
+        "Execute (ms)",
+        "Prove (ms)",
+        "Verify (ms)",
+        "Proof Bytes"
+    );
+    for point in points {
+        println!(
+            "{:>10}  {:>12}  {:>10}  {:>12}  {:>10}  {:>11}  {:>11}",
+            point.total_leaves.unwrap_or_default(),
+            point.cycles.unwrap_or_default(),
+            ms(point.timings.build_ms),
+            ms(point.timings.execute_ms),
+            ms(point.timings.prove_ms),
+            ms(point.timings.verify_ms),
+            point
+                .proof_bytes
+                .map_or("-".to_string(), |bytes| bytes.to_string())
+        );
+    }
+}
+
+/// How a saved proof was generated, written next to it by `--prove`.
+#[derive(Serialize)]
+struct ProofMetadata {
+    /// Seconds since the Unix epoch when the proof was saved.
+    timestamp: u64,
+    /// The version of this script.
+    version: &'static str,
+    /// The SHA-256 of the program ELF.
+    elf_hash: String,
+    vkey: String,
+    /// The tree scheme, unknown when the inputs come from `--input-file` or the proof from `--fetch`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheme: Option<String>,
+    /// The command-line arguments, with the values of secret arguments redacted.
+    args: Vec<String>,
+}
+
+/// The extension of proof metadata files, which `--verify-dir` skips.
+const METADATA_EXTENSION: &str = "meta.json";
+
+/// Returns the path of the metadata saved next to the proof at `proof_file`.
+fn metadata_path(proof_file: &Path) -> PathBuf {
+    proof_file.with_extension(METADATA_EXTENSION)
+}
+
+/// Loads the proof saved at `path`, prints its decoded public values and verifies it against the
+/// program's verification key, returning whether it verified.
+fn verify_file(client: &ProverClient, path: &Path) -> bool {
+    let proof = match SP1ProofWithPublicValues::load(path) {
+        Ok(proof) => proof,
+        Err(e) => {
+            eprintln!("Error: failed to load proof {}: {}", path.display(), e);
+            return false;
+        }
+    };
 
-        // Verify the proof.
-        client.verify(&proof, &vk).expect("failed to verify proof");
-        println!("Successfully verified proof!");
+    // Print what the proof claims first, so the wrong proof is noticed before verifying it.
+    match decode_public_values(proof.public_values.as_slice()) {
+        Ok(output) => {
+            println!("Merkle Root: 0x{}", hex::encode(output.root));
+            println!("Leaf: 0x{}", hex::encode(output.leaf));
+            println!("Is Valid: {}", output.is_valid);
+            print_extensions(&output);
+        }
+        Err(e) => println!("Public values do not decode: {}", e),
+    }
+
+    let (_, vk) = client.setup(MERKLE_ELF);
+    match client.verify(&proof, &vk) {
+        Ok(()) => {
+            println!("Successfully verified proof!");
+            true
+        }
+        Err(e) => {
+            eprintln!("Error: failed to verify proof: {}", e);
+            false
+        }
+    }
+}
+
+/// Prints every scheme the witness at `path` verifies under, or `no match`, and returns whether
+/// there was one.
+fn detect_scheme(path: &Path) -> bool {
+    let witness = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))
+        .and_then(|json| {
+            serde_json::from_str::<Witness>(&json).map_err(|e| format!("invalid witness: {}", e))
+        })
+        .and_then(|witness| witness.detect_schemes());
+    match witness {
+        Ok(schemes) if schemes.is_empty() => {
+            println!("Detected Scheme: no match");
+            false
+        }
+        Ok(schemes) => {
+            for scheme in schemes {
+                println!("Detected Scheme: {}", scheme);
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            false
+        }
+    }
+}
+
+/// Verifies every file in `dir` against `vk`, continuing past failures, and returns the number of
+/// files that failed to load or verify.
+fn verify_dir(client: &ProverClient, vk: &SP1VerifyingKey, dir: &Path) -> usize {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| {
+            eprintln!("Error: failed to read {}: {}", dir.display(), e);
+            std::process::exit(1);
+        })
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            !path
+                .to_string_lossy()
+                .ends_with(&format!(".{}", METADATA_EXTENSION))
+        })
+        .collect();
+    paths.sort();
+
+    let mut failed = 0;
+    for path in &paths {
+        let result = SP1ProofWithPublicValues::load(path)
+            .map_err(|e| format!("failed to load proof: {}", e))
+            .and_then(|proof| client.verify(&proof, vk).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => println!("[pass] {}", path.display()),
+            Err(e) => {
+                println!("[fail] {}: {}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "Verified {} proofs: {} passed, {} failed",
+        paths.len(),
+        paths.len() - failed,
+        failed
+    );
+    failed
+}
+
+/// The outcome of one `--witnesses-file` line.
+#[derive(Serialize)]
+struct WitnessResult {
+    /// The 1-based line number of the witness.
+    line: usize,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public_values: Option<String>,
+    /// The cycle count, when executing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cycles: Option<u64>,
+    /// The bincode-serialized `SP1ProofWithPublicValues`, when proving.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Executes, or with `prove` proves and verifies, every witness in `witnesses`, continuing past
+/// failures unless `fail_fast` is set, and writes one result per processed witness to `results`.
+/// Returns the number of witnesses that failed.
+///
+/// With `fail_fast`, a witness whose public values report `is_valid = 0` also counts as failed.
+fn run_witnesses(
+    client: &ProverClient,
+    shards: &ShardArgs,
+    prove: bool,
+    fail_fast: bool,
+    witnesses: &Path,
+    results: &Path,
+) -> usize {
+    let exit = |e: String| -> ! {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    };
+    let data = std::fs::read_to_string(witnesses)
+        .unwrap_or_else(|e| exit(format!("failed to read {}: {}", witnesses.display(), e)));
+    let mut out = std::fs::File::create(results)
+        .unwrap_or_else(|e| exit(format!("failed to create {}: {}", results.display(), e)));
+    let lines: Vec<(usize, &str)> = data
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, line))
+        .collect();
+    let keys = prove.then(|| client.setup(MERKLE_ELF));
+
+    let mut failed = 0;
+    for (done, &(line, json)) in lines.iter().enumerate() {
+        let mut result = WitnessResult {
+            line,
+            ok: false,
+            public_values: None,
+            cycles: None,
+            proof: None,
+            error: None,
+        };
+        let outcome = serde_json::from_str::<Witness>(json)
+            .map_err(|e| format!("invalid witness: {}", e))
+            .and_then(|witness| witness.to_stdin())
+            .and_then(|stdin| match &keys {
+                Some((pk, vk)) => {
+                    let proof = shards
+                        .apply(client.prove(pk, stdin))
+                        .run()
+                        .map_err(|e| format!("failed to generate proof: {}", e))?;
+                    client
+                        .verify(&proof, vk)
+                        .map_err(|e| format!("failed to verify proof: {}", e))?;
+                    result.proof = Some(format!(
+                        "0x{}",
+                        hex::encode(bincode::serialize(&proof).expect("failed to serialize"))
+                    ));
+                    Ok(proof.public_values)
+                }
+                None => {
+                    let (output, report) = client
+                        .execute(MERKLE_ELF, stdin)
+                        .run()
+                        .map_err(|e| format!("failed to execute: {}", e))?;
+                    result.cycles = Some(report.total_instruction_count());
+                    Ok(output)
+                }
+            })
+            .and_then(|public_values| {
+                if fail_fast && !is_valid_membership(public_values.as_slice()) {
+                    return Err("invalid membership (is_valid = 0)".to_string());
+                }
+                Ok(public_values)
+            });
+        match outcome {
+            Ok(public_values) => {
+                result.ok = true;
+                result.public_values = Some(format!("0x{}", hex::encode(public_values.as_slice())));
+            }
+            Err(e) => {
+                result.error = Some(e);
+                failed += 1;
+            }
+        }
+        println!(
+            "[{}/{}] line {}: {}",
+            done + 1,
+            lines.len(),
+            line,
+            result.error.as_deref().unwrap_or("ok")
+        );
+        writeln!(out, "{}", serde_json::to_string(&result).unwrap())
+            .unwrap_or_else(|e| exit(format!("failed to write {}: {}", results.display(), e)));
+        if fail_fast && failed > 0 {
+            println!(
+                "Stopped at line {} after {} of {} witnesses",
+                line,
+                done + 1,
+                lines.len()
+            );
+            return failed;
+        }
+    }
+
+    println!(
+        "Processed {} witnesses: {} succeeded, {} failed",
+        lines.len(),
+        lines.len() - failed,
+        failed
+    );
+    failed
+}
+
+/// Returns whether committed public values decode and report a valid membership.
+fn is_valid_membership(public_values: &[u8]) -> bool {
+    decode_public_values(public_values).is_ok_and(|values| values.is_valid)
+}
+
+/// Exits with an error if the committed public values report an invalid membership.
+fn abort_if_invalid(public_values: &[u8]) {
+    let values = decode_public_values(public_values).expect("failed to decode public values");
+    if !values.is_valid {
+        eprintln!("Error: the leaf is not a member of the tree (is_valid = 0)");
+        std::process::exit(1);
     }
 }
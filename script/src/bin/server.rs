@@ -0,0 +1,51 @@
+//! A long-running proving service: sets up the prover and proving key once, then proves JSON
+//! witnesses posted to `/prove` until stopped. The same service as `goldinals --serve`.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --features serve --bin server -- --addr 127.0.0.1:3000
+//! ```
+
+use clap::Parser;
+use goldinals_script::{
+    network::{self, ProverKind},
+    serve::{self, ServeArgs},
+    ThreadArgs,
+};
+use std::net::SocketAddr;
+
+/// The arguments for the server command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct ServerArgs {
+    /// The address to listen on.
+    #[clap(long, default_value = "127.0.0.1:3000")]
+    addr: SocketAddr,
+
+    #[clap(flatten)]
+    service: ServeArgs,
+
+    #[clap(flatten)]
+    threads: ThreadArgs,
+
+    /// The prover to prove on [default: the one `SP1_PROVER` selects, or cpu].
+    #[clap(long, value_enum)]
+    prover: Option<ProverKind>,
+}
+
+fn main() {
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+
+    // Parse the command line arguments.
+    let args = ServerArgs::parse();
+
+    // Setup the prover client.
+    args.threads.init_prove_pool();
+    let client = network::client(args.prover).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    serve::run(args.addr, client, &args.service);
+}
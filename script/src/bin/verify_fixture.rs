@@ -0,0 +1,181 @@
+//! Verifies Groth16 and PLONK fixtures written by `evm` and `aggregate` off-chain, with the same
+//! checks as the SP1 verifier contract, so that fixtures can be validated without deploying it.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! cargo run --release --bin verify-fixture -- ../contracts/src/fixtures
+//! ```
+
+use clap::{Parser, ValueEnum};
+use goldinals_lib::abi;
+use goldinals_script::{decode_public_values, parse_hash};
+use serde::Deserialize;
+use sp1_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+use std::path::{Path, PathBuf};
+
+/// The arguments for the verify-fixture command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct VerifyFixtureArgs {
+    /// The fixtures to verify, or directories whose `.json` fixtures are all verified.
+    #[clap(default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../contracts/src/fixtures"))]
+    paths: Vec<PathBuf>,
+
+    /// The proof system of every fixture [default: the one in each file name].
+    #[clap(long, value_enum)]
+    system: Option<ProofSystem>,
+
+    /// Also require every fixture to be for this vkey.
+    #[clap(long)]
+    vkey: Option<String>,
+}
+
+/// Enum representing the available proof systems
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+enum ProofSystem {
+    Plonk,
+    Groth16,
+}
+
+/// The fields of a fixture that are verified. `root`, `leaf` and `isValid` are only in `evm`
+/// fixtures, and are checked against the public values when present.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Fixture {
+    vkey: String,
+    public_values: String,
+    proof: String,
+    root: Option<String>,
+    leaf: Option<String>,
+    is_valid: Option<bool>,
+}
+
+fn main() {
+    let args = VerifyFixtureArgs::parse();
+
+    let mut paths = Vec::new();
+    for path in &args.paths {
+        match fixture_paths(path) {
+            Ok(found) => paths.extend(found),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("Error: no fixtures found");
+        std::process::exit(1);
+    }
+
+    let mut ok = true;
+    for path in &paths {
+        let file = path.file_name().unwrap().to_string_lossy().into_owned();
+        if file.starts_with("insecure-mock-") {
+            println!("[skip] {}: mock proofs cannot be verified", file);
+            continue;
+        }
+        match verify_fixture(path, &file, &args) {
+            Ok(system) => println!("[ok] {} ({:?})", file, system),
+            Err(e) => {
+                println!("[fail] {}: {}", file, e);
+                ok = false;
+            }
+        }
+    }
+    std::process::exit(if ok { 0 } else { 1 });
+}
+
+/// Returns `path` if it is a file, or every `.json` fixture in it, sorted, if it is a directory.
+fn fixture_paths(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "json")
+                && path.file_name().is_some_and(|name| name != "manifest.json")
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Verifies the fixture at `path` and returns the proof system it was verified with.
+fn verify_fixture(
+    path: &Path,
+    file: &str,
+    args: &VerifyFixtureArgs,
+) -> Result<ProofSystem, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let fixture: Fixture = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    if let Some(vkey) = &args.vkey {
+        if !fixture.vkey.eq_ignore_ascii_case(vkey) {
+            return Err(format!("vkey {} is not {}", fixture.vkey, vkey));
+        }
+    }
+
+    let system = match args.system {
+        Some(system) => system,
+        None if file.contains("groth16") => ProofSystem::Groth16,
+        None if file.contains("plonk") => ProofSystem::Plonk,
+        None => return Err("no proof system in the file name, pass --system".to_string()),
+    };
+    // The verifier reads the first four bytes of the proof as the hash of its verifying key.
+    let proof = decode_hex("proof", &fixture.proof)?;
+    if proof.len() < 4 {
+        return Err("proof is too short".to_string());
+    }
+    let mut public_values = decode_hex("publicValues", &fixture.public_values)?;
+    // ABI fixtures share the proof of the raw bytes, which they re-pack into before verifying.
+    if file.ends_with("-abi-fixture.json") {
+        public_values = abi::decode(&public_values)
+            .ok_or_else(|| "publicValues is not an ABI-encoded struct".to_string())?;
+    }
+
+    match system {
+        ProofSystem::Groth16 => {
+            Groth16Verifier::verify(&proof, &public_values, &fixture.vkey, &GROTH16_VK_BYTES)
+                .map_err(|e| format!("invalid groth16 proof: {}", e))?
+        }
+        ProofSystem::Plonk => {
+            PlonkVerifier::verify(&proof, &public_values, &fixture.vkey, &PLONK_VK_BYTES)
+                .map_err(|e| format!("invalid plonk proof: {}", e))?
+        }
+    }
+
+    check_values(&fixture, &public_values)?;
+    Ok(system)
+}
+
+/// Checks that the fields an `evm` fixture records match the public values its proof commits.
+fn check_values(fixture: &Fixture, public_values: &[u8]) -> Result<(), String> {
+    if fixture.root.is_none() && fixture.leaf.is_none() && fixture.is_valid.is_none() {
+        return Ok(());
+    }
+    let values = decode_public_values(public_values)
+        .map_err(|e| format!("failed to decode public values: {}", e))?;
+    if let Some(root) = &fixture.root {
+        if parse_hash(root)? != values.root {
+            return Err(format!("root {} is not the committed root", root));
+        }
+    }
+    if let Some(leaf) = &fixture.leaf {
+        if parse_hash(leaf)? != values.leaf {
+            return Err(format!("leaf {} is not the committed leaf", leaf));
+        }
+    }
+    if let Some(is_valid) = fixture.is_valid {
+        if is_valid != values.is_valid {
+            return Err(format!("isValid {} is not the committed value", is_valid));
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a `0x`-prefixed hex field of a fixture.
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| format!("{}: {}", field, e))
+}
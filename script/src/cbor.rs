@@ -0,0 +1,114 @@
+//! CBOR (RFC 8949) datasets for `--leaves-cbor`.
+//!
+//! A dataset is one top-level array whose items are byte strings, each the data of one leaf. Arrays
+//! and byte strings may be definite or indefinite length. Items of any other type are decoded too,
+//! so that every malformed entry can be reported, but are returned as [`Entry::Other`]. `ciborium`
+//! bounds how deeply items may nest, so a small file of nested arrays or tags is rejected rather
+//! than overflowing the stack.
+
+use ciborium::{de::Error, Value};
+
+/// An item of the top-level array.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry {
+    Bytes(Vec<u8>),
+    /// An item that is not a byte string, with the name of its type.
+    Other(&'static str),
+}
+
+/// Decodes a CBOR array into its entries.
+///
+/// Errors when the data is not exactly one well-formed array.
+pub fn decode_array(data: &[u8]) -> Result<Vec<Entry>, String> {
+    let mut reader = data;
+    let value: Value = ciborium::from_reader(&mut reader).map_err(|e| match e {
+        Error::Io(_) => "unexpected end of data".to_string(),
+        Error::Syntax(offset) => format!("invalid item at byte {}", offset),
+        Error::Semantic(_, message) => message,
+        Error::RecursionLimitExceeded => "items are nested too deeply".to_string(),
+    })?;
+    if !reader.is_empty() {
+        return Err(format!("{} trailing bytes after the array", reader.len()));
+    }
+
+    match value {
+        Value::Array(items) => Ok(items
+            .into_iter()
+            .map(|item| match item {
+                Value::Bytes(bytes) => Entry::Bytes(bytes),
+                item => Entry::Other(type_name(&item)),
+            })
+            .collect()),
+        value => Err(format!("expected an array, found {}", type_name(&value))),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Integer(n) if i128::from(*n) < 0 => "a negative integer",
+        Value::Integer(_) => "an unsigned integer",
+        Value::Bytes(_) => "a byte string",
+        Value::Text(_) => "a text string",
+        Value::Array(_) => "an array",
+        Value::Map(_) => "a map",
+        Value::Tag(..) => "a tagged item",
+        _ => "a simple value or float",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definite_and_indefinite_items_are_decoded() {
+        // [h'0102', (_ h'03', h'04'), 7]
+        let data = [
+            0x83, 0x42, 0x01, 0x02, 0x5f, 0x41, 0x03, 0x41, 0x04, 0xff, 0x07,
+        ];
+        assert_eq!(
+            decode_array(&data),
+            Ok(vec![
+                Entry::Bytes(vec![1, 2]),
+                Entry::Bytes(vec![3, 4]),
+                Entry::Other("an unsigned integer"),
+            ])
+        );
+        // [_ h'05', -1]
+        assert_eq!(
+            decode_array(&[0x9f, 0x41, 0x05, 0x20, 0xff]),
+            Ok(vec![
+                Entry::Bytes(vec![5]),
+                Entry::Other("a negative integer")
+            ])
+        );
+    }
+
+    #[test]
+    fn malformed_data_is_rejected() {
+        // Not an array.
+        assert!(decode_array(&[0x41, 0x00]).is_err());
+        // A byte string running past the end of the data.
+        assert!(decode_array(&[0x81, 0x45, 0x01]).is_err());
+        // A byte string whose length cannot be allocated.
+        assert!(
+            decode_array(&[0x81, 0x5b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err()
+        );
+        // An indefinite array without its break code.
+        assert!(decode_array(&[0x9f, 0x41, 0x00]).is_err());
+        // A reserved additional information value.
+        assert!(decode_array(&[0x81, 0x1c]).is_err());
+        // Trailing bytes after the array.
+        assert!(decode_array(&[0x80, 0x00]).is_err());
+        assert!(decode_array(&[]).is_err());
+    }
+
+    #[test]
+    fn deeply_nested_items_are_rejected() {
+        let too_deep = Err("items are nested too deeply".to_string());
+        let arrays = [vec![0x81; 100_000], vec![0x80]].concat();
+        assert_eq!(decode_array(&arrays), too_deep);
+        let tags = [vec![0x81], vec![0xc6; 100_000], vec![0x00]].concat();
+        assert_eq!(decode_array(&tags), too_deep);
+    }
+}
@@ -0,0 +1,165 @@
+//! Environment readiness checks for the `doctor` subcommand.
+//!
+//! Every check is read-only and bounded by a short timeout, so the doctor can run on any machine
+//! before committing to a long proof.
+
+use std::{
+    net::{TcpStream, ToSocketAddrs},
+    process::Command,
+    time::Duration,
+};
+
+use crate::MERKLE_ELF;
+
+/// The network RPC used by the SDK when `PROVER_NETWORK_RPC` is unset.
+const DEFAULT_NETWORK_RPC: &str = "https://rpc.succinct.xyz/";
+
+/// Memory recommended for local Groth16 and PLONK proofs.
+const EVM_PROOF_MEMORY_GB: u64 = 128;
+
+/// The outcome of a single check.
+enum Status {
+    Ok(String),
+    Warn(String),
+    Fail(String),
+}
+
+/// Runs every check, prints a status line for each, and returns whether all critical checks
+/// passed.
+pub fn run() -> bool {
+    let checks = [
+        ("Program ELF", check_elf()),
+        ("SP1 toolchain", check_toolchain()),
+        ("Prover", check_prover()),
+        ("Memory", check_memory()),
+    ];
+
+    let mut ok = true;
+    for (name, status) in checks {
+        match status {
+            Status::Ok(message) => println!("[ok]   {}: {}", name, message),
+            Status::Warn(message) => println!("[warn] {}: {}", name, message),
+            Status::Fail(message) => {
+                println!("[fail] {}: {}", name, message);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+/// Checks that the embedded ELF is a 32-bit RISC-V executable.
+fn check_elf() -> Status {
+    if MERKLE_ELF.len() < 20 || &MERKLE_ELF[..4] != b"\x7fELF" {
+        return Status::Fail("embedded program is not an ELF file".to_string());
+    }
+    let is_32_bit = MERKLE_ELF[4] == 1;
+    let is_risc_v = u16::from_le_bytes([MERKLE_ELF[18], MERKLE_ELF[19]]) == 0xf3;
+    if !is_32_bit || !is_risc_v {
+        return Status::Fail("embedded program is not a 32-bit RISC-V ELF".to_string());
+    }
+    Status::Ok(format!("{} bytes, riscv32", MERKLE_ELF.len()))
+}
+
+/// Checks that `cargo prove` and the `succinct` rustup toolchain are installed.
+fn check_toolchain() -> Status {
+    let cargo_prove = match Command::new("cargo").args(["prove", "--version"]).output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        _ => return Status::Fail("`cargo prove` not found, install it with sp1up".to_string()),
+    };
+
+    let has_succinct = Command::new("rustup")
+        .args(["toolchain", "list"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("succinct"))
+        .unwrap_or(false);
+    if !has_succinct {
+        return Status::Fail("the `succinct` rustup toolchain is not installed".to_string());
+    }
+    Status::Ok(cargo_prove)
+}
+
+/// Checks the prover selected by `SP1_PROVER`.
+fn check_prover() -> Status {
+    if crate::use_mock_prover() {
+        return Status::Warn("SP1_USE_MOCK is set, the mock prover will be used".to_string());
+    }
+    let prover = std::env::var("SP1_PROVER")
+        .unwrap_or("local".to_string())
+        .to_lowercase();
+    match prover.as_str() {
+        "mock" => Status::Warn("mock prover selected, proofs will not be secure".to_string()),
+        "local" => {
+            let has_docker = Command::new("docker")
+                .arg("info")
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false);
+            if has_docker {
+                Status::Ok("local prover, docker available for Groth16/PLONK".to_string())
+            } else {
+                Status::Warn(
+                    "local prover, docker unavailable so Groth16/PLONK will fail".to_string(),
+                )
+            }
+        }
+        "network" => {
+            if std::env::var("SP1_PRIVATE_KEY").map_or(true, |key| key.is_empty()) {
+                return Status::Fail(
+                    "SP1_PRIVATE_KEY must be set for the network prover".to_string(),
+                );
+            }
+            let rpc =
+                std::env::var("PROVER_NETWORK_RPC").unwrap_or(DEFAULT_NETWORK_RPC.to_string());
+            if is_reachable(&rpc) {
+                Status::Ok(format!("network prover, {} reachable", rpc))
+            } else {
+                Status::Fail(format!("network prover, {} unreachable", rpc))
+            }
+        }
+        other => Status::Fail(format!(
+            "invalid SP1_PROVER `{}`, expected `local`, `mock` or `network`",
+            other
+        )),
+    }
+}
+
+/// Checks total memory against the recommendation for EVM-compatible proofs (Linux only).
+fn check_memory() -> Status {
+    let total_kb = std::fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| {
+            let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+            line.split_whitespace().nth(1)?.parse::<u64>().ok()
+        });
+    match total_kb {
+        Some(kb) if kb / 1024 / 1024 >= EVM_PROOF_MEMORY_GB => {
+            Status::Ok(format!("{} GB", kb / 1024 / 1024))
+        }
+        Some(kb) => Status::Warn(format!(
+            "{} GB, local Groth16/PLONK proofs need about {} GB",
+            kb / 1024 / 1024,
+            EVM_PROOF_MEMORY_GB
+        )),
+        None => Status::Warn("unable to read total memory".to_string()),
+    }
+}
+
+/// Returns whether a TCP connection to the URL's host can be opened within a few seconds.
+fn is_reachable(url: &str) -> bool {
+    let without_scheme = url.split("://").nth(1).unwrap_or(url);
+    let authority = without_scheme.split('/').next().unwrap_or(without_scheme);
+    let address = match authority.contains(':') {
+        true => authority.to_string(),
+        false if url.starts_with("http://") => format!("{}:80", authority),
+        false => format!("{}:443", authority),
+    };
+
+    address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .is_some_and(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(3)).is_ok())
+}
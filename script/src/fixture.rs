@@ -0,0 +1,569 @@
+//! Groth16 and PLONK fixtures for verifying proofs in Solidity, written by the `evm` binary and
+//! `goldinals fixture`.
+
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{
+    action::Prove, HashableKey, ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1Stdin,
+    SP1VerifyingKey,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    abi_encode_public_values, check_public_values_size, decode_public_values,
+    network::{self, ProofMode, ProverArgs},
+    print_extensions, print_key_info, setup_inputs, use_mock_prover, write_public_values,
+    ShardArgs, ThreadArgs, TreeArgs, MERKLE_ELF,
+};
+
+/// The arguments for writing a fixture, shared by the `evm` binary and `goldinals fixture`.
+#[derive(Args, Debug)]
+pub struct FixtureArgs {
+    #[clap(flatten)]
+    pub tree: TreeArgs,
+
+    #[clap(flatten)]
+    pub threads: ThreadArgs,
+
+    #[clap(flatten)]
+    pub shards: ShardArgs,
+
+    #[clap(flatten)]
+    pub prover: ProverArgs,
+
+    /// The proof system to wrap the proof in. A `--fetch` proof keeps the system it was requested
+    /// with.
+    #[clap(long, value_enum, default_value = "groth16")]
+    pub system: ProofSystem,
+    /// Print metadata about the proving and verifying keys after setup.
+    #[clap(long)]
+    pub dump_pk_vk_info: bool,
+    /// Also write an ABI-encoded fixture that shares the same proof. Strict and field-packed
+    /// public values do not have the struct's layout.
+    #[clap(long, conflicts_with_all = ["strict", "field_packed", "abi_encoded"])]
+    pub abi_fixture: bool,
+
+    /// Write the raw committed public values to this file.
+    #[clap(long)]
+    pub output_public_values: Option<PathBuf>,
+
+    /// Exit with an error if the committed public values are larger than this many bytes.
+    #[clap(long)]
+    pub max_public_values_bytes: Option<usize>,
+
+    /// Prove the same inputs with both groth16 and plonk and print a comparison instead of
+    /// writing fixtures.
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "system",
+            "abi_fixture",
+            "output_public_values",
+            "unique_fixture_names",
+            "no_wait",
+            "fetch",
+        ]
+    )]
+    pub compare_systems: bool,
+
+    /// Also write the `--compare-systems` results to this path as JSON.
+    #[clap(long, requires = "compare_systems")]
+    pub compare_json: Option<PathBuf>,
+
+    /// Name fixtures after the root and public values they carry rather than only the proof
+    /// system, so that fixtures of different inputs do not overwrite each other.
+    #[clap(long)]
+    pub unique_fixture_names: bool,
+
+    /// Exit with an error instead of proving if executing the program takes more than this many
+    /// cycles.
+    #[clap(long)]
+    pub max_cycles: Option<u64>,
+
+    /// Summarize the fixtures in this directory into its `manifest.json` instead of proving, and
+    /// exit with an error if they do not all share one vkey.
+    #[clap(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+/// Enum representing the available proof systems
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum ProofSystem {
+    Plonk,
+    Groth16,
+}
+
+/// The measurements of one proof system in `--compare-systems`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemComparison {
+    system: String,
+    proof_bytes: usize,
+    public_values_bytes: usize,
+    prove_ms: u128,
+    verify_ms: u128,
+}
+
+/// One fixture in a `--manifest` summary.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+    file: String,
+    /// The proof system, from the file name.
+    system: String,
+    vkey: String,
+    /// SHA-256 of the fixture's `publicValues` bytes, ABI-encoded for ABI fixtures.
+    public_values_hash: String,
+    /// Whether the vkey differs from the one most fixtures share.
+    stale: bool,
+}
+
+/// The fields of a fixture that `--manifest` reads, so that fixtures of older layouts are listed
+/// too.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureKeys {
+    vkey: String,
+    public_values: String,
+}
+
+/// The `manifest.json` written by `--manifest`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    /// The vkey most fixtures share, or `None` if several are shared equally often.
+    vkey: Option<String>,
+    fixtures: Vec<ManifestEntry>,
+}
+
+/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SP1MerkleProofFixture {
+    root: String,
+    leaf: String,
+    is_valid: bool,
+    leaf_index: u64,
+    total_leaves: u64,
+    /// The committed diagnostic codes, if the program recovered from a failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    diagnostics: Option<Vec<u8>>,
+    vkey: String,
+    public_values: String,
+    proof: String,
+}
+
+/// Proves the inputs `args` describe with Groth16 or PLONK and writes the fixtures, or runs the
+/// `--manifest`, `--status`, `--fetch` or `--compare-systems` mode instead.
+pub fn run(args: &FixtureArgs) {
+    if let Some(dir) = &args.manifest {
+        let ok = write_manifest(dir);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    if let Some(id) = &args.prover.status {
+        let ok = network::print_status(id).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        std::process::exit(if ok { 0 } else { 2 });
+    }
+
+    // Setup the prover client.
+    args.threads.init_prove_pool();
+    let client = args
+        .prover
+        .check()
+        .and_then(|_| args.prover.client())
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+
+    // Setup the program.
+    let (pk, vk) = client.setup(MERKLE_ELF);
+    if args.dump_pk_vk_info {
+        print_key_info(&pk, &vk);
+    }
+
+    if let Some(id) = &args.prover.fetch {
+        let proof = network::fetch(id).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        let system = match proof.proof {
+            SP1Proof::Plonk(_) => ProofSystem::Plonk,
+            SP1Proof::Groth16(_) => ProofSystem::Groth16,
+            _ => {
+                eprintln!("Error: {} is not a Groth16 or PLONK proof", id);
+                std::process::exit(1);
+            }
+        };
+        println!("Proof System: {:?}", system);
+        client.verify(&proof, &vk).expect("failed to verify proof");
+        check_public_values_size(proof.public_values.as_slice(), args.max_public_values_bytes);
+        if let Some(path) = &args.output_public_values {
+            write_public_values(path, proof.public_values.as_slice());
+        }
+        create_proof_fixture(
+            &proof,
+            &vk,
+            system,
+            args.abi_fixture,
+            args.unique_fixture_names,
+        );
+        return;
+    }
+
+    // Setup the inputs
+    let stdin = args
+        .threads
+        .build(|| setup_inputs(&args.tree))
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+
+    if let Ok(scheme) = args.tree.scheme() {
+        println!("Scheme: {:?}", scheme);
+    }
+
+    // Execute first to report the cost, and to compare against the proven public values.
+    let executed = execute(&client, stdin.clone(), args.max_cycles);
+
+    if args.compare_systems {
+        let comparisons: Vec<SystemComparison> = [ProofSystem::Groth16, ProofSystem::Plonk]
+            .into_iter()
+            .map(|system| {
+                println!("Proving With: {:?}", system);
+                compare_system(
+                    &client,
+                    args.shards.apply(client.prove(&pk, stdin.clone())),
+                    &vk,
+                    system,
+                    &executed,
+                    args.max_public_values_bytes,
+                )
+            })
+            .collect();
+        print_comparison(&comparisons);
+        if let Some(path) = &args.compare_json {
+            std::fs::write(path, serde_json::to_string_pretty(&comparisons).unwrap())
+                .expect("failed to write the comparison");
+            println!("Wrote comparison to {}", path.display());
+        }
+        return;
+    }
+
+    println!("Proof System: {:?}", args.system);
+
+    if args.prover.no_wait {
+        let mode = match args.system {
+            ProofSystem::Plonk => ProofMode::Plonk,
+            ProofSystem::Groth16 => ProofMode::Groth16,
+        };
+        let id = network::submit(MERKLE_ELF, stdin, mode).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        println!("Proof Request: {}", id);
+        println!("Fetch the proof with --fetch {}", id);
+        return;
+    }
+
+    // Generate the proof based on the selected proof system.
+    let prove = args.shards.apply(client.prove(&pk, stdin));
+    let proof = match args.system {
+        ProofSystem::Plonk => prove.plonk().run(),
+        ProofSystem::Groth16 => prove.groth16().run(),
+    }
+    .expect("failed to generate proof");
+
+    check_deterministic(&executed, proof.public_values.as_slice());
+    check_public_values_size(proof.public_values.as_slice(), args.max_public_values_bytes);
+    if let Some(path) = &args.output_public_values {
+        write_public_values(path, proof.public_values.as_slice());
+    }
+
+    create_proof_fixture(
+        &proof,
+        &vk,
+        args.system,
+        args.abi_fixture,
+        args.unique_fixture_names,
+    );
+}
+
+/// Executes the program on `stdin`, prints its cycle count and syscall breakdown, and returns the
+/// committed public values. Exits with an error if execution fails or takes more than
+/// `max_cycles` cycles.
+fn execute(client: &ProverClient, stdin: SP1Stdin, max_cycles: Option<u64>) -> Vec<u8> {
+    let (output, report) = client.execute(MERKLE_ELF, stdin).run().unwrap_or_else(|e| {
+        eprintln!("Error: failed to execute the program: {}", e);
+        std::process::exit(1);
+    });
+    let cycles = report.total_instruction_count();
+    println!("Number of cycles: {}", cycles);
+    for (syscall, &count) in report.syscall_counts.iter() {
+        if count > 0 {
+            println!("  {:?}: {}", syscall, count);
+        }
+    }
+    if let Some(max_cycles) = max_cycles {
+        if cycles > max_cycles {
+            eprintln!(
+                "Error: execution took {} cycles, over the --max-cycles budget of {}",
+                cycles, max_cycles
+            );
+            std::process::exit(1);
+        }
+    }
+    output.as_slice().to_vec()
+}
+
+/// Exits with an error if the proven public values differ from the executed ones, which would mean
+/// the program is not deterministic on its inputs.
+fn check_deterministic(executed: &[u8], proven: &[u8]) {
+    if executed != proven {
+        eprintln!(
+            "Error: the proven public values differ from the executed ones\n  executed: 0x{}\n  proven:   0x{}",
+            hex::encode(executed),
+            hex::encode(proven)
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Runs `prove` with `system`, checks the public values against the `executed` ones and their size,
+/// and times verifying the result.
+///
+/// Both systems wrap the same SP1 proof, so they share one proving key and `vk`; each additionally
+/// needs its own circuit artifacts, which the SDK downloads on first use and which are not timed
+/// separately.
+fn compare_system(
+    client: &ProverClient,
+    prove: Prove,
+    vk: &SP1VerifyingKey,
+    system: ProofSystem,
+    executed: &[u8],
+    max_public_values_bytes: Option<usize>,
+) -> SystemComparison {
+    let start = Instant::now();
+    let proof = match system {
+        ProofSystem::Plonk => prove.plonk().run(),
+        ProofSystem::Groth16 => prove.groth16().run(),
+    }
+    .expect("failed to generate proof");
+    let prove_ms = start.elapsed().as_millis();
+    check_deterministic(executed, proof.public_values.as_slice());
+    check_public_values_size(proof.public_values.as_slice(), max_public_values_bytes);
+
+    let start = Instant::now();
+    client.verify(&proof, vk).expect("failed to verify proof");
+    let verify_ms = start.elapsed().as_millis();
+
+    SystemComparison {
+        system: format!("{:?}", system).to_lowercase(),
+        proof_bytes: proof.bytes().len(),
+        public_values_bytes: proof.public_values.as_slice().len(),
+        prove_ms,
+        verify_ms,
+    }
+}
+
+/// Prints the `--compare-systems` results as a table.
+fn print_comparison(comparisons: &[SystemComparison]) {
+    println!(
+        "{:<8}  {:>11}  {:>18}  {:>10}  {:>11}",
+        "System", "Proof Bytes", "Public Value Bytes", "Prove (ms)", "Verify (ms)"
+    );
+    for comparison in comparisons {
+        println!(
+            "{:<8}  {:>11}  {:>18}  {:>10}  {:>11}",
+            comparison.system,
+            comparison.proof_bytes,
+            comparison.public_values_bytes,
+            comparison.prove_ms,
+            comparison.verify_ms
+        );
+    }
+}
+
+/// Writes `manifest.json` summarizing every fixture in `dir`, and returns whether all of them
+/// parsed and share one vkey.
+///
+/// Fixtures whose vkey differs from the one most fixtures share were likely generated before a
+/// guest change and are marked stale.
+fn write_manifest(dir: &Path) -> bool {
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "json")
+                    && path.file_name().is_some_and(|name| name != "manifest.json")
+            })
+            .collect(),
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", dir.display(), e);
+            return false;
+        }
+    };
+    paths.sort();
+
+    let mut ok = true;
+    let mut fixtures = Vec::new();
+    for path in paths {
+        let file = path.file_name().unwrap().to_string_lossy().into_owned();
+        let fixture = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str::<FixtureKeys>(&json).map_err(|e| e.to_string()));
+        match fixture {
+            Ok(fixture) => fixtures.push((file, fixture)),
+            Err(e) => {
+                println!("[fail] {}: {}", file, e);
+                ok = false;
+            }
+        }
+    }
+
+    let mut counts = BTreeMap::new();
+    for (_, fixture) in &fixtures {
+        *counts.entry(fixture.vkey.as_str()).or_insert(0) += 1;
+    }
+    // Without a vkey shared by more fixtures than any other, there is no reference to compare
+    // against and every fixture is marked stale.
+    let most = counts.values().copied().max().unwrap_or(0);
+    let mut shared = counts.iter().filter(|(_, &count)| count == most);
+    let vkey = match (shared.next(), shared.next()) {
+        (Some((&vkey, _)), None) => Some(vkey.to_string()),
+        _ => None,
+    };
+
+    let entries: Vec<ManifestEntry> = fixtures
+        .into_iter()
+        .map(|(file, fixture)| {
+            let system = ["groth16", "plonk"]
+                .into_iter()
+                .find(|system| file.contains(system))
+                .unwrap_or("unknown")
+                .to_string();
+            let public_values =
+                hex::decode(fixture.public_values.trim_start_matches("0x")).unwrap_or_default();
+            let stale = vkey.as_deref() != Some(fixture.vkey.as_str());
+            if stale {
+                println!("[stale] {}: vkey {}", file, fixture.vkey);
+                ok = false;
+            } else {
+                println!("[ok] {}", file);
+            }
+            ManifestEntry {
+                file,
+                system,
+                vkey: fixture.vkey,
+                public_values_hash: format!("0x{}", hex::encode(Sha256::digest(&public_values))),
+                stale,
+            }
+        })
+        .collect();
+
+    let manifest = Manifest {
+        vkey,
+        fixtures: entries,
+    };
+    let path = dir.join("manifest.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&manifest).unwrap())
+        .expect("failed to write the manifest");
+    println!(
+        "Wrote manifest of {} fixtures to {}",
+        manifest.fixtures.len(),
+        path.display()
+    );
+    ok
+}
+
+/// Create a fixture for the given proof.
+///
+/// `{system}-fixture.json` carries the raw committed bytes, for verifiers that pass
+/// `publicValues` straight to the SP1 verifier. With `abi_fixture`, `{system}-abi-fixture.json`
+/// additionally carries the same values as `abi.encode(PublicValuesStruct)`, for verifiers that
+/// decode the struct and re-pack it before verifying. Both share one proof.
+///
+/// With `unique_names`, `{system}` is followed by `-{root}-{digest}`: the first four bytes of the
+/// root and of the SHA-256 of the raw public values, in hex, so that fixtures of different inputs
+/// coexist and regenerating the same inputs overwrites the same file.
+fn create_proof_fixture(
+    proof: &SP1ProofWithPublicValues,
+    vk: &SP1VerifyingKey,
+    system: ProofSystem,
+    abi_fixture: bool,
+    unique_names: bool,
+) {
+    let output = proof.public_values.as_slice();
+    let values = decode_public_values(output).expect("failed to decode public values");
+
+    // Create the testing fixture
+    let fixture = SP1MerkleProofFixture {
+        root: format!("0x{}", hex::encode(values.root)),
+        leaf: format!("0x{}", hex::encode(values.leaf)),
+        is_valid: values.is_valid,
+        leaf_index: values.leaf_index,
+        total_leaves: values.total_leaves,
+        diagnostics: values
+            .diagnostics
+            .as_ref()
+            .map(|diagnostics| diagnostics.iter().map(|&code| code as u8).collect()),
+        vkey: vk.bytes32().to_string(),
+        public_values: format!("0x{}", hex::encode(output)),
+        proof: format!("0x{}", hex::encode(proof.bytes())),
+    };
+
+    // Save the fixture, marking mock fixtures as such in the file name.
+    let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../contracts/src/fixtures");
+    let prefix = if use_mock_prover() {
+        "insecure-mock-"
+    } else {
+        ""
+    };
+    let stem = if unique_names {
+        format!(
+            "{:?}-{}-{}",
+            system,
+            hex::encode(&values.root[..4]),
+            hex::encode(&Sha256::digest(output)[..4])
+        )
+    } else {
+        format!("{:?}", system)
+    };
+    std::fs::create_dir_all(&fixture_path).expect("failed to create fixture path");
+    std::fs::write(
+        fixture_path.join(format!("{}{}-fixture.json", prefix, stem).to_lowercase()),
+        serde_json::to_string_pretty(&fixture).unwrap(),
+    )
+    .expect("failed to write fixture");
+
+    if abi_fixture {
+        let abi_fixture = SP1MerkleProofFixture {
+            public_values: format!("0x{}", hex::encode(abi_encode_public_values(output))),
+            ..fixture.clone()
+        };
+        std::fs::write(
+            fixture_path.join(format!("{}{}-abi-fixture.json", prefix, stem).to_lowercase()),
+            serde_json::to_string_pretty(&abi_fixture).unwrap(),
+        )
+        .expect("failed to write ABI fixture");
+        println!("ABI Public Values: {}", abi_fixture.public_values);
+    }
+
+    println!("Merkle Root: {}", fixture.root);
+    println!("Leaf: {}", fixture.leaf);
+    println!("Is Valid: {}", fixture.is_valid);
+    print_extensions(&values);
+    println!("Verification Key: {}", fixture.vkey);
+    println!("Public Values: {}", fixture.public_values);
+    println!("Proof Bytes: {}", fixture.proof);
+}
@@ -0,0 +1,125 @@
+//! Binary trees built level by level on rayon, with `rs_merkle`-compatible proofs.
+//!
+//! `rs_merkle::MerkleTree::from_leaves` hashes every level on one thread. [`LevelTree`] builds the
+//! same shape, pairing each level's nodes in parallel and promoting an unpaired last node
+//! unchanged, and yields the same roots and proof bytes, so the guest verifies its proofs with
+//! `rs_merkle` as before.
+
+use std::marker::PhantomData;
+
+use goldinals_lib::Hash;
+use rayon::prelude::*;
+use rs_merkle::{Hasher, MerkleProof};
+
+/// Every level of a binary tree under `H`, leaves first and root last.
+pub struct LevelTree<H> {
+    levels: Vec<Vec<Hash>>,
+    hasher: PhantomData<H>,
+}
+
+impl<H: Hasher<Hash = Hash>> LevelTree<H> {
+    /// Builds the tree over `leaves`, hashing the pairs of each level on the current rayon pool.
+    pub fn from_leaves(leaves: &[Hash]) -> Self {
+        let mut levels = vec![leaves.to_vec()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .par_chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => H::concat_and_hash(left, Some(right)),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self {
+            levels,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Returns the root, or `None` for a tree without leaves.
+    pub fn root(&self) -> Option<Hash> {
+        self.levels.last().unwrap().first().copied()
+    }
+
+    /// Returns the proof of the leaves at `indices`, which must be ascending and distinct, as
+    /// `rs_merkle::MerkleTree::proof` builds it.
+    ///
+    /// Each level contributes the siblings of the nodes on the leaves' paths that are neither on a
+    /// path themselves nor missing because the node is promoted, in index order.
+    pub fn proof(&self, indices: &[usize]) -> MerkleProof<H> {
+        let mut nodes = indices.to_vec();
+        let mut helpers = Vec::new();
+        for level in &self.levels {
+            helpers.extend(
+                nodes
+                    .iter()
+                    .map(|&index| index ^ 1)
+                    .filter(|sibling| !nodes.contains(sibling))
+                    .filter_map(|sibling| level.get(sibling)),
+            );
+            nodes = nodes.iter().map(|&index| index / 2).collect();
+            nodes.dedup();
+        }
+        MerkleProof::new(helpers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use goldinals_lib::{keccak::Keccak256Hasher, sha256::Sha256Hasher};
+    use rs_merkle::MerkleTree;
+
+    use super::*;
+
+    fn leaves(n: u8) -> Vec<Hash> {
+        (0..n).map(|i| Sha256Hasher::hash(&[i])).collect()
+    }
+
+    #[test]
+    fn roots_match_rs_merkle() {
+        for n in 0..=33 {
+            let leaves = leaves(n);
+            assert_eq!(
+                LevelTree::<Sha256Hasher>::from_leaves(&leaves).root(),
+                MerkleTree::<Sha256Hasher>::from_leaves(&leaves).root(),
+                "{} sha256 leaves",
+                n
+            );
+            assert_eq!(
+                LevelTree::<Keccak256Hasher>::from_leaves(&leaves).root(),
+                MerkleTree::<Keccak256Hasher>::from_leaves(&leaves).root(),
+                "{} keccak256 leaves",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn proofs_match_rs_merkle() {
+        let leaves = leaves(13);
+        let ours = LevelTree::<Sha256Hasher>::from_leaves(&leaves);
+        let theirs = MerkleTree::<Sha256Hasher>::from_leaves(&leaves);
+        // Single leaves, including the promoted last one, and batches sharing siblings.
+        let batches: Vec<Vec<usize>> = (0..13)
+            .map(|i| vec![i])
+            .chain([
+                vec![0, 1],
+                vec![2, 5, 12],
+                vec![3, 4, 8, 9, 11],
+                (0..13).collect(),
+            ])
+            .collect();
+        for indices in batches {
+            assert_eq!(
+                ours.proof(&indices).to_bytes(),
+                theirs.proof(&indices).to_bytes(),
+                "indices {:?}",
+                indices
+            );
+        }
+    }
+}
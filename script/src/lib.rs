@@ -0,0 +1,3284 @@
+//! Host-side helpers shared by the `goldinals` and `evm` binaries.
+
+use clap::{ArgGroup, Args, ValueEnum};
+use goldinals_lib::{
+    abi,
+    aggregate::{AggregateEntry, AggregateProofs},
+    append::{self, Append},
+    authorization::{self, Authorization},
+    batch::LeafBatch,
+    bitcoin::{self, MerkleBlock},
+    blake2b::{self, Blake2bTree},
+    blake3::{self, Blake3Tree},
+    blocklist::BlocklistExclusion,
+    bloom::BloomFilter,
+    bls::{self, BlsSignature, SignedValue},
+    checkpoint::CheckpointProof,
+    count::{self, CountProof},
+    erasure::{self, ChunkSample, Coding},
+    forest::{self, ForestLookup},
+    format::{self, HeaderError},
+    hybrid::{self, HybridTree},
+    imt::{self, ImtQuery},
+    index::{IndexLookup, IndexTree},
+    kary::KaryTree,
+    keccak, kzg,
+    mmr::Mmr,
+    public_values::{Canonicalization, DecodeError, RevealPhase, CORE_LEN, STRICT_LEN},
+    range::{self, RangeCount},
+    reveal::Reveal,
+    sorted::{Absence, Neighbor, Neighbors, PairedLeaf},
+    sparse::SparseTree,
+    transform::{LeafTransform, TransformedLeaf},
+    tree,
+    truncated::{self, TruncatedTree},
+    vdf::VdfProof,
+    Options, PublicValues, Scheme,
+};
+use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+use levels::LevelTree;
+use rand::Rng;
+use rayon::{prelude::*, ThreadPoolBuilder};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sp1_sdk::{
+    action::Prove, include_elf, HashableKey, ProverClient, SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
+};
+use std::{
+    collections::HashSet,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use store::{StoreHeader, TreeStore};
+use witness::Witness;
+
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod doctor;
+pub mod fixture;
+pub mod levels;
+pub mod network;
+#[cfg(feature = "proto")]
+pub mod proto;
+pub mod report;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod store;
+pub mod witness;
+
+/// The ELF file for the Merkle Tree program
+pub const MERKLE_ELF: &[u8] = include_elf!("goldinals-merkle-tree");
+
+pub use goldinals_lib::{keccak::Keccak256Hasher, sha256::Sha256Hasher};
+
+/// Enum representing the available tree hashing schemes
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum TreeScheme {
+    /// SHA-256 for leaves and internal nodes.
+    Sha256,
+    /// SHA-256 leaves with Poseidon (BN254) internal nodes.
+    Hybrid,
+    /// Keccak-256 leaves and sorted-pair internal nodes, as verified by OpenZeppelin's
+    /// `MerkleProof`.
+    Keccak256,
+    /// BLAKE2b-256 leaves and internal nodes under `--hash-personalization`.
+    Blake2b,
+    /// BLAKE3 leaves and internal nodes.
+    Blake3,
+}
+
+/// The formats `--proof-input` reads.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum ProofFormat {
+    /// A JSON object with the root, the leaf, its index, the number of leaves and the sibling
+    /// hashes from the leaf up, as OpenZeppelin's `MerkleTree` libraries return them.
+    Json,
+    /// The scheme's proof bytes, as written by `rs_merkle::MerkleProof::to_bytes` for binary
+    /// trees, with the root, leaf, index and number of leaves given by `--proof-root`,
+    /// `--proof-leaf`, `--leaf-index` and `--total-leaves`.
+    RsMerkle,
+}
+
+/// The committed values a BLS signature can sign.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum BlsSigned {
+    Leaf,
+    Root,
+}
+
+/// Normalizations applied to leaf data before hashing, see [`TreeArgs::hash_leaf`].
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum CanonicalizeMode {
+    /// Parse the data as JSON and re-serialize it with object keys sorted and no insignificant
+    /// whitespace. Data that is not valid JSON is rejected.
+    Json,
+    /// Lowercase ASCII letters, leaving every other byte unchanged.
+    Lowercase,
+    /// Remove leading and trailing ASCII whitespace.
+    Trim,
+}
+
+impl CanonicalizeMode {
+    /// Returns the canonical form of `data`.
+    pub fn apply(self, data: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            CanonicalizeMode::Json => {
+                let value: serde_json::Value = serde_json::from_slice(data)
+                    .map_err(|e| format!("leaf data is not valid JSON: {}", e))?;
+                Ok(serde_json::to_vec(&sort_keys(value)).expect("values serialize"))
+            }
+            CanonicalizeMode::Lowercase => Ok(data.to_ascii_lowercase()),
+            CanonicalizeMode::Trim => Ok(data.trim_ascii().to_vec()),
+        }
+    }
+
+    /// The mode as committed by the program.
+    pub fn committed(self) -> Canonicalization {
+        match self {
+            CanonicalizeMode::Json => Canonicalization::Json,
+            CanonicalizeMode::Lowercase => Canonicalization::Lowercase,
+            CanonicalizeMode::Trim => Canonicalization::Trim,
+        }
+    }
+}
+
+/// Rebuilds every object with its keys in sorted order, whatever order the map type keeps.
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(sort_keys).collect())
+        }
+        value => value,
+    }
+}
+
+/// Tree and witness arguments shared by both binaries.
+///
+/// The inputs that replace building a tree, such as `--trie-proof` or `--mmr`, form the `source`
+/// group, of which at most one is given. The options that only apply to a built tree form the
+/// `tree_options` group, which conflicts with every source. The files the leaves are read from
+/// instead of being generated form the `dataset` group, of which at most one is given.
+#[derive(Args, Debug)]
+#[clap(
+    group(ArgGroup::new("source").multiple(false)),
+    group(ArgGroup::new("dataset").multiple(false)),
+    group(ArgGroup::new("tree_options").multiple(true).conflicts_with("source"))
+)]
+pub struct TreeArgs {
+    #[clap(long, default_value = "40000000")]
+    pub total_leaves: usize,
+
+    /// Read the synthetic tree's nodes from the tree store at this path, building it there first if
+    /// it does not exist, instead of rehashing every leaf.
+    #[clap(long, group = "tree_options")]
+    pub tree_path: Option<PathBuf>,
+
+    #[clap(
+        long,
+        visible_alias = "hasher",
+        alias = "hash",
+        value_enum,
+        default_value = "sha256"
+    )]
+    pub scheme: TreeScheme,
+
+    /// Truncate every SHA-256 node to this many bytes (sha256 scheme only).
+    #[clap(long, value_parser = clap::value_parser!(u8).range(1..=32))]
+    pub hash_bytes: Option<u8>,
+
+    /// Build a tree with this many children per node (sha256 scheme only).
+    #[clap(long, value_parser = clap::value_parser!(u8).range(2..=16))]
+    pub arity: Option<u8>,
+
+    /// Personalize every BLAKE2b node with this string of at most 16 bytes (blake2b scheme only;
+    /// empty by default).
+    #[clap(long)]
+    pub hash_personalization: Option<String>,
+
+    /// Also commit whether the proven leaf equals this 32-byte hex value.
+    #[clap(long, value_parser = parse_hash)]
+    pub expect_leaf: Option<[u8; 32]>,
+
+    /// Build the tree over this CBOR array of byte strings, one leaf's data per item, instead of
+    /// `--total-leaves` synthetic leaves.
+    #[cfg(feature = "cbor")]
+    #[clap(
+        long,
+        conflicts_with = "global_index",
+        group = "dataset",
+        group = "tree_options"
+    )]
+    pub leaves_cbor: Option<PathBuf>,
+
+    /// Build the tree over these 32-byte leaves, taken verbatim, instead of `--total-leaves`
+    /// synthetic leaves. The file lists one hex leaf per line or holds a JSON array of 0x-prefixed
+    /// hex strings.
+    #[clap(
+        long,
+        conflicts_with_all = ["global_index", "leaf_transform"],
+        group = "dataset"
+    )]
+    pub leaves_file: Option<PathBuf>,
+
+    /// Build the tree over the Reed-Solomon coded chunks of this file, one chunk per leaf, instead
+    /// of `--total-leaves` synthetic leaves, and have the program check the proven chunk against
+    /// the coding parameters.
+    #[clap(
+        long,
+        requires = "data_chunks",
+        conflicts_with_all = ["global_index", "sorted_neighbors", "leaf_transform", "count_leaf"],
+        group = "dataset",
+        group = "tree_options",
+    )]
+    pub erasure_data: Option<PathBuf>,
+
+    /// The number of data chunks `--erasure-data` is split into.
+    #[clap(long, requires = "erasure_data", value_parser = clap::value_parser!(u16).range(1..=256))]
+    pub data_chunks: Option<u16>,
+
+    /// The number of chunks after coding [default: twice `--data-chunks`, at most 256].
+    #[clap(long, requires = "erasure_data", value_parser = clap::value_parser!(u16).range(1..=256))]
+    pub total_chunks: Option<u16>,
+
+    /// Build the tree over this stream of length-delimited protobuf messages, one leaf's data per
+    /// message, instead of `--total-leaves` synthetic leaves.
+    #[cfg(feature = "proto")]
+    #[clap(
+        long,
+        conflicts_with_all = ["global_index", "trie_proof"],
+        group = "dataset"
+    )]
+    pub leaves_proto: Option<PathBuf>,
+
+    /// Use every `--leaves-cbor` item as the leaf itself rather than hashing it; items must be
+    /// exactly 32 bytes.
+    #[cfg(feature = "cbor")]
+    #[clap(
+        long,
+        requires = "leaves_cbor",
+        conflicts_with_all = ["leaf_transform", "commit_preimage"]
+    )]
+    pub raw_leaves: bool,
+
+    /// Prove the leaf whose hash is the hash of this file's contents.
+    #[clap(long, group = "tree_options")]
+    pub leaf_from_file: Option<PathBuf>,
+
+    /// Prove the leaf at this index [default: a random leaf].
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "leaf_from_file",
+            "index_file",
+            "global_index",
+            "reveal",
+            "imt_values",
+        ]
+    )]
+    pub leaf_index: Option<u64>,
+
+    /// Derive every leaf from its data with this comma-separated chain of hashes, applied first to
+    /// last (`sha256`, `keccak256` or `sha3-256`), instead of SHA-256, and have the program check
+    /// the derivation of the proven leaf.
+    #[clap(
+        long,
+        value_parser = LeafTransform::parse,
+        conflicts_with = "sorted_neighbors",
+        group = "tree_options",
+    )]
+    pub leaf_transform: Option<LeafTransform>,
+
+    /// Pass the proven leaf's data to the program, which hashes it into the leaf itself and
+    /// commits the data's SHA-256 and length, binding the proof to the data rather than its hash.
+    /// The data's SHA-256 is the sha256 leaf, so it cannot be combined with hiding the leaf.
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "sorted_neighbors",
+            "leaf_transform",
+            "count_leaf",
+            "commit_leaf_hiding",
+            "prove_absent",
+        ],
+        group = "tree_options",
+    )]
+    pub commit_preimage: bool,
+
+    /// Normalize every leaf's data, including the `--leaf-from-file` contents, before hashing it,
+    /// and commit the mode.
+    #[clap(long, value_enum, group = "tree_options")]
+    pub canonicalize: Option<CanonicalizeMode>,
+
+    /// Also commit the leaf's position as `floor(leaf_index * 1e9 / total_leaves)`.
+    #[clap(long, group = "tree_options")]
+    pub commit_ordinal: bool,
+
+    /// Also prove that the proven leaf is absent from a blocklist tree over the 32-byte hex leaves
+    /// listed in this file, one per line, and commit the result.
+    #[clap(long, group = "tree_options")]
+    pub blocklist: Option<PathBuf>,
+
+    /// Reserve leaf 0 of the generated tree for the count leaf of its size, prove it and commit the
+    /// verified leaf count.
+    #[clap(long, conflicts_with_all = ["global_index", "sorted_neighbors"], group = "tree_options")]
+    pub count_leaf: bool,
+
+    /// Also commit the root with its bytes reversed.
+    #[clap(long)]
+    pub commit_root_reversed: bool,
+
+    /// Also commit every check result as a labelled list.
+    #[clap(long)]
+    pub tagged_results: bool,
+
+    /// Also commit the inclusion, sorted, range, authorization, leaf, index, pair and IMT results as
+    /// one bit each of a single byte instead of a labelled list.
+    #[clap(long, conflicts_with = "tagged_results")]
+    pub pack_flags: bool,
+
+    /// Also commit the SHA-256 of the embedded program ELF.
+    #[clap(long)]
+    pub commit_elf_hash: bool,
+
+    /// Also commit the EIP-712 digest of `(root, leaf, isValid)` under this 32-byte hex domain
+    /// separator.
+    #[clap(long, value_parser = parse_hash)]
+    pub eip712_domain: Option<[u8; 32]>,
+
+    /// Commit a Poseidon commitment to the leaf instead of the leaf itself.
+    #[clap(long, group = "tree_options")]
+    pub commit_leaf_hiding: bool,
+
+    /// The 32-byte hex randomness for `--commit-leaf-hiding` [default: freshly generated].
+    #[clap(long, value_parser = parse_hash, requires = "commit_leaf_hiding")]
+    pub leaf_randomness: Option<[u8; 32]>,
+
+    /// Replace the proven leaf with this 32-byte hex value and commit the new root; repeat to
+    /// chain several updates (sha256 and hybrid schemes only).
+    #[clap(long = "update", value_parser = parse_hash, group = "tree_options")]
+    pub updates: Vec<[u8; 32]>,
+
+    /// Append this 32-byte hex leaf to the tree and commit the new root; repeat to append several
+    /// leaves in order (sha256, hybrid and keccak256 schemes only).
+    #[clap(long = "append", value_parser = parse_hash, group = "tree_options")]
+    pub appends: Vec<[u8; 32]>,
+
+    /// Sign the leaf with this 32-byte hex secp256k1 secret key and have the program verify the
+    /// signature and commit the signer's address.
+    #[clap(long, value_parser = parse_hash, group = "tree_options")]
+    pub authorization_key: Option<[u8; 32]>,
+
+    /// Sign the leaf, or the root with `--bls-sign root`, with this 32-byte hex BLS12-381 secret
+    /// key and have the program verify the signature and commit the public key; repeat to
+    /// aggregate several validators' signatures.
+    #[clap(long = "bls-key", value_parser = parse_hash, group = "tree_options")]
+    pub bls_keys: Vec<[u8; 32]>,
+
+    /// The value the `--bls-key` signatures sign.
+    #[clap(long, value_enum, default_value = "leaf", requires = "bls_keys")]
+    pub bls_sign: BlsSigned,
+
+    /// Evaluate a VDF for this many sequential squarings over a challenge bound to the root and
+    /// leaf, and have the program verify it and commit the output.
+    #[clap(long, group = "tree_options")]
+    pub vdf_iterations: Option<u64>,
+
+    /// A 32-byte hex value the VDF challenge is derived from, e.g. a recent block hash, so the
+    /// delay starts no earlier than it was known [default: zero].
+    #[clap(long, value_parser = parse_hash, requires = "vdf_iterations")]
+    pub vdf_seed: Option<[u8; 32]>,
+
+    /// Also commit the number of leaves proven and of distinct sibling hashes in the proof
+    /// (sha256 scheme only).
+    #[clap(long, group = "tree_options")]
+    pub commit_proof_stats: bool,
+
+    /// Also commit the levels at which the leaf's path is promoted without a sibling (binary trees
+    /// only).
+    #[clap(long, group = "tree_options")]
+    pub commit_promotions: bool,
+
+    /// Also commit the node this many levels below the root that the leaf descends from, for
+    /// verifiers that store only a Merkle cap (sha256 and hybrid schemes).
+    #[clap(long, group = "tree_options")]
+    pub cap_level: Option<u8>,
+
+    /// Sort the leaves, and also prove the leaf's neighbors and commit that they are ordered
+    /// around it.
+    #[clap(long, group = "tree_options")]
+    pub sorted_neighbors: bool,
+
+    /// Sort the leaves, and also prove the two adjacent leaves bracketing a target value absent
+    /// from the tree and commit whether it is absent.
+    #[clap(
+        long,
+        conflicts_with_all = ["count_leaf", "global_index", "leaf_transform", "erasure_data"],
+        group = "tree_options",
+    )]
+    pub prove_absent: bool,
+
+    /// The 32-byte hex target of `--prove-absent` [default: a random value not in the tree].
+    #[clap(long, value_parser = parse_hash, requires = "prove_absent")]
+    pub absent_target: Option<[u8; 32]>,
+
+    /// Also commit to a polynomial through every leaf over a freshly generated, insecure test
+    /// setup, and have the program verify its KZG opening at the leaf's index.
+    #[clap(long, group = "tree_options")]
+    pub kzg_opening: bool,
+
+    /// Sizes of the trees of a forest laid end to end over one global index space, e.g.
+    /// `1024,512,2048`; the tree `--global-index` maps to is proven instead of `--total-leaves`.
+    #[clap(long, value_delimiter = ',', requires = "global_index")]
+    pub forest_sizes: Vec<u64>,
+
+    /// Prove the leaf at this global index of the `--forest-sizes` forest and commit the mapping.
+    #[clap(
+        long,
+        requires = "forest_sizes",
+        conflicts_with_all = ["leaf_from_file", "index_file", "sorted_neighbors"],
+        group = "tree_options",
+    )]
+    pub global_index: Option<u64>,
+
+    /// Also prove the leaf at this index and commit whether it comes after the proven leaf.
+    #[clap(long, group = "tree_options")]
+    pub pair_index: Option<u64>,
+
+    /// Also build a bloom filter of this many bits over the leaves and commit whether the proven
+    /// leaf passes it, as an advisory pre-check.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..), group = "tree_options")]
+    pub bloom_bits: Option<u64>,
+
+    /// The number of bits the bloom filter sets per leaf.
+    #[clap(
+        long,
+        default_value = "7",
+        requires = "bloom_bits",
+        value_parser = clap::value_parser!(u8).range(1..)
+    )]
+    pub bloom_hashes: u8,
+
+    /// Also prove that the root is one of these checkpointed roots, listed oldest first as one
+    /// 32-byte hex value per line, and commit its checkpoint index.
+    #[clap(long, group = "tree_options")]
+    pub checkpoint_roots: Option<PathBuf>,
+
+    /// Commit that the proven leaf is a `SHA-256(salt || value)` commitment in the commit phase of
+    /// a commit-reveal, without revealing it.
+    #[clap(long, group = "tree_options")]
+    pub commit_phase: bool,
+
+    /// Open a commitment as `SALT:VALUE`, with a 32-byte hex salt and a UTF-8 value: prove the leaf
+    /// `SHA-256(salt || value)`, have the program check the opening and commit the value.
+    #[clap(
+        long,
+        value_parser = parse_reveal,
+        conflicts_with_all = [
+            "commit_phase",
+            "leaf_from_file",
+            "index_file",
+            "global_index",
+            "leaf_transform",
+            "canonicalize",
+        ],
+        group = "tree_options",
+    )]
+    pub reveal: Option<Reveal>,
+
+    /// Also prove every leaf at positions `FIRST:LAST`, inclusive, and commit how many of them
+    /// start with `--range-prefix`.
+    #[clap(long, value_parser = parse_range, group = "tree_options")]
+    pub range_count: Option<RangeInclusive<u64>>,
+
+    /// The hex prefix, of at most 32 bytes, a leaf of `--range-count` must start with to be
+    /// counted; empty counts every leaf.
+    // Fully qualified so that clap parses a single value rather than a list of bytes.
+    #[clap(long, default_value = "", value_parser = parse_prefix, requires = "range_count")]
+    pub range_prefix: ::std::vec::Vec<u8>,
+
+    /// Build an indexed Merkle tree over these values, listed in insertion order as one 32-byte hex
+    /// value per line, instead of the `--total-leaves` tree.
+    #[clap(
+        long,
+        requires = "imt_query",
+        conflicts_with_all = [
+            "leaf_from_file",
+            "leaf_transform",
+            "canonicalize",
+            "sorted_neighbors",
+            "prove_absent",
+            "global_index",
+            "index_file",
+            "reveal",
+        ],
+        group = "dataset",
+        group = "tree_options",
+    )]
+    pub imt_values: Option<PathBuf>,
+
+    /// Prove that this 32-byte hex value is absent from the `--imt-values` tree by proving its low
+    /// leaf.
+    #[clap(long, value_parser = parse_hash, requires = "imt_values")]
+    pub imt_query: Option<[u8; 32]>,
+
+    /// Also verify this many distinct leaves in total, the proven leaf and randomly chosen others,
+    /// with one multi-proof, and make `is_valid` cover all of them. Requires the sha256 scheme.
+    #[clap(
+        long,
+        default_value = "1",
+        value_parser = clap::value_parser!(u64).range(1..=MAX_BATCH_LEAVES as u64),
+        group = "tree_options",
+    )]
+    pub num_leaves: u64,
+
+    /// Also prove this many distinct random leaves, each with its own proof in the tree's scheme,
+    /// and commit how many of them verified and the index of each one that did not.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..), group = "tree_options")]
+    pub batch_size: Option<u64>,
+
+    /// Make the program panic at the first `--batch-size` proof that does not verify.
+    #[clap(long, requires = "batch_size")]
+    pub batch_require_all: bool,
+
+    /// Make the program panic unless the proof is valid, and commit only the root and leaf. Cannot
+    /// be combined with options that commit anything else.
+    #[clap(long)]
+    pub strict: bool,
+
+    /// Commit only the root, leaf and validity, as five BN254 field elements for a Groth16
+    /// verifier. Cannot be combined with options that commit anything else.
+    #[clap(long, conflicts_with = "strict")]
+    pub field_packed: bool,
+
+    /// Commit the public values as `abi.encode(PublicValuesStruct)`, so that a contract decodes
+    /// them with `abi.decode` instead of slicing bytes.
+    #[clap(long, conflicts_with_all = ["strict", "field_packed"])]
+    pub abi_encoded: bool,
+
+    /// Index file mapping keys to leaf positions, one `key,position` entry per line.
+    #[clap(
+        long,
+        requires = "index_key",
+        conflicts_with = "leaf_from_file",
+        group = "tree_options"
+    )]
+    pub index_file: Option<PathBuf>,
+
+    /// Prove the leaf at the position `--index-file` maps this key to, together with the lookup.
+    #[clap(long, requires = "index_file")]
+    pub index_key: Option<String>,
+
+    /// Verify this Merkle-Patricia trie proof (JSON with `root`, `key`, `value` and `proof`)
+    /// instead of building a tree.
+    #[clap(
+        long,
+        group = "source",
+        conflicts_with_all = [
+            "scheme",
+            "hash_bytes",
+            "arity",
+            "hash_personalization",
+            "expect_leaf",
+            "leaf_index",
+            "leaves_file",
+        ]
+    )]
+    pub trie_proof: Option<PathBuf>,
+
+    /// Build a sparse Merkle tree over these entries, one `key value` pair of 32-byte hex values per
+    /// line, and prove the value `--sparse-key` holds, or that it holds none, instead of building a
+    /// tree.
+    #[clap(
+        long,
+        requires = "sparse_key",
+        group = "source",
+        conflicts_with_all = [
+            "scheme",
+            "hash_bytes",
+            "arity",
+            "hash_personalization",
+            "expect_leaf",
+            "leaf_index",
+            "leaves_file",
+        ]
+    )]
+    pub sparse_entries: Option<PathBuf>,
+
+    /// The key `--sparse-entries` proves.
+    #[clap(long, value_parser = parse_hash, requires = "sparse_entries")]
+    pub sparse_key: Option<[u8; 32]>,
+
+    /// Prove that a transaction is in a Bitcoin block with the hex-encoded `merkleblock` message at
+    /// this path, as printed by `bitcoin-cli gettxoutproof`, instead of building a tree.
+    #[clap(
+        long,
+        group = "source",
+        conflicts_with_all = [
+            "scheme",
+            "hash_bytes",
+            "arity",
+            "hash_personalization",
+            "expect_leaf",
+            "leaf_index",
+            "leaves_file",
+        ]
+    )]
+    pub merkleblock: Option<PathBuf>,
+
+    /// The txid `--merkleblock` proves, in the byte order block explorers and `bitcoin-cli` display
+    /// [default: the only transaction the message matches].
+    #[clap(long, value_parser = parse_hash, requires = "merkleblock")]
+    pub txid: Option<[u8; 32]>,
+
+    /// Build a Merkle Mountain Range over the synthetic leaves or `--leaves-file` and prove the leaf
+    /// at `--leaf-index` in it, instead of building a tree.
+    #[clap(
+        long,
+        group = "source",
+        conflicts_with_all = [
+            "scheme",
+            "hash_bytes",
+            "arity",
+            "hash_personalization",
+            "expect_leaf",
+        ]
+    )]
+    pub mmr: bool,
+
+    /// Prove the leaf of this externally generated Merkle proof, in `--proof-format`, instead of
+    /// building a tree.
+    #[clap(long, group = "source", conflicts_with = "leaves_file")]
+    pub proof_input: Option<PathBuf>,
+
+    /// The format of `--proof-input`.
+    #[clap(long, value_enum, default_value = "json", requires = "proof_input")]
+    pub proof_format: ProofFormat,
+
+    /// The root of a `--proof-format rs-merkle` proof.
+    #[clap(long, value_parser = parse_hash, requires = "proof_input")]
+    pub proof_root: Option<[u8; 32]>,
+
+    /// The leaf of a `--proof-format rs-merkle` proof.
+    #[clap(long, value_parser = parse_hash, requires = "proof_input")]
+    pub proof_leaf: Option<[u8; 32]>,
+
+    /// Use these bincode-serialized `SP1Stdin` bytes verbatim, ignoring all tree arguments.
+    #[clap(long)]
+    pub input_file: Option<PathBuf>,
+
+    /// Write the assembled `SP1Stdin` to this path in the format read by `--input-file`.
+    #[clap(long)]
+    pub dump_stdin: Option<PathBuf>,
+}
+
+/// Thread counts for tree building and proving, shared by both binaries.
+#[derive(Args, Debug)]
+pub struct ThreadArgs {
+    /// Threads for both building the tree and proving, unless `--threads-build` or
+    /// `--threads-prove` sets one of them [default: all cores].
+    #[clap(long)]
+    pub threads: Option<usize>,
+
+    /// Threads for hashing the leaves and building the tree [default: `--threads`].
+    #[clap(long)]
+    pub threads_build: Option<usize>,
+
+    /// Threads for the SDK's parallel proving work [default: `--threads`].
+    #[clap(long)]
+    pub threads_prove: Option<usize>,
+}
+
+impl ThreadArgs {
+    /// Sizes rayon's global pool, which the SDK proves on, to `--threads-prove` or `--threads`.
+    ///
+    /// Must run before anything else uses the global pool, i.e. before the prover client is set up.
+    pub fn init_prove_pool(&self) {
+        if let Some(threads) = self.threads_prove.or(self.threads) {
+            ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()
+                .expect("the global thread pool is already initialized");
+        }
+    }
+
+    /// Runs `f` on a dedicated pool of `--threads-build` or `--threads` threads, leaving the global
+    /// pool alone.
+    pub fn build<T: Send>(&self, f: impl FnOnce() -> T + Send) -> T {
+        let mut builder = ThreadPoolBuilder::new();
+        if let Some(threads) = self.threads_build.or(self.threads) {
+            builder = builder.num_threads(threads);
+        }
+        builder
+            .build()
+            .expect("failed to create the tree-building thread pool")
+            .install(f)
+    }
+}
+
+/// The largest shard `--shard-size` accepts, the SDK's own limit for core shards.
+const MAX_SHARD_SIZE: usize = 1 << 21;
+
+/// Shard sizes for local proving, shared by the binaries that prove.
+///
+/// An execution is proven in shards of a fixed number of cycles, a batch of shards at a time, so
+/// these bound the prover's memory regardless of how long the execution runs. The prover network
+/// picks its own.
+#[derive(Args, Debug)]
+pub struct ShardArgs {
+    /// Prove in shards of this many cycles, a power of two of at most 2^21 [default: the SDK's,
+    /// from `SHARD_SIZE` or the available memory].
+    #[clap(long, value_parser = parse_shard_size)]
+    pub shard_size: Option<usize>,
+
+    /// Prove this many shards at a time [default: the SDK's, from `SHARD_BATCH_SIZE` or the
+    /// available memory].
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub shard_batch_size: Option<u64>,
+}
+
+impl ShardArgs {
+    /// Applies `--shard-size` and `--shard-batch-size` to a proof request.
+    pub fn apply<'a>(&self, mut prove: Prove<'a>) -> Prove<'a> {
+        if let Some(size) = self.shard_size {
+            prove = prove.shard_size(size);
+        }
+        if let Some(size) = self.shard_batch_size {
+            prove = prove.shard_batch_size(size as usize);
+        }
+        prove
+    }
+}
+
+/// Parses a `--shard-size`, which the prover requires to be a power of two.
+fn parse_shard_size(value: &str) -> Result<usize, String> {
+    let size: usize = value.parse().map_err(|e| format!("{}", e))?;
+    if !size.is_power_of_two() || size > MAX_SHARD_SIZE {
+        return Err(format!(
+            "expected a power of two of at most {}",
+            MAX_SHARD_SIZE
+        ));
+    }
+    Ok(size)
+}
+
+impl TreeArgs {
+    /// The scheme the guest verifies against.
+    pub fn scheme(&self) -> Result<Scheme, String> {
+        if self.trie_proof.is_some() {
+            return Ok(Scheme::Patricia);
+        }
+        if self.sparse_entries.is_some() {
+            return Ok(Scheme::Sparse);
+        }
+        if self.merkleblock.is_some() {
+            return Ok(Scheme::Bitcoin);
+        }
+        if self.mmr {
+            return Ok(Scheme::Mmr);
+        }
+        if let Some(personalization) = &self.hash_personalization {
+            if self.scheme != TreeScheme::Blake2b {
+                return Err(
+                    "--hash-personalization is only supported with the blake2b scheme".to_string(),
+                );
+            }
+            if !blake2b::is_valid_personalization(personalization.as_bytes()) {
+                return Err(format!(
+                    "--hash-personalization must be at most {} bytes, got {}",
+                    blake2b::MAX_PERSONALIZATION_LEN,
+                    personalization.len()
+                ));
+            }
+        }
+        match (self.scheme, self.hash_bytes, self.arity) {
+            (TreeScheme::Sha256, None, None) => Ok(Scheme::Sha256),
+            (TreeScheme::Sha256, Some(_), None) => Ok(Scheme::TruncatedSha256),
+            (TreeScheme::Sha256, None, Some(_)) => Ok(Scheme::Kary),
+            (TreeScheme::Hybrid, None, None) => Ok(Scheme::Hybrid),
+            (TreeScheme::Keccak256, None, None) => Ok(Scheme::Keccak256),
+            (TreeScheme::Blake2b, None, None) => Ok(Scheme::Blake2b),
+            (TreeScheme::Blake3, None, None) => Ok(Scheme::Blake3),
+            (TreeScheme::Sha256, Some(_), Some(_)) => {
+                Err("--hash-bytes and --arity cannot be combined".to_string())
+            }
+            (
+                TreeScheme::Hybrid
+                | TreeScheme::Keccak256
+                | TreeScheme::Blake2b
+                | TreeScheme::Blake3,
+                _,
+                _,
+            ) => Err(
+                "--hash-bytes and --arity are only supported with the sha256 scheme".to_string(),
+            ),
+        }
+    }
+
+    /// Returns whether an option reads leaves other than the proven one, so the tree cannot be
+    /// streamed.
+    fn needs_all_leaves(&self) -> bool {
+        self.sorted_neighbors
+            || self.prove_absent
+            || self.bloom_bits.is_some()
+            || self.kzg_opening
+            || self.num_leaves > 1
+            || self.batch_size.is_some()
+            || !self.appends.is_empty()
+            || self.range_count.is_some()
+            || self.pair_index.is_some()
+            || self.leaf_from_file.is_some()
+            || self.reveal.is_some()
+    }
+
+    /// Returns leaf data canonicalized with `--canonicalize`, or unchanged without it.
+    pub fn canonical_data(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        match self.canonicalize {
+            Some(mode) => mode.apply(data),
+            None => Ok(data.to_vec()),
+        }
+    }
+
+    /// The `--hash-personalization` bytes, empty when unset.
+    pub fn personalization(&self) -> &[u8] {
+        self.hash_personalization
+            .as_deref()
+            .map_or(&[], str::as_bytes)
+    }
+
+    /// Hashes leaf data, canonicalized with `--canonicalize`, into a leaf of the selected scheme:
+    /// with the `--leaf-transform` chain if set, or Keccak-256, BLAKE2b, BLAKE3 or SHA-256
+    /// otherwise.
+    pub fn hash_leaf(&self, data: &[u8]) -> Result<[u8; 32], String> {
+        let data = self.canonical_data(data)?;
+        let leaf = match (&self.leaf_transform, self.scheme) {
+            (Some(transform), _) => transform.apply(&data),
+            (None, TreeScheme::Keccak256) => keccak::hash(&data),
+            (None, TreeScheme::Blake2b) => blake2b::hash(&data, self.personalization()),
+            (None, TreeScheme::Blake3) => blake3::hash(&data),
+            (None, _) => Sha256::digest(&data).into(),
+        };
+        Ok(match self.hash_bytes {
+            Some(n) => truncated::truncate(&leaf, n),
+            None => leaf,
+        })
+    }
+}
+
+/// Loads the guest inputs from `--input-file`, or builds them from the tree arguments, and writes
+/// them to `--dump-stdin` when requested.
+pub fn setup_inputs(args: &TreeArgs) -> Result<SP1Stdin, String> {
+    let stdin = match &args.input_file {
+        Some(path) => {
+            let bytes = std::fs::read(path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+            let stdin = bincode::deserialize(&bytes)
+                .map_err(|e| format!("{} is not a serialized SP1Stdin: {}", path.display(), e))?;
+            check_header(&stdin, format::SUPPORTED_VERSIONS)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            stdin
+        }
+        None => match (&args.trie_proof, &args.sparse_entries, &args.merkleblock) {
+            (Some(path), _, _) => build_trie_inputs(args, path)?,
+            (None, Some(path), _) => build_sparse_inputs(args, path)?,
+            (None, None, Some(path)) => build_bitcoin_inputs(args, path)?,
+            (None, None, None) => match &args.proof_input {
+                Some(path) => build_proof_inputs(args, path)?,
+                None if args.mmr => build_mmr_inputs(args)?,
+                None => build_inputs(args)?,
+            },
+        },
+    };
+
+    if let Some(path) = &args.dump_stdin {
+        let bytes = bincode::serialize(&stdin).expect("failed to serialize stdin");
+        std::fs::write(path, bytes)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        println!("Wrote stdin to {}", path.display());
+    }
+
+    Ok(stdin)
+}
+
+/// Writes the [`format`] header of the current [`format::FORMAT_VERSION`], which must open every
+/// input before the scheme byte.
+pub fn write_header(stdin: &mut SP1Stdin) {
+    stdin.write_slice(&format::header(format::FORMAT_VERSION));
+}
+
+/// Checks the header of an assembled input as the program does, returning its version.
+pub fn check_header(stdin: &SP1Stdin, supported: &[u8]) -> Result<u8, HeaderError> {
+    format::check_header(
+        stdin.buffer.first().map_or(&[][..], Vec::as_slice),
+        supported,
+    )
+}
+
+/// Returns a copy of an assembled input that verifies sha256 proofs with software SHA-256, by
+/// setting [`Options::software_sha256`] in its last item.
+pub fn with_software_sha256(stdin: &SP1Stdin) -> Result<SP1Stdin, String> {
+    let mut stdin = stdin.clone();
+    let last = stdin
+        .buffer
+        .last_mut()
+        .ok_or_else(|| "the input is empty".to_string())?;
+    let mut options: Options = bincode::deserialize(last)
+        .map_err(|e| format!("the input does not end with options: {}", e))?;
+    options.software_sha256 = true;
+    *last = bincode::serialize(&options).expect("failed to serialize options");
+    Ok(stdin)
+}
+
+/// Splits an assembled input whose options aggregate proofs into one input per `chunk_size` of the
+/// aggregated entries, in order, by replacing [`Options::aggregate`] in its last item.
+///
+/// Every chunk keeps the rest of the input, so each proves the same claim and commits the aggregate
+/// record of its own entries, and the chunks' records together cover the whole batch.
+pub fn split_aggregate(stdin: &SP1Stdin, chunk_size: usize) -> Result<Vec<SP1Stdin>, String> {
+    let (last, items) = stdin
+        .buffer
+        .split_last()
+        .ok_or_else(|| "the input is empty".to_string())?;
+    let mut options: Options = bincode::deserialize(last)
+        .map_err(|e| format!("the input does not end with options: {}", e))?;
+    // Taken out so that the options cloned into each chunk carry no entries yet.
+    let aggregate = options
+        .aggregate
+        .take()
+        .ok_or_else(|| "the input does not aggregate proofs".to_string())?;
+
+    Ok(aggregate
+        .entries
+        .chunks(chunk_size)
+        .map(|entries| {
+            let options = Options {
+                aggregate: Some(AggregateProofs {
+                    entries: entries.to_vec(),
+                    require_all: aggregate.require_all,
+                }),
+                ..options.clone()
+            };
+            let mut chunk = SP1Stdin::new();
+            for item in items {
+                chunk.write_slice(item);
+            }
+            chunk.write(&options);
+            chunk
+        })
+        .collect())
+}
+
+/// The largest tree `--kzg-opening` interpolates over; interpolation is quadratic in the leaf count.
+const KZG_MAX_LEAVES: usize = 4096;
+
+/// The most leaves `--num-leaves` can batch, since the batch record holds 40 bytes per leaf.
+const MAX_BATCH_LEAVES: usize = u16::MAX as usize / 40;
+
+/// Subtrees at least this many levels high are hashed on two threads by [`root_and_proof`].
+const PARALLEL_SUBTREE_HEIGHT: usize = 12;
+
+/// The parent function of a binary scheme, shared by the threads of [`root_and_proof`].
+pub type ParentFn<'a> = &'a (dyn Fn(&[u8; 32], &[u8; 32]) -> [u8; 32] + Sync);
+
+/// Produces the leaf at an index of a streamed tree.
+pub type LeafFn<'a> = &'a (dyn Fn(usize) -> Result<[u8; 32], String> + Sync);
+
+/// The root of a streamed tree and one leaf with its sibling path, see [`root_and_proof`].
+pub struct StreamedProof {
+    pub root: [u8; 32],
+    pub leaf: [u8; 32],
+    pub path: Vec<[u8; 32]>,
+}
+
+/// Builds the inputs for one leaf of a dataset or synthetic tree of any scheme but the trie,
+/// sparse, Bitcoin and MMR ones.
+///
+/// The tree is the `--leaves-*`, `--erasure-data` or `--imt-values` dataset, or else the
+/// synthetic tree over `sha256(i)` for `0..total_leaves`, sorted with `--sorted-neighbors`. The
+/// proven leaf is fixed by the forest, the IMT query, `--index-key`, `--leaf-from-file`,
+/// `--reveal` or `--leaf-index`, in that order, and is random otherwise.
+///
+/// Synthetic binary trees are streamed with [`root_and_proof`] unless an option needs every leaf,
+/// so the leaves and levels of a large tree are never held in memory.
+fn build_inputs(args: &TreeArgs) -> Result<SP1Stdin, String> {
+    let scheme = args.scheme()?;
+    check_scheme_options(args, scheme)?;
+    let forest = match args.global_index {
+        Some(global_index) => Some(locate_in_forest(&args.forest_sizes, global_index)?),
+        None => None,
+    };
+    let leaf_file = match &args.leaf_from_file {
+        Some(path) => Some(
+            std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?,
+        ),
+        None => None,
+    };
+    let coding = args.data_chunks.map(|data_chunks| Coding {
+        data_chunks,
+        total_chunks: args
+            .total_chunks
+            .unwrap_or((2 * data_chunks).min(erasure::MAX_CHUNKS as u16)),
+    });
+    let imt = match (&args.imt_values, &args.imt_query) {
+        (Some(path), Some(value)) => Some(load_imt(args, path, value)?),
+        _ => None,
+    };
+    let (dataset, imt) = match imt {
+        Some(imt) => (Some(imt.dataset), Some((imt.query, imt.low_index))),
+        None => (load_dataset(args, coding)?, None),
+    };
+    let (offset, total_leaves) = match (&forest, &dataset) {
+        (Some(located), _) => (located.offset, located.size),
+        (None, Some(dataset)) => (0, dataset.leaves.len()),
+        (None, None) => (0, args.total_leaves),
+    };
+    println!("Total Leaves: {}", total_leaves);
+    check_tree_size(args, scheme, total_leaves, dataset.is_some())?;
+    let count_leaf = args.count_leaf.then(|| {
+        let count_leaf = count::leaf(total_leaves as u64);
+        println!("Count Leaf: 0x{}", hex::encode(count_leaf));
+        match args.hash_bytes {
+            Some(n) => truncated::truncate(&count_leaf, n),
+            None => count_leaf,
+        }
+    });
+    let synthetic_leaf = |i: usize| match count_leaf {
+        Some(count_leaf) if i as u64 == count::INDEX => Ok(count_leaf),
+        _ => args.hash_leaf(&(offset + i).to_le_bytes()),
+    };
+    let hash_bytes = args.hash_bytes.unwrap_or(32);
+    let truncated_parent =
+        |left: &[u8; 32], right: &[u8; 32]| truncated::parent(left, right, hash_bytes);
+    let blake2b_parent =
+        |left: &[u8; 32], right: &[u8; 32]| blake2b::parent(left, right, args.personalization());
+    let stream_parent: Option<ParentFn> = match scheme {
+        _ if dataset.is_some() || args.needs_all_leaves() => None,
+        Scheme::Sha256 => Some(&tree::sha256_parent),
+        Scheme::Keccak256 => Some(&keccak::parent),
+        Scheme::Hybrid => Some(&hybrid::parent),
+        Scheme::TruncatedSha256 => Some(&truncated_parent),
+        Scheme::Blake2b => Some(&blake2b_parent),
+        Scheme::Blake3 => Some(&blake3::parent),
+        Scheme::Kary | Scheme::Patricia | Scheme::Sparse | Scheme::Bitcoin | Scheme::Mmr => None,
+    };
+    if args.tree_path.is_some() && stream_parent.is_none() {
+        return Err(
+            "--tree-path only stores synthetic binary trees, without options that need \
+                    every leaf"
+                .to_string(),
+        );
+    }
+    let (mut leaves, items) = match dataset {
+        Some(dataset) => (dataset.leaves, Some(dataset.items)),
+        None if stream_parent.is_some() => (Vec::new(), None),
+        None => (0..total_leaves)
+            .into_par_iter()
+            .map(synthetic_leaf)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|leaves| (leaves, None))?,
+    };
+    if args.sorted_neighbors || args.prove_absent {
+        leaves.par_sort_unstable();
+    }
+
+    let index_lookup = match (&args.index_file, &args.index_key) {
+        (Some(path), Some(key)) => Some(build_index_lookup(path, key)?),
+        _ => None,
+    };
+
+    // The forest mapping and the IMT low leaf each fix the proven position.
+    let fixed_index = match (&forest, &imt) {
+        (Some(located), _) => Some(located.local_index),
+        (None, Some((_, low_index))) => Some(*low_index),
+        (None, None) => None,
+    };
+    let leaf_index = select_leaf(
+        args,
+        &leaves,
+        total_leaves,
+        fixed_index,
+        leaf_file.as_deref(),
+        index_lookup.as_ref(),
+    )?;
+    if args.count_leaf && leaf_index as u64 == count::INDEX {
+        return Err("leaf 0 of a counted tree is the count leaf, not a data leaf".to_string());
+    }
+    let leaf = match stream_parent {
+        Some(_) => synthetic_leaf(leaf_index)?,
+        None => leaves[leaf_index],
+    };
+
+    let positions = select_positions(args, &leaves, leaf_index, total_leaves)?;
+    let TreeProofs {
+        root,
+        proofs,
+        batch,
+    } = prove_positions(
+        args,
+        scheme,
+        &leaves,
+        total_leaves,
+        &positions,
+        &synthetic_leaf,
+        stream_parent,
+    )?;
+
+    let mut stdin = SP1Stdin::new();
+    write_header(&mut stdin);
+    stdin.write(&(scheme as u8));
+    if let Some(n) = args.hash_bytes {
+        stdin.write(&n);
+    }
+    if let Some(arity) = args.arity {
+        stdin.write(&arity);
+    }
+    if scheme == Scheme::Blake2b {
+        stdin.write(&args.personalization().to_vec());
+    }
+    stdin.write(&root);
+    stdin.write(&leaf);
+    stdin.write(&proofs[0]);
+    stdin.write(&leaf_index);
+    stdin.write(&total_leaves);
+    let tree = ProvenTree {
+        leaves,
+        items,
+        offset,
+        leaf_index,
+        leaf,
+        root,
+        positions,
+        proofs,
+        batch,
+    };
+    let options = build_options(
+        args,
+        scheme,
+        tree,
+        leaf_file,
+        index_lookup,
+        imt.map(|(query, _)| query),
+        coding,
+    )?;
+    check_core_only(&options)?;
+    check_pack_flags(&options)?;
+    stdin.write(&options);
+    Ok(stdin)
+}
+
+/// Rejects the options [`build_inputs`] does not support with `scheme`, before any leaf is loaded.
+fn check_scheme_options(args: &TreeArgs, scheme: Scheme) -> Result<(), String> {
+    if !args.updates.is_empty() && !matches!(scheme, Scheme::Sha256 | Scheme::Hybrid) {
+        return Err("--update is only supported with the sha256 and hybrid schemes".to_string());
+    }
+    if !args.appends.is_empty()
+        && !matches!(scheme, Scheme::Sha256 | Scheme::Hybrid | Scheme::Keccak256)
+    {
+        return Err(
+            "--append is only supported with the sha256, hybrid and keccak256 schemes".to_string(),
+        );
+    }
+    if args.commit_promotions && scheme == Scheme::Kary {
+        return Err("--commit-promotions is only supported with binary trees".to_string());
+    }
+    if args.num_leaves > 1 && scheme != Scheme::Sha256 {
+        return Err("--num-leaves is only supported with the sha256 scheme".to_string());
+    }
+    // The batch record commits every batched leaf, which would publish the hidden one.
+    if args.num_leaves > 1 && args.commit_leaf_hiding {
+        return Err("--num-leaves cannot be combined with --commit-leaf-hiding".to_string());
+    }
+    if args.commit_proof_stats && scheme != Scheme::Sha256 {
+        return Err("--commit-proof-stats is only supported with the sha256 scheme".to_string());
+    }
+    Ok(())
+}
+
+/// Rejects the options that do not fit a tree of `total_leaves` leaves, loaded from a dataset if
+/// `from_dataset` is set.
+fn check_tree_size(
+    args: &TreeArgs,
+    scheme: Scheme,
+    total_leaves: usize,
+    from_dataset: bool,
+) -> Result<(), String> {
+    if args.count_leaf && from_dataset {
+        return Err("--count-leaf is only supported with generated trees".to_string());
+    }
+    if args.count_leaf && total_leaves < 2 {
+        return Err("--count-leaf needs a tree of at least two leaves".to_string());
+    }
+    if let Some(cap_level) = args.cap_level {
+        if !matches!(scheme, Scheme::Sha256 | Scheme::Hybrid) {
+            return Err(
+                "--cap-level is only supported with the sha256 and hybrid schemes".to_string(),
+            );
+        }
+        let height = tree::height(total_leaves);
+        if cap_level as usize > height {
+            return Err(format!(
+                "--cap-level {} exceeds the tree height of {}",
+                cap_level, height
+            ));
+        }
+    }
+    if args.kzg_opening && total_leaves > KZG_MAX_LEAVES {
+        return Err(format!(
+            "--kzg-opening interpolates over every leaf and supports at most {} leaves",
+            KZG_MAX_LEAVES
+        ));
+    }
+    Ok(())
+}
+
+/// Loads the `--leaves-proto`, `--erasure-data`, `--leaves-cbor` or `--leaves-file` dataset, with
+/// that precedence, or returns `None` for a synthetic tree.
+fn load_dataset(args: &TreeArgs, coding: Option<Coding>) -> Result<Option<Dataset>, String> {
+    let dataset = match &args.leaves_file {
+        Some(path) => Some(load_leaves_file(path)?),
+        None => None,
+    };
+    #[cfg(feature = "cbor")]
+    let dataset = match &args.leaves_cbor {
+        Some(path) => Some(load_cbor_leaves(args, path)?),
+        None => dataset,
+    };
+    let dataset = match (&args.erasure_data, coding) {
+        (Some(path), Some(coding)) => Some(load_erasure_chunks(args, path, coding)?),
+        _ => dataset,
+    };
+    #[cfg(feature = "proto")]
+    let dataset = match &args.leaves_proto {
+        Some(path) => Some(load_proto_leaves(args, path)?),
+        None => dataset,
+    };
+    Ok(dataset)
+}
+
+/// Finds the index of the proven leaf: `fixed_index`, the position `index_lookup` maps its key to,
+/// the leaf hashed from `leaf_file`, the `--reveal` commitment or `--leaf-index`, in that order, or
+/// a random data leaf.
+///
+/// `leaves` is only searched for `leaf_file` and `--reveal`, which need every leaf.
+fn select_leaf(
+    args: &TreeArgs,
+    leaves: &[[u8; 32]],
+    total_leaves: usize,
+    fixed_index: Option<usize>,
+    leaf_file: Option<&[u8]>,
+    index_lookup: Option<&IndexLookup>,
+) -> Result<usize, String> {
+    Ok(
+        match (
+            fixed_index,
+            args.leaf_from_file.as_ref().zip(leaf_file),
+            index_lookup,
+            &args.reveal,
+        ) {
+            (Some(index), _, _, _) => index,
+            (None, _, Some(lookup), _) => usize::try_from(lookup.position)
+                .ok()
+                .filter(|&position| position < total_leaves)
+                .ok_or_else(|| {
+                    format!(
+                        "the index maps the key to position {}, outside the tree",
+                        lookup.position
+                    )
+                })?,
+            (None, Some((path, data)), None, _) => {
+                let leaf = args.hash_leaf(data)?;
+                println!("Leaf File Hash: 0x{}", hex::encode(leaf));
+                let index = leaves
+                    .par_iter()
+                    .position_first(|l| *l == leaf)
+                    .ok_or_else(|| {
+                        format!("the hash of {} is not a leaf of the tree", path.display())
+                    })?;
+                println!("Leaf File Index: {}", index);
+                index
+            }
+            (None, None, None, Some(reveal)) => {
+                let commitment = reveal.commitment();
+                let leaf = match args.hash_bytes {
+                    Some(n) => truncated::truncate(&commitment, n),
+                    None => commitment,
+                };
+                println!("Reveal Commitment: 0x{}", hex::encode(commitment));
+                let index = leaves
+                    .par_iter()
+                    .position_first(|l| *l == leaf)
+                    .ok_or_else(|| {
+                        "the revealed commitment is not a leaf of the tree".to_string()
+                    })?;
+                println!("Reveal Index: {}", index);
+                index
+            }
+            (None, None, None, None) => match args.leaf_index {
+                Some(index) => usize::try_from(index)
+                    .ok()
+                    .filter(|&index| index < total_leaves)
+                    .ok_or_else(|| {
+                        format!(
+                            "--leaf-index {} is outside the tree of {} leaves",
+                            index, total_leaves
+                        )
+                    })?,
+                None => rand::thread_rng().gen_range(args.count_leaf as usize..total_leaves),
+            },
+        },
+    )
+}
+
+/// The positions of a tree that [`build_inputs`] needs proofs of, see [`select_positions`].
+struct Positions {
+    /// Every position to prove: the proven leaf first, followed by its neighbors, paired leaf,
+    /// aggregated leaves, absence bracket, count leaf and counted range when requested.
+    indices: Vec<usize>,
+    /// The `--num-leaves` batch, ascending and including the proven leaf.
+    batch: Option<Vec<usize>>,
+    pair: Option<usize>,
+    aggregate: Vec<usize>,
+    /// The `--prove-absent` target and the number of leaves below it.
+    absence: Option<([u8; 32], usize)>,
+    range: Option<RangeInclusive<usize>>,
+    /// Where the paired leaf, aggregated leaves, absence bracket, count leaf and counted range
+    /// start in `indices`.
+    pair_position: usize,
+    aggregate_position: usize,
+    absence_position: usize,
+    count_position: usize,
+    range_position: usize,
+}
+
+/// Selects the positions the options need proofs of besides the leaf at `leaf_index`, sampling
+/// the `--num-leaves` batch and the `--batch-size` aggregate at random.
+fn select_positions(
+    args: &TreeArgs,
+    leaves: &[[u8; 32]],
+    leaf_index: usize,
+    total_leaves: usize,
+) -> Result<Positions, String> {
+    let batch = match args.num_leaves as usize {
+        1 => None,
+        k if k > total_leaves => {
+            return Err(format!(
+                "--num-leaves {} exceeds the {} leaves of the tree",
+                k, total_leaves
+            ))
+        }
+        k => {
+            // Sample the other leaves among every position but the proven one.
+            let mut batch: Vec<usize> =
+                rand::seq::index::sample(&mut rand::thread_rng(), total_leaves - 1, k - 1)
+                    .into_iter()
+                    .map(|i| if i >= leaf_index { i + 1 } else { i })
+                    .collect();
+            batch.push(leaf_index);
+            // rs_merkle only verifies multi-proofs built for ascending, distinct indices.
+            batch.sort_unstable();
+            batch.dedup();
+            println!("Batch Leaves: {}", batch.len());
+            Some(batch)
+        }
+    };
+
+    let aggregate = match args.batch_size {
+        Some(n) if n > total_leaves as u64 => {
+            return Err(format!(
+                "--batch-size {} exceeds the {} leaves of the tree",
+                n, total_leaves
+            ))
+        }
+        Some(n) => {
+            let mut indices =
+                rand::seq::index::sample(&mut rand::thread_rng(), total_leaves, n as usize)
+                    .into_vec();
+            indices.sort_unstable();
+            println!("Aggregated Leaves: {}", indices.len());
+            indices
+        }
+        None => Vec::new(),
+    };
+
+    let pair = match args.pair_index {
+        Some(index) => Some(
+            usize::try_from(index)
+                .ok()
+                .filter(|&index| index < total_leaves)
+                .ok_or_else(|| format!("--pair-index {} is outside the tree", index))?,
+        ),
+        None => None,
+    };
+
+    // The bracketing leaves are at `position - 1` and `position`, where `position` is the number of
+    // leaves below the target.
+    let absence = if args.prove_absent {
+        let target = match args.absent_target {
+            Some(target) => target,
+            None => loop {
+                let target: [u8; 32] = rand::thread_rng().gen();
+                if leaves.binary_search(&target).is_err() {
+                    break target;
+                }
+            },
+        };
+        let position = leaves.binary_search(&target).err().ok_or_else(|| {
+            format!(
+                "--absent-target 0x{} is a leaf of the tree",
+                hex::encode(target)
+            )
+        })?;
+        Some((target, position))
+    } else {
+        None
+    };
+
+    let range = match &args.range_count {
+        Some(range) => Some(
+            usize::try_from(*range.start())
+                .ok()
+                .zip(usize::try_from(*range.end()).ok())
+                .filter(|&(_, last)| last < total_leaves)
+                .map(|(first, last)| first..=last)
+                .ok_or_else(|| {
+                    format!(
+                        "--range-count {}:{} is outside the tree",
+                        range.start(),
+                        range.end()
+                    )
+                })?,
+        ),
+        None => None,
+    };
+
+    let mut indices = vec![leaf_index];
+    if args.sorted_neighbors {
+        indices.extend(leaf_index.checked_sub(1));
+        indices.extend(Some(leaf_index + 1).filter(|&next| next < total_leaves));
+    }
+    let pair_position = indices.len();
+    indices.extend(pair);
+    let aggregate_position = indices.len();
+    indices.extend(&aggregate);
+    let absence_position = indices.len();
+    if let Some((_, position)) = absence {
+        indices.extend(position.checked_sub(1));
+        indices.extend(Some(position).filter(|&high| high < total_leaves));
+    }
+    let count_position = indices.len();
+    indices.extend(args.count_leaf.then_some(count::INDEX as usize));
+    let range_position = indices.len();
+    indices.extend(range.clone().into_iter().flatten());
+    Ok(Positions {
+        indices,
+        batch,
+        pair,
+        aggregate,
+        absence,
+        range,
+        pair_position,
+        aggregate_position,
+        absence_position,
+        count_position,
+        range_position,
+    })
+}
+
+/// The root of a tree and the proofs of its [`Positions`].
+struct TreeProofs {
+    root: [u8; 32],
+    /// The proof of each of `Positions::indices`, in order.
+    proofs: Vec<Vec<u8>>,
+    /// The `--num-leaves` batch with its multi-proof.
+    batch: Option<LeafBatch>,
+}
+
+/// Computes the root of the tree and the proof of each of `positions.indices`, with the
+/// multi-proof of the `--num-leaves` batch for sha256 trees.
+///
+/// With a `parent`, the synthetic tree over `leaf` is streamed, or read from `--tree-path`;
+/// otherwise the tree is built over `leaves`.
+fn prove_positions(
+    args: &TreeArgs,
+    scheme: Scheme,
+    leaves: &[[u8; 32]],
+    total_leaves: usize,
+    positions: &Positions,
+    leaf: LeafFn,
+    parent: Option<ParentFn>,
+) -> Result<TreeProofs, String> {
+    let indices = &positions.indices;
+    let hash_bytes = args.hash_bytes.unwrap_or(32);
+    let mut batch = None;
+    let (root, proofs) = match (parent, scheme) {
+        (Some(parent), _) => {
+            let mut store = match &args.tree_path {
+                Some(path) => {
+                    println!("Tree Store: {}", path.display());
+                    let header = StoreHeader {
+                        scheme: scheme as u8,
+                        hash_bytes,
+                        total_leaves: total_leaves as u64,
+                    };
+                    Some(TreeStore::open_or_build(path, header, leaf, parent)?)
+                }
+                None => None,
+            };
+            let mut root = [0; 32];
+            let mut proofs = Vec::with_capacity(indices.len());
+            for &index in indices {
+                let streamed = match &mut store {
+                    Some(store) => {
+                        let streamed = store.root_and_proof(index)?;
+                        if streamed.leaf != leaf(index)? {
+                            return Err(format!(
+                                "leaf {} of the tree store is not the leaf these arguments \
+                                 derive; delete the store to rebuild it",
+                                index
+                            ));
+                        }
+                        streamed
+                    }
+                    None => root_and_proof(total_leaves, index, leaf, parent)?,
+                };
+                // Truncated siblings are packed as their meaningful bytes.
+                proofs.push(
+                    streamed
+                        .path
+                        .iter()
+                        .flat_map(|node| node[32 - hash_bytes as usize..].to_vec())
+                        .collect(),
+                );
+                root = streamed.root;
+            }
+            (root, proofs)
+        }
+        (None, Scheme::Sha256) => {
+            let tree = LevelTree::<Sha256Hasher>::from_leaves(leaves);
+            let root = tree.root().expect("Failed to get root");
+            batch = positions.batch.as_ref().map(|indices| LeafBatch {
+                leaves: indices.iter().map(|&i| leaves[i]).collect(),
+                proof_bytes: tree.proof(indices).to_bytes(),
+                indices: indices.iter().map(|&i| i as u64).collect(),
+            });
+            (
+                root,
+                indices
+                    .iter()
+                    .map(|&i| tree.proof(&[i]).to_bytes())
+                    .collect(),
+            )
+        }
+        (None, Scheme::Keccak256) => {
+            let tree = LevelTree::<Keccak256Hasher>::from_leaves(leaves);
+            let root = tree.root().expect("Failed to get root");
+            (
+                root,
+                indices
+                    .iter()
+                    .map(|&i| tree.proof(&[i]).to_bytes())
+                    .collect(),
+            )
+        }
+        (None, Scheme::Hybrid) => {
+            let tree = HybridTree::from_leaves(leaves);
+            let root = tree.root().expect("Failed to get root");
+            (root, indices.iter().map(|&i| tree.proof_bytes(i)).collect())
+        }
+        (None, Scheme::TruncatedSha256) => {
+            let tree = TruncatedTree::from_leaves(leaves, args.hash_bytes.unwrap());
+            let root = tree.root().expect("Failed to get root");
+            (root, indices.iter().map(|&i| tree.proof_bytes(i)).collect())
+        }
+        (None, Scheme::Kary) => {
+            let tree = KaryTree::from_leaves(leaves, args.arity.unwrap());
+            let root = tree.root().expect("Failed to get root");
+            (root, indices.iter().map(|&i| tree.proof_bytes(i)).collect())
+        }
+        (None, Scheme::Blake2b) => {
+            let tree = Blake2bTree::from_leaves(leaves, args.personalization());
+            let root = tree.root().expect("Failed to get root");
+            (root, indices.iter().map(|&i| tree.proof_bytes(i)).collect())
+        }
+        (None, Scheme::Blake3) => {
+            let tree = Blake3Tree::from_leaves(leaves);
+            let root = tree.root().expect("Failed to get root");
+            (root, indices.iter().map(|&i| tree.proof_bytes(i)).collect())
+        }
+        (None, Scheme::Patricia) => unreachable!("trie proofs are built by build_trie_inputs"),
+        (None, Scheme::Sparse) => unreachable!("sparse proofs are built by build_sparse_inputs"),
+        (None, Scheme::Bitcoin) => unreachable!("bitcoin proofs are built by build_bitcoin_inputs"),
+        (None, Scheme::Mmr) => unreachable!("mmr proofs are built by build_mmr_inputs"),
+    };
+    Ok(TreeProofs {
+        root,
+        proofs,
+        batch,
+    })
+}
+
+/// The tree [`build_inputs`] proves a leaf of, with the proofs of its [`Positions`].
+struct ProvenTree {
+    /// Every leaf, or none for a streamed tree.
+    leaves: Vec<[u8; 32]>,
+    /// The items of a dataset's leaves.
+    items: Option<Vec<Vec<u8>>>,
+    /// The global index of the tree's first leaf in its forest, or zero.
+    offset: usize,
+    leaf_index: usize,
+    leaf: [u8; 32],
+    root: [u8; 32],
+    positions: Positions,
+    /// The proof of each of `positions.indices`, in order.
+    proofs: Vec<Vec<u8>>,
+    batch: Option<LeafBatch>,
+}
+
+/// Assembles the options of the proven leaf from the tree's proofs and the remaining tree
+/// arguments, signing, evaluating and loading what each option needs.
+fn build_options(
+    args: &TreeArgs,
+    scheme: Scheme,
+    tree: ProvenTree,
+    leaf_file: Option<Vec<u8>>,
+    index_lookup: Option<IndexLookup>,
+    imt: Option<ImtQuery>,
+    coding: Option<Coding>,
+) -> Result<Options, String> {
+    let ProvenTree {
+        leaves,
+        items,
+        offset,
+        leaf_index,
+        leaf,
+        root,
+        positions,
+        proofs,
+        batch,
+    } = tree;
+    let indices = &positions.indices;
+
+    let neighbors = args.sorted_neighbors.then(|| {
+        let neighbor = |index: usize| {
+            let position = indices.iter().position(|&i| i == index)?;
+            Some(Neighbor {
+                leaf: leaves[index],
+                proof_bytes: proofs[position].clone(),
+            })
+        };
+        Neighbors {
+            prev: leaf_index.checked_sub(1).and_then(neighbor),
+            next: neighbor(leaf_index + 1),
+        }
+    });
+
+    let pair = positions.pair.map(|index| PairedLeaf {
+        index: index as u64,
+        leaf: leaves[index],
+        proof_bytes: proofs[positions.pair_position].clone(),
+    });
+
+    let aggregate = args.batch_size.map(|_| AggregateProofs {
+        entries: positions
+            .aggregate
+            .iter()
+            .zip(&proofs[positions.aggregate_position..])
+            .map(|(&index, proof_bytes)| AggregateEntry {
+                index: index as u64,
+                leaf: leaves[index],
+                proof_bytes: proof_bytes.clone(),
+            })
+            .collect(),
+        require_all: args.batch_require_all,
+    });
+
+    let absence = positions.absence.map(|(target, position)| {
+        let bracket = |index: usize| {
+            let offset = indices[positions.absence_position..]
+                .iter()
+                .position(|&i| i == index)?;
+            Some(PairedLeaf {
+                index: index as u64,
+                leaf: leaves[index],
+                proof_bytes: proofs[positions.absence_position + offset].clone(),
+            })
+        };
+        Absence {
+            target,
+            low: position.checked_sub(1).and_then(bracket),
+            high: bracket(position),
+        }
+    });
+
+    let append = (!args.appends.is_empty()).then(|| {
+        let parent = match scheme {
+            Scheme::Hybrid => hybrid::parent,
+            Scheme::Keccak256 => keccak::parent,
+            _ => tree::sha256_parent,
+        };
+        Append {
+            frontier: append::frontier(&leaves, parent),
+            leaves: args.appends.clone(),
+        }
+    });
+
+    let erasure = coding
+        .zip(items.as_ref())
+        .map(|(coding, chunks)| ChunkSample {
+            coding,
+            chunk: chunks[leaf_index].clone(),
+        });
+
+    let leaf_count = args.count_leaf.then(|| CountProof {
+        proof_bytes: proofs[positions.count_position].clone(),
+    });
+
+    let range_count = positions.range.map(|range| RangeCount {
+        first: *range.start() as u64,
+        leaves: leaves[range].to_vec(),
+        proofs: proofs[positions.range_position..].to_vec(),
+        prefix: args.range_prefix.clone(),
+    });
+
+    let leaf_data = || {
+        let data = match (&leaf_file, &items) {
+            (Some(data), _) => data.clone(),
+            (None, Some(items)) => items[leaf_index].clone(),
+            (None, None) => (offset + leaf_index).to_le_bytes().to_vec(),
+        };
+        args.canonical_data(&data)
+    };
+    let leaf_transform = match &args.leaf_transform {
+        Some(transform) => {
+            println!("Leaf Transform: {}", transform.spec());
+            Some(TransformedLeaf {
+                transform: transform.clone(),
+                data: leaf_data()?,
+            })
+        }
+        None => None,
+    };
+    let preimage = match args.commit_preimage {
+        true => Some(leaf_data()?),
+        false => None,
+    };
+
+    let checkpoint = match &args.checkpoint_roots {
+        Some(path) => Some(build_checkpoint_proof(path, &root)?),
+        None => None,
+    };
+    let blocklist = match &args.blocklist {
+        Some(path) => Some(build_blocklist_exclusion(path, &leaf)?),
+        None => None,
+    };
+
+    let bloom_filter = args
+        .bloom_bits
+        .map(|bits| BloomFilter::from_leaves(&leaves, bits, args.bloom_hashes));
+
+    let authorization = match args.authorization_key {
+        Some(secret) => Some(sign_leaf(&secret, &leaf)?),
+        None => None,
+    };
+
+    let bls_signature = match args.bls_sign {
+        _ if args.bls_keys.is_empty() => None,
+        BlsSigned::Leaf => Some(sign_bls(&args.bls_keys, SignedValue::Leaf, &leaf)?),
+        BlsSigned::Root => Some(sign_bls(&args.bls_keys, SignedValue::Root, &root)?),
+    };
+
+    let vdf = args.vdf_iterations.map(|iterations| {
+        let start = std::time::Instant::now();
+        let vdf = VdfProof::evaluate(args.vdf_seed.unwrap_or_default(), &root, &leaf, iterations);
+        println!("VDF Evaluation Time: {:?}", start.elapsed());
+        vdf
+    });
+
+    let kzg_opening = args.kzg_opening.then(|| {
+        println!("WARNING: --kzg-opening uses an insecure test setup with a known tau.");
+        let coefficients = kzg::interpolate(&leaves);
+        let setup = kzg::Setup::insecure(&rand::thread_rng().gen(), coefficients.len());
+        setup.open(&coefficients, leaf_index as u64)
+    });
+
+    let leaf_randomness = args.commit_leaf_hiding.then(|| {
+        let randomness = args
+            .leaf_randomness
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        println!("Leaf: 0x{}", hex::encode(leaf));
+        println!("Leaf Randomness: 0x{}", hex::encode(randomness));
+        randomness
+    });
+
+    Ok(Options {
+        expect_leaf: args.expect_leaf,
+        commit_ordinal: args.commit_ordinal,
+        commit_root_reversed: args.commit_root_reversed,
+        tagged_results: args.tagged_results,
+        pack_flags: args.pack_flags,
+        index_lookup,
+        elf_hash: args.commit_elf_hash.then(elf_hash),
+        eip712_domain: args.eip712_domain,
+        leaf_randomness,
+        updates: (!args.updates.is_empty()).then(|| args.updates.clone()),
+        authorization,
+        commit_proof_stats: args.commit_proof_stats,
+        neighbors,
+        kzg_opening,
+        canonicalization: args.canonicalize.map(CanonicalizeMode::committed),
+        forest: args.global_index.map(|global_index| ForestLookup {
+            sizes: args.forest_sizes.clone(),
+            global_index,
+        }),
+        bls_signature,
+        vdf,
+        commit_promotions: args.commit_promotions,
+        cap_level: args.cap_level,
+        pair,
+        bloom_filter,
+        leaf_transform,
+        checkpoint,
+        commit_phase: args.commit_phase,
+        reveal: args.reveal.clone(),
+        range_count,
+        imt,
+        batch,
+        strict: args.strict,
+        field_packed: args.field_packed,
+        abi_encoded: args.abi_encoded,
+        blocklist,
+        leaf_count,
+        erasure,
+        absence,
+        aggregate,
+        software_sha256: false,
+        append,
+        preimage,
+    })
+}
+
+/// Computes the root of a binary tree of `total_leaves` leaves and the leaf at `index` with its
+/// sibling path, bottom-up as in [`tree::proof_path`], producing the leaves with `leaf` on the fly.
+///
+/// The sibling subtrees of the path cover every other leaf exactly once, and each is hashed
+/// depth-first, so only one node per level is held at a time, per thread, instead of the whole tree.
+pub fn root_and_proof(
+    total_leaves: usize,
+    index: usize,
+    leaf: LeafFn,
+    parent: ParentFn,
+) -> Result<StreamedProof, String> {
+    // The node `height` levels above the leaves at `position` of its level, or `None` past the
+    // end of the level.
+    fn subtree(
+        height: usize,
+        position: usize,
+        total_leaves: usize,
+        leaf: LeafFn,
+        parent: ParentFn,
+    ) -> Result<Option<[u8; 32]>, String> {
+        if position << height >= total_leaves {
+            return Ok(None);
+        }
+        if height == 0 {
+            return leaf(position).map(Some);
+        }
+        let left = || subtree(height - 1, 2 * position, total_leaves, leaf, parent);
+        let right = || subtree(height - 1, 2 * position + 1, total_leaves, leaf, parent);
+        let (left, right) = if height >= PARALLEL_SUBTREE_HEIGHT {
+            rayon::join(left, right)
+        } else {
+            (left(), right())
+        };
+        // An unpaired last node is promoted unchanged.
+        Ok(match (left?, right?) {
+            (Some(left), Some(right)) => Some(parent(&left, &right)),
+            (left, _) => left,
+        })
+    }
+
+    let proven = leaf(index)?;
+    let mut path = Vec::new();
+    for height in 0..tree::height(total_leaves) {
+        path.extend(subtree(
+            height,
+            (index >> height) ^ 1,
+            total_leaves,
+            leaf,
+            parent,
+        )?);
+    }
+    let root = tree::root_from_path(proven, index, total_leaves, &path, parent)
+        .ok_or_else(|| format!("leaf {} is outside the tree of {}", index, total_leaves))?;
+    Ok(StreamedProof {
+        root,
+        leaf: proven,
+        path,
+    })
+}
+
+/// Rejects strict or field-packed options that ask for anything besides the core, which those
+/// proofs would silently drop.
+fn check_core_only(options: &Options) -> Result<(), String> {
+    let core_only = Options {
+        strict: options.strict,
+        field_packed: options.field_packed,
+        ..Options::default()
+    };
+    let flag = match (options.strict, options.field_packed) {
+        (true, _) => "--strict",
+        (false, true) => "--field-packed",
+        (false, false) => return Ok(()),
+    };
+    if *options != core_only {
+        return Err(format!(
+            "{} commits only the core values and cannot be combined with options that commit \
+             anything else",
+            flag
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects packed flags with options whose results have no bit in
+/// [`goldinals_lib::public_values::FLAG_LABELS`], which the program would commit as a labelled
+/// list instead.
+fn check_pack_flags(options: &Options) -> Result<(), String> {
+    let unpackable = [
+        ("--global-index", options.forest.is_some()),
+        ("--bls-key", options.bls_signature.is_some()),
+        ("--vdf-iterations", options.vdf.is_some()),
+        ("--bloom-bits", options.bloom_filter.is_some()),
+        ("--leaf-transform", options.leaf_transform.is_some()),
+        ("--checkpoint-roots", options.checkpoint.is_some()),
+        (
+            "--reveal",
+            options.reveal.is_some() && !options.commit_phase,
+        ),
+        ("--kzg-opening", options.kzg_opening.is_some()),
+    ];
+    match unpackable.iter().find(|(_, set)| *set) {
+        Some((flag, _)) if options.pack_flags => Err(format!(
+            "--pack-flags has no bit for the result of {}; use --tagged-results instead",
+            flag
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The leaves of a `--leaves-cbor`, `--leaves-file`, `--erasure-data` or `--leaves-proto` dataset
+/// and the items they were derived from.
+struct Dataset {
+    leaves: Vec<[u8; 32]>,
+    items: Vec<Vec<u8>>,
+}
+
+impl Dataset {
+    /// Lists the malformed items of the dataset at `path` and fails if there are any, or if there
+    /// are no items at all.
+    fn check(self, path: &Path, label: &str, malformed: &[String]) -> Result<Self, String> {
+        if !malformed.is_empty() {
+            println!("{} Malformed Items: {}", label, malformed.len());
+            for line in malformed {
+                println!("{}", line);
+            }
+            return Err(format!(
+                "{} has {} malformed items",
+                path.display(),
+                malformed.len()
+            ));
+        }
+        if self.leaves.is_empty() {
+            return Err(format!("{} has no items", path.display()));
+        }
+        Ok(self)
+    }
+}
+
+/// An indexed Merkle tree built from `--imt-values`, with the low leaf of the queried value.
+struct ImtDataset {
+    dataset: Dataset,
+    query: ImtQuery,
+    low_index: usize,
+}
+
+/// Builds the indexed Merkle tree over the values listed at `path` and finds the low leaf of
+/// `value`, failing if the value is in the set.
+///
+/// Each leaf's item is its 72-byte encoding, hashed with [`TreeArgs::hash_leaf`].
+fn load_imt(args: &TreeArgs, path: &PathBuf, value: &[u8; 32]) -> Result<ImtDataset, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let mut seen = HashSet::new();
+    let values = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let value = parse_hash(line.trim())
+                .map_err(|e| format!("{}:{}: {}", path.display(), number + 1, e))?;
+            if value == [0; 32] {
+                return Err(format!(
+                    "{}:{}: 0 is reserved for the zero leaf",
+                    path.display(),
+                    number + 1
+                ));
+            }
+            if !seen.insert(value) {
+                return Err(format!(
+                    "{}:{}: duplicate value",
+                    path.display(),
+                    number + 1
+                ));
+            }
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    println!("IMT Values: {}", values.len());
+
+    let leaves = imt::leaves_from_values(&values);
+    let low_index = imt::low_leaf_index(&leaves, value).ok_or_else(|| {
+        format!(
+            "0x{} is in the set of {}, so it has no low leaf",
+            hex::encode(value),
+            path.display()
+        )
+    })?;
+    println!("IMT Low Leaf Index: {}", low_index);
+
+    let items: Vec<Vec<u8>> = leaves.iter().map(|leaf| leaf.to_bytes().to_vec()).collect();
+    let dataset = Dataset {
+        leaves: items
+            .iter()
+            .map(|item| args.hash_leaf(item))
+            .collect::<Result<_, _>>()?,
+        items,
+    };
+    Ok(ImtDataset {
+        dataset,
+        query: ImtQuery {
+            value: *value,
+            low_leaf: leaves[low_index],
+        },
+        low_index,
+    })
+}
+
+/// Reads the leaves of a `--leaves-cbor` dataset, hashing each item with [`TreeArgs::hash_leaf`]
+/// or, with `--raw-leaves`, taking it verbatim.
+///
+/// Every malformed item is reported, not just the first.
+#[cfg(feature = "cbor")]
+fn load_cbor_leaves(args: &TreeArgs, path: &PathBuf) -> Result<Dataset, String> {
+    let data =
+        std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let entries = cbor::decode_array(&data)
+        .map_err(|e| format!("{} is not a CBOR array: {}", path.display(), e))?;
+    println!("CBOR Items: {}", entries.len());
+
+    let mut leaves = Vec::with_capacity(entries.len());
+    let mut items = Vec::with_capacity(entries.len());
+    let mut malformed = Vec::new();
+    for (i, entry) in entries.into_iter().enumerate() {
+        let leaf = match &entry {
+            cbor::Entry::Bytes(bytes) if args.raw_leaves => <[u8; 32]>::try_from(&bytes[..])
+                .map_err(|_| format!("{} bytes, expected 32", bytes.len())),
+            cbor::Entry::Bytes(bytes) => args.hash_leaf(bytes),
+            cbor::Entry::Other(kind) => Err(format!("{}, expected a byte string", kind)),
+        };
+        match (leaf, entry) {
+            (Ok(leaf), cbor::Entry::Bytes(bytes)) => {
+                leaves.push(leaf);
+                items.push(bytes);
+            }
+            (Ok(_), cbor::Entry::Other(_)) => unreachable!("only byte strings hash to leaves"),
+            (Err(e), _) => malformed.push(format!("  item {}: {}", i, e)),
+        }
+    }
+    Dataset { leaves, items }.check(path, "CBOR", &malformed)
+}
+
+/// Reads the leaves of a `--leaves-file` dataset, one hex leaf per line or a JSON array of
+/// 0x-prefixed hex strings, each taken verbatim as its own item.
+///
+/// Every malformed leaf is reported with its line, or its position in the JSON array, not just the
+/// first.
+fn load_leaves_file(path: &PathBuf) -> Result<Dataset, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let json = contents.trim_start().starts_with('[');
+    let entries: Vec<(String, String)> = if json {
+        let values: Vec<String> = serde_json::from_str(&contents)
+            .map_err(|e| format!("{} is not a JSON array of strings: {}", path.display(), e))?;
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (format!("entry {}", i), value))
+            .collect()
+    } else {
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| (format!("line {}", number + 1), line.trim().to_string()))
+            .collect()
+    };
+    println!("Leaves File Entries: {}", entries.len());
+
+    let mut leaves = Vec::with_capacity(entries.len());
+    let mut malformed = Vec::new();
+    for (location, value) in entries {
+        let leaf = if json && !value.starts_with("0x") {
+            Err("expected a 0x prefix".to_string())
+        } else {
+            parse_hash(&value)
+        };
+        match leaf {
+            Ok(leaf) => leaves.push(leaf),
+            Err(e) => malformed.push(format!("  {}: {}", location, e)),
+        }
+    }
+    let items = leaves.iter().map(|leaf| leaf.to_vec()).collect();
+    Dataset { leaves, items }.check(path, "Leaves File", &malformed)
+}
+
+/// Splits the file at `path` into chunks with the Reed-Solomon `coding` and hashes every chunk into
+/// its leaf with [`erasure::leaf`], truncated with `--hash-bytes`, regardless of the scheme.
+fn load_erasure_chunks(args: &TreeArgs, path: &PathBuf, coding: Coding) -> Result<Dataset, String> {
+    if !coding.is_valid() {
+        return Err(format!(
+            "--total-chunks {} is below --data-chunks {}",
+            coding.total_chunks, coding.data_chunks
+        ));
+    }
+    let data =
+        std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let chunks = coding.encode(&data);
+    println!(
+        "Erasure Chunks: {} data, {} total, {} bytes each",
+        coding.data_chunks,
+        coding.total_chunks,
+        chunks[0].len()
+    );
+    let leaves = chunks
+        .iter()
+        .map(|chunk| {
+            let leaf = erasure::leaf(chunk);
+            match args.hash_bytes {
+                Some(n) => truncated::truncate(&leaf, n),
+                None => leaf,
+            }
+        })
+        .collect();
+    Ok(Dataset {
+        leaves,
+        items: chunks,
+    })
+}
+
+/// Reads the leaves of a `--leaves-proto` dataset, hashing each message's bytes with
+/// [`TreeArgs::hash_leaf`].
+///
+/// Every message that is not well-formed protobuf is reported, not just the first.
+#[cfg(feature = "proto")]
+fn load_proto_leaves(args: &TreeArgs, path: &PathBuf) -> Result<Dataset, String> {
+    let data =
+        std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let messages = proto::split_delimited(&data).map_err(|e| {
+        format!(
+            "{} is not a stream of length-delimited messages: {}",
+            path.display(),
+            e
+        )
+    })?;
+    println!("Protobuf Messages: {}", messages.len());
+
+    let mut leaves = Vec::with_capacity(messages.len());
+    let mut malformed = Vec::new();
+    for (i, message) in messages.iter().enumerate() {
+        match proto::check_wire_format(message).and_then(|()| args.hash_leaf(message)) {
+            Ok(leaf) => leaves.push(leaf),
+            Err(e) => malformed.push(format!("  message {}: {}", i, e)),
+        }
+    }
+    Dataset {
+        leaves,
+        items: messages,
+    }
+    .check(path, "Protobuf", &malformed)
+}
+
+/// Signs `message` with every BLS secret key and aggregates the signatures.
+fn sign_bls(
+    keys: &[[u8; 32]],
+    signed: SignedValue,
+    message: &[u8],
+) -> Result<BlsSignature, String> {
+    let secrets: Vec<_> = keys
+        .iter()
+        .map(|key| bls::secret_key(key).ok_or_else(|| "invalid BLS12-381 secret key".to_string()))
+        .collect::<Result<_, _>>()?;
+    let signature = bls::sign_aggregate(&secrets, signed, message);
+    println!("BLS Signature: 0x{}", hex::encode(&signature.signature));
+    Ok(signature)
+}
+
+/// The tree of a `--forest-sizes` forest that a build proves.
+struct LocatedTree {
+    /// The global index of the tree's first leaf.
+    offset: usize,
+    size: usize,
+    local_index: usize,
+}
+
+/// Finds the tree `--global-index` maps to.
+///
+/// An out-of-range global index is not an error: leaf 0 of tree 0 is proven instead, and the
+/// program commits the out-of-range status.
+fn locate_in_forest(sizes: &[u64], global_index: u64) -> Result<LocatedTree, String> {
+    if sizes.is_empty() || sizes.contains(&0) {
+        return Err("--forest-sizes must list at least one tree, each non-empty".to_string());
+    }
+    let (tree, local_index) = match forest::locate(sizes, global_index) {
+        Some((tree, local)) => {
+            println!("Forest Tree: {}", tree);
+            println!("Forest Local Index: {}", local);
+            (tree as usize, local as usize)
+        }
+        None => {
+            println!(
+                "WARNING: global index {} is outside the forest; proving leaf 0 of tree 0.",
+                global_index
+            );
+            (0, 0)
+        }
+    };
+    let offset: u64 = sizes[..tree].iter().sum();
+    let to_usize = |n: u64| usize::try_from(n).map_err(|_| format!("{} leaves is too many", n));
+    Ok(LocatedTree {
+        offset: to_usize(offset)?,
+        size: to_usize(sizes[tree])?,
+        local_index,
+    })
+}
+
+/// A Merkle-Patricia trie proof, with every field hex-encoded.
+///
+/// `proof` lists the RLP-encoded nodes from the root down, as in the `accountProof` and
+/// `storageProof[].proof` fields of `eth_getProof`.
+#[derive(Deserialize)]
+struct TrieProofFile {
+    root: String,
+    key: String,
+    value: String,
+    proof: Vec<String>,
+}
+
+/// Writes the guest inputs for the trie proof at `path`.
+fn build_trie_inputs(args: &TreeArgs, path: &PathBuf) -> Result<SP1Stdin, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let file: TrieProofFile = serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not a trie proof: {}", path.display(), e))?;
+    let decode = |field: &str, value: &str| {
+        hex::decode(value.strip_prefix("0x").unwrap_or(value))
+            .map_err(|e| format!("{}: invalid hex in `{}`: {}", path.display(), field, e))
+    };
+
+    let root = parse_hash(&file.root).map_err(|e| format!("{}: `root`: {}", path.display(), e))?;
+    let key = decode("key", &file.key)?;
+    let value = decode("value", &file.value)?;
+    let proof = file
+        .proof
+        .iter()
+        .map(|node| decode("proof", node))
+        .collect::<Result<Vec<_>, _>>()?;
+    println!("Trie Proof Nodes: {}", proof.len());
+
+    let mut stdin = SP1Stdin::new();
+    write_header(&mut stdin);
+    stdin.write(&(Scheme::Patricia as u8));
+    stdin.write(&root);
+    stdin.write(&key);
+    stdin.write(&value);
+    stdin.write(&proof);
+    let options = Options {
+        commit_root_reversed: args.commit_root_reversed,
+        tagged_results: args.tagged_results,
+        pack_flags: args.pack_flags,
+        elf_hash: args.commit_elf_hash.then(elf_hash),
+        eip712_domain: args.eip712_domain,
+        strict: args.strict,
+        field_packed: args.field_packed,
+        abi_encoded: args.abi_encoded,
+        ..Options::default()
+    };
+    check_core_only(&options)?;
+    stdin.write(&options);
+    Ok(stdin)
+}
+
+/// Builds the sparse tree over the entries listed at `path` and proves the path of `--sparse-key`.
+fn build_sparse_inputs(args: &TreeArgs, path: &PathBuf) -> Result<SP1Stdin, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let mut keys = HashSet::new();
+    let entries = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let at = |e: String| format!("{}:{}: {}", path.display(), number + 1, e);
+            let [key, value] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+                return Err(at("expected a key and a value".to_string()));
+            };
+            let key = parse_hash(key).map_err(at)?;
+            if !keys.insert(key) {
+                return Err(at(format!("duplicate key 0x{}", hex::encode(key))));
+            }
+            Ok((key, parse_hash(value).map_err(at)?))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    println!("Sparse Entries: {}", entries.len());
+
+    let key = args.sparse_key.expect("--sparse-key is required");
+    let tree = SparseTree::from_entries(entries);
+    let value = tree.get(&key).copied();
+    let proof = tree.proof(&key);
+    println!("Sparse Proof Siblings: {}", proof.siblings.len());
+
+    let mut stdin = SP1Stdin::new();
+    write_header(&mut stdin);
+    stdin.write(&(Scheme::Sparse as u8));
+    stdin.write(&tree.root());
+    stdin.write(&key);
+    stdin.write(&value);
+    stdin.write(&proof);
+    let options = Options {
+        commit_root_reversed: args.commit_root_reversed,
+        tagged_results: args.tagged_results,
+        pack_flags: args.pack_flags,
+        elf_hash: args.commit_elf_hash.then(elf_hash),
+        eip712_domain: args.eip712_domain,
+        strict: args.strict,
+        field_packed: args.field_packed,
+        abi_encoded: args.abi_encoded,
+        ..Options::default()
+    };
+    check_core_only(&options)?;
+    stdin.write(&options);
+    Ok(stdin)
+}
+
+/// Reads the `merkleblock` message at `path` and proves the branch of `--txid` in it.
+fn build_bitcoin_inputs(args: &TreeArgs, path: &PathBuf) -> Result<SP1Stdin, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let contents = contents.trim();
+    let bytes = hex::decode(contents.strip_prefix("0x").unwrap_or(contents))
+        .map_err(|e| format!("{}: invalid hex: {}", path.display(), e))?;
+    let block = MerkleBlock::parse(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let branches = block
+        .branches()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    // Txids are displayed in the reverse of the byte order they are hashed in.
+    let display = |hash: &[u8; 32]| hex::encode(hash.iter().rev().copied().collect::<Vec<_>>());
+    let (txid, proof) = match args.txid {
+        Some(mut txid) => {
+            txid.reverse();
+            branches
+                .into_iter()
+                .find(|(matched, _)| *matched == txid)
+                .ok_or_else(|| {
+                    format!(
+                        "{} does not match the transaction {}",
+                        path.display(),
+                        display(&txid)
+                    )
+                })?
+        }
+        None => match <[_; 1]>::try_from(branches) {
+            Ok([branch]) => branch,
+            Err(branches) => {
+                return Err(format!(
+                    "{} matches {} transactions, select one with --txid",
+                    path.display(),
+                    branches.len()
+                ))
+            }
+        },
+    };
+    println!(
+        "Block Hash: {}",
+        display(&bitcoin::block_hash(&block.header))
+    );
+    println!("Txid: {}", display(&txid));
+    println!(
+        "Transaction Index: {} of {}",
+        proof.index, proof.total_transactions
+    );
+
+    let mut stdin = SP1Stdin::new();
+    write_header(&mut stdin);
+    stdin.write(&(Scheme::Bitcoin as u8));
+    stdin.write(&block.header);
+    stdin.write(&txid);
+    stdin.write(&proof);
+    let options = Options {
+        commit_root_reversed: args.commit_root_reversed,
+        tagged_results: args.tagged_results,
+        pack_flags: args.pack_flags,
+        elf_hash: args.commit_elf_hash.then(elf_hash),
+        eip712_domain: args.eip712_domain,
+        strict: args.strict,
+        field_packed: args.field_packed,
+        abi_encoded: args.abi_encoded,
+        ..Options::default()
+    };
+    check_core_only(&options)?;
+    stdin.write(&options);
+    Ok(stdin)
+}
+
+/// Builds a Merkle Mountain Range over the `--leaves-file` leaves, or over `sha256(i)` for
+/// `0..total_leaves`, and proves the leaf at `--leaf-index`, or a random leaf.
+fn build_mmr_inputs(args: &TreeArgs) -> Result<SP1Stdin, String> {
+    let leaves = match &args.leaves_file {
+        Some(path) => load_leaves_file(path)?.leaves,
+        None => (0..args.total_leaves)
+            .into_par_iter()
+            .map(|i| args.hash_leaf(&i.to_le_bytes()))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    if leaves.is_empty() {
+        return Err("an MMR needs at least one leaf".to_string());
+    }
+    let total_leaves = leaves.len() as u64;
+    println!("Total Leaves: {}", total_leaves);
+    let leaf_index = match args.leaf_index {
+        Some(index) if index >= total_leaves => {
+            return Err(format!(
+                "--leaf-index {} is outside the MMR of {} leaves",
+                index, total_leaves
+            ))
+        }
+        Some(index) => index,
+        None => rand::thread_rng().gen_range(0..total_leaves),
+    };
+
+    let mmr = Mmr::from_leaves(&leaves);
+    let proof = mmr.proof(leaf_index).expect("the leaf is in the MMR");
+    println!("MMR Size: {}", proof.mmr_size);
+    println!("Leaf Index: {} (position {})", leaf_index, proof.position());
+
+    let mut stdin = SP1Stdin::new();
+    write_header(&mut stdin);
+    stdin.write(&(Scheme::Mmr as u8));
+    stdin.write(&mmr.root());
+    stdin.write(&leaves[leaf_index as usize]);
+    stdin.write(&proof);
+    let options = Options {
+        commit_root_reversed: args.commit_root_reversed,
+        tagged_results: args.tagged_results,
+        pack_flags: args.pack_flags,
+        elf_hash: args.commit_elf_hash.then(elf_hash),
+        eip712_domain: args.eip712_domain,
+        strict: args.strict,
+        field_packed: args.field_packed,
+        abi_encoded: args.abi_encoded,
+        ..Options::default()
+    };
+    check_core_only(&options)?;
+    stdin.write(&options);
+    Ok(stdin)
+}
+
+/// A `--proof-format json` proof. Field names are accepted in camelCase or snake_case.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SiblingProof {
+    root: String,
+    leaf: String,
+    /// The sibling hashes from the leaf up, each 32 bytes or `--hash-bytes` in truncated trees.
+    proof: Vec<String>,
+    #[serde(alias = "leaf_index", alias = "index")]
+    leaf_index: usize,
+    #[serde(alias = "total_leaves")]
+    total_leaves: usize,
+}
+
+/// Reads the proof at `path` in `--proof-format` and writes it as guest inputs for the tree
+/// `--scheme` selects, warning if it does not verify.
+fn build_proof_inputs(args: &TreeArgs, path: &PathBuf) -> Result<SP1Stdin, String> {
+    let scheme = match args.scheme()? {
+        Scheme::Sha256 => "sha256",
+        Scheme::Hybrid => "hybrid",
+        Scheme::TruncatedSha256 => "truncated_sha256",
+        Scheme::Kary => "kary",
+        Scheme::Keccak256 => "keccak256",
+        Scheme::Blake2b => "blake2b",
+        Scheme::Blake3 => "blake3",
+        scheme => return Err(format!("--proof-input does not support {:?} trees", scheme)),
+    };
+    let (root, leaf, proof, leaf_index, total_leaves) = match args.proof_format {
+        ProofFormat::Json => {
+            if args.proof_root.is_some() || args.proof_leaf.is_some() || args.leaf_index.is_some() {
+                return Err(
+                    "--proof-format json reads the root, leaf and index from the file, \
+                     not --proof-root, --proof-leaf or --leaf-index"
+                        .to_string(),
+                );
+            }
+            if args.arity.is_some() {
+                return Err(
+                    "k-ary proofs have no sibling array, use --proof-format rs-merkle".to_string(),
+                );
+            }
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+            let json: SiblingProof = serde_json::from_str(&contents)
+                .map_err(|e| format!("{}: invalid proof: {}", path.display(), e))?;
+            let node_len = args.hash_bytes.unwrap_or(32) as usize;
+            let proof = json
+                .proof
+                .iter()
+                .enumerate()
+                .map(|(i, sibling)| {
+                    let bytes = hex::decode(sibling.strip_prefix("0x").unwrap_or(sibling))
+                        .map_err(|e| format!("{}: sibling {}: {}", path.display(), i, e))?;
+                    match bytes.len() == node_len {
+                        true => Ok(bytes),
+                        false => Err(format!(
+                            "{}: sibling {} is {} bytes, expected {}",
+                            path.display(),
+                            i,
+                            bytes.len(),
+                            node_len
+                        )),
+                    }
+                })
+                .collect::<Result<Vec<_>, String>>()?
+                .concat();
+            (
+                json.root,
+                json.leaf,
+                proof,
+                json.leaf_index,
+                json.total_leaves,
+            )
+        }
+        ProofFormat::RsMerkle => {
+            let (Some(root), Some(leaf), Some(leaf_index)) =
+                (args.proof_root, args.proof_leaf, args.leaf_index)
+            else {
+                return Err(
+                    "--proof-format rs-merkle requires --proof-root, --proof-leaf and --leaf-index"
+                        .to_string(),
+                );
+            };
+            let proof = std::fs::read(path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+            let leaf_index = usize::try_from(leaf_index)
+                .map_err(|_| format!("leaf index {} is too large", leaf_index))?;
+            (
+                hex::encode(root),
+                hex::encode(leaf),
+                proof,
+                leaf_index,
+                args.total_leaves,
+            )
+        }
+    };
+
+    let witness = Witness {
+        scheme: scheme.to_string(),
+        hash_bytes: args.hash_bytes,
+        arity: args.arity,
+        personalization: args.hash_personalization.clone(),
+        root,
+        leaf,
+        proof: hex::encode(proof),
+        leaf_index,
+        total_leaves,
+    };
+    println!("Leaf Index: {} of {}", leaf_index, total_leaves);
+    if let (false, diagnostic) = witness.expected_validity()? {
+        println!(
+            "WARNING: the proof does not verify, so the program will commit is_valid = false{}",
+            diagnostic.map_or(String::new(), |d| format!(" (diagnostic {})", d as u8))
+        );
+    }
+
+    let options = Options {
+        expect_leaf: args.expect_leaf,
+        commit_root_reversed: args.commit_root_reversed,
+        tagged_results: args.tagged_results,
+        pack_flags: args.pack_flags,
+        elf_hash: args.commit_elf_hash.then(elf_hash),
+        eip712_domain: args.eip712_domain,
+        strict: args.strict,
+        field_packed: args.field_packed,
+        abi_encoded: args.abi_encoded,
+        ..Options::default()
+    };
+    check_core_only(&options)?;
+    witness.to_stdin_with(&options)
+}
+
+/// Signs `leaf` as described in [`goldinals_lib::authorization`].
+fn sign_leaf(secret: &[u8; 32], leaf: &[u8; 32]) -> Result<Authorization, String> {
+    let key = SigningKey::from_slice(secret)
+        .map_err(|_| "--authorization-key is not a valid secp256k1 secret key".to_string())?;
+    let signature: Signature = key
+        .sign_prehash(&authorization::message_hash(leaf))
+        .expect("failed to sign the leaf");
+    println!(
+        "Authorization Signer: 0x{}",
+        hex::encode(authorization::address(key.verifying_key()))
+    );
+    Ok(Authorization {
+        public_key: key.verifying_key().to_sec1_bytes().to_vec(),
+        signature: signature.to_bytes().to_vec(),
+    })
+}
+
+/// Reads `key,position` entries from an index file, builds the index tree over them in file order
+/// and returns the lookup of `key`.
+/// Proves that `root` is in the checkpoint tree over the roots listed at `path`.
+fn build_checkpoint_proof(path: &PathBuf, root: &[u8; 32]) -> Result<CheckpointProof, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let roots = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            parse_hash(line.trim()).map_err(|e| format!("{}:{}: {}", path.display(), number + 1, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let index = roots.iter().position(|r| r == root).ok_or_else(|| {
+        format!(
+            "the root 0x{} is not checkpointed in {}",
+            hex::encode(root),
+            path.display()
+        )
+    })?;
+    let tree = LevelTree::<Sha256Hasher>::from_leaves(&roots);
+    let proof = CheckpointProof {
+        checkpoint_root: tree.root().expect("Failed to get root"),
+        index: index as u64,
+        count: roots.len() as u64,
+        proof_bytes: tree.proof(&[index]).to_bytes(),
+    };
+    println!("Checkpoint Root: 0x{}", hex::encode(proof.checkpoint_root));
+    println!("Checkpoint Index: {} / {}", proof.index, proof.count);
+    Ok(proof)
+}
+
+/// Builds the blocklist tree over the leaves listed at `path`, sorted and deduplicated, and proves
+/// the blocklist leaves around `leaf`, failing if `leaf` is blocklisted.
+fn build_blocklist_exclusion(
+    path: &PathBuf,
+    leaf: &[u8; 32],
+) -> Result<BlocklistExclusion, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let mut leaves = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            parse_hash(line.trim()).map_err(|e| format!("{}:{}: {}", path.display(), number + 1, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    leaves.sort_unstable();
+    leaves.dedup();
+
+    let position = match leaves.binary_search(leaf) {
+        Ok(_) => {
+            return Err(format!(
+                "the leaf 0x{} is on the blocklist {}",
+                hex::encode(leaf),
+                path.display()
+            ))
+        }
+        Err(position) => position,
+    };
+    let tree = LevelTree::<Sha256Hasher>::from_leaves(&leaves);
+    let bound = |index: usize| Neighbor {
+        leaf: leaves[index],
+        proof_bytes: tree.proof(&[index]).to_bytes(),
+    };
+    let exclusion = BlocklistExclusion {
+        root: tree.root().unwrap_or_default(),
+        total_leaves: leaves.len() as u64,
+        position: position as u64,
+        lower: position.checked_sub(1).map(bound),
+        upper: (position < leaves.len()).then(|| bound(position)),
+    };
+    println!("Blocklist Root: 0x{}", hex::encode(exclusion.root));
+    println!(
+        "Blocklist Position: {} / {}",
+        exclusion.position, exclusion.total_leaves
+    );
+    Ok(exclusion)
+}
+
+fn build_index_lookup(path: &PathBuf, key: &str) -> Result<IndexLookup, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let mut entries = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (entry_key, position) = line
+            .rsplit_once(',')
+            .and_then(|(k, p)| Some((k, p.trim().parse::<u64>().ok()?)))
+            .ok_or_else(|| format!("{}:{}: expected `key,position`", path.display(), number + 1))?;
+        entries.push((entry_key.as_bytes(), position));
+    }
+
+    let entry_index = entries
+        .iter()
+        .position(|(entry_key, _)| *entry_key == key.as_bytes())
+        .ok_or_else(|| format!("key {:?} is not in {}", key, path.display()))?;
+    let tree = IndexTree::from_entries(&entries);
+    let lookup = IndexLookup {
+        root: tree.root().expect("Failed to get root"),
+        key: key.as_bytes().to_vec(),
+        position: entries[entry_index].1,
+        proof_bytes: tree.proof_bytes(entry_index),
+        entry_index,
+        total_entries: entries.len(),
+    };
+    println!("Index Root: 0x{}", hex::encode(lookup.root));
+    println!("Index Position: {}", lookup.position);
+    Ok(lookup)
+}
+
+/// The SHA-256 of [`MERKLE_ELF`].
+pub fn elf_hash() -> [u8; 32] {
+    Sha256::digest(MERKLE_ELF).into()
+}
+
+/// The arguments whose values are secret keys, salts or blinding randomness.
+pub const SECRET_ARGS: &[&str] = &[
+    "--authorization-key",
+    "--bls-key",
+    "--leaf-randomness",
+    "--reveal",
+];
+
+/// Returns the command-line arguments with the value of every [`SECRET_ARGS`] argument replaced by
+/// `<redacted>`, in both the `--arg value` and `--arg=value` forms.
+pub fn redacted_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut redact_next = false;
+    args.into_iter()
+        .map(|arg| {
+            if std::mem::take(&mut redact_next) {
+                return "<redacted>".to_string();
+            }
+            match arg.split_once('=') {
+                Some((name, _)) if SECRET_ARGS.contains(&name) => format!("{}=<redacted>", name),
+                _ => {
+                    redact_next = SECRET_ARGS.contains(&arg.as_str());
+                    arg
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns whether `SP1_USE_MOCK` asks for the mock prover.
+pub fn use_mock_prover() -> bool {
+    std::env::var("SP1_USE_MOCK").is_ok_and(|value| matches!(value.as_str(), "1" | "true"))
+}
+
+/// Creates the prover client from the environment, falling back to the mock prover when
+/// `SP1_USE_MOCK` is set.
+pub fn prover_client() -> ProverClient {
+    if use_mock_prover() {
+        eprintln!("==================================================================");
+        eprintln!("WARNING: SP1_USE_MOCK is set, using the MOCK prover.");
+        eprintln!("Proofs and fixtures produced by this run are INSECURE and must only");
+        eprintln!("be used to exercise the pipeline in tests.");
+        eprintln!("==================================================================");
+        ProverClient::mock()
+    } else {
+        ProverClient::new()
+    }
+}
+
+/// Prints the size of the committed public values and exits with an error if it exceeds `max`.
+pub fn check_public_values_size(public_values: &[u8], max: Option<usize>) {
+    println!("Public Values Size: {} bytes", public_values.len());
+    if let Some(max) = max.filter(|&max| public_values.len() > max) {
+        eprintln!(
+            "Error: the public values are {} bytes, over the --max-public-values-bytes limit of {}",
+            public_values.len(),
+            max
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Writes the raw committed public values to `path`, exiting on failure.
+pub fn write_public_values(path: &std::path::Path, public_values: &[u8]) {
+    std::fs::write(path, public_values).unwrap_or_else(|e| {
+        eprintln!("Error: failed to write {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+    println!("Wrote public values to {}", path.display());
+}
+
+/// ABI-encodes raw committed public values as a [`goldinals_lib::abi::PublicValuesStruct`].
+///
+/// Only regular public values, starting with the full core, can be encoded this way.
+pub fn abi_encode_public_values(bytes: &[u8]) -> Vec<u8> {
+    abi::encode(bytes)
+}
+
+/// Decodes committed public values in either the ABI-encoded layout of `--abi-encoded` or the
+/// layouts [`PublicValues::from_bytes`] parses.
+///
+/// The ABI layout is tried first: its strict decoding needs a first word of `0x20`, which the root
+/// of regular public values does not have, while regular decoding is looser.
+pub fn decode_public_values(bytes: &[u8]) -> Result<PublicValues, DecodeError> {
+    PublicValues::from_abi_bytes(bytes).or_else(|_| PublicValues::from_bytes(bytes))
+}
+
+/// Parses a 32-byte hash from hex, with or without a `0x` prefix.
+pub fn parse_hash(value: &str) -> Result<[u8; 32], String> {
+    let bytes =
+        hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| e.to_string())?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("expected 32 bytes, got {}", bytes.len()))
+}
+
+/// Parses a `--range-count` range as `FIRST:LAST`, inclusive.
+pub fn parse_range(value: &str) -> Result<RangeInclusive<u64>, String> {
+    let (first, last) = value
+        .split_once(':')
+        .ok_or_else(|| "expected FIRST:LAST".to_string())?;
+    let first: u64 = first.parse().map_err(|e| format!("invalid FIRST: {}", e))?;
+    let last: u64 = last.parse().map_err(|e| format!("invalid LAST: {}", e))?;
+    if first > last {
+        return Err(format!("FIRST {} is after LAST {}", first, last));
+    }
+    Ok(first..=last)
+}
+
+/// Parses a `--range-prefix` of at most [`range::MAX_PREFIX_LEN`] bytes from hex, with or without
+/// a `0x` prefix.
+pub fn parse_prefix(value: &str) -> Result<Vec<u8>, String> {
+    let bytes =
+        hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| e.to_string())?;
+    if bytes.len() > range::MAX_PREFIX_LEN {
+        return Err(format!(
+            "expected at most {} bytes, got {}",
+            range::MAX_PREFIX_LEN,
+            bytes.len()
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Parses a `--reveal` opening as `SALT:VALUE`, a 32-byte hex salt and a UTF-8 value.
+pub fn parse_reveal(value: &str) -> Result<Reveal, String> {
+    let (salt, value) = value
+        .split_once(':')
+        .ok_or_else(|| "expected SALT:VALUE".to_string())?;
+    let value = value.as_bytes().to_vec();
+    // The value is committed after the phase and result bytes of a single record.
+    if value.len() > u16::MAX as usize - 2 {
+        return Err(format!(
+            "the value is {} bytes, at most {} can be committed",
+            value.len(),
+            u16::MAX - 2
+        ));
+    }
+    Ok(Reveal {
+        salt: parse_hash(salt)?,
+        value,
+    })
+}
+
+/// Prints the public values that follow the root, leaf and validity: the leaf's position and the
+/// optional records.
+pub fn print_extensions(values: &PublicValues) {
+    println!(
+        "Leaf Index: {} of {}",
+        values.leaf_index, values.total_leaves
+    );
+    if values.strict {
+        println!("Strict: true");
+    }
+    if values.field_packed {
+        println!("Field Packed: true");
+    }
+    if values.abi_encoded {
+        println!("ABI Encoded: true");
+    }
+    if values.leaf_hidden {
+        println!("Leaf Hidden: true");
+    }
+    if let Some(hash_bytes) = values.hash_bytes {
+        println!("Hash Bytes: {}", hash_bytes);
+    }
+    if let Some(leaf_matches) = values.leaf_matches {
+        println!("Leaf Matches Expected: {}", leaf_matches);
+    }
+    if let Some(arity) = values.arity {
+        println!("Arity: {}", arity);
+    }
+    if let Some(hash) = values.personalization_hash {
+        println!("Personalization Hash: 0x{}", hex::encode(hash));
+    }
+    if let Some(ordinal) = values.ordinal {
+        println!("Ordinal: {} / {}", ordinal.value, ordinal.scale);
+    }
+    if let Some(root_reversed) = values.root_reversed {
+        println!("Merkle Root (Reversed): 0x{}", hex::encode(root_reversed));
+    }
+    if let Some(index) = &values.index {
+        println!("Index Root: 0x{}", hex::encode(index.root));
+        println!("Index Key: {}", String::from_utf8_lossy(&index.key));
+        println!("Index Position: {}", index.position);
+        println!("Index Lookup Valid: {}", index.valid);
+    }
+    if let Some(append) = values.append {
+        println!("Append Valid: {}", append.valid);
+        println!("Leaves Appended: {}", append.count);
+        println!("New Root: 0x{}", hex::encode(append.new_root));
+    }
+    if let Some(sparse) = values.sparse {
+        println!("Sparse Key: 0x{}", hex::encode(sparse.key));
+        println!("Sparse Value: 0x{}", hex::encode(sparse.value));
+        println!("Sparse Member: {}", sparse.is_member);
+    }
+    if let Some(mmr) = values.mmr {
+        println!("MMR Size: {}", mmr.mmr_size);
+        println!("MMR Position: {}", mmr.position);
+    }
+    if let Some(trie) = &values.trie {
+        println!("Trie Key: 0x{}", hex::encode(&trie.key));
+        println!("Trie Value: 0x{}", hex::encode(&trie.value));
+    }
+    if let Some(updates) = values.updates {
+        println!("Update Status: {:?}", updates.status);
+        println!("Updates Applied: {}", updates.count);
+        println!("Final Root: 0x{}", hex::encode(updates.final_root));
+    }
+    if let Some(signer) = values.authorization {
+        println!("Signer Address: 0x{}", hex::encode(signer.address));
+        println!("Signature Valid: {}", signer.signature_valid);
+        println!("Authorized: {}", signer.authorized);
+    }
+    if let Some(stats) = values.proof_stats {
+        println!("Leaves Proven: {}", stats.leaves);
+        println!("Distinct Siblings: {}", stats.distinct_siblings);
+    }
+    if let Some(neighbors) = values.neighbors {
+        if let Some(prev) = neighbors.prev {
+            println!("Previous Leaf: 0x{}", hex::encode(prev));
+        }
+        if let Some(next) = neighbors.next {
+            println!("Next Leaf: 0x{}", hex::encode(next));
+        }
+        println!("Ordered: {}", neighbors.ordered);
+    }
+    if let Some(kzg) = values.kzg {
+        println!("KZG Commitment: 0x{}", hex::encode(kzg.commitment));
+        println!("KZG Setup Tau G2: 0x{}", hex::encode(kzg.tau_g2));
+        println!("KZG Evaluation Point: {}", kzg.point);
+        println!("KZG Opening Valid: {}", kzg.valid);
+    }
+    if let Some(blocklist) = values.blocklist {
+        println!("Blocklist Root: 0x{}", hex::encode(blocklist.root));
+        println!("Blocklist Leaves: {}", blocklist.total_leaves);
+        println!("Blocklist Excluded: {}", blocklist.excluded);
+    }
+    if let Some(absence) = values.absence {
+        println!("Absence Target: 0x{}", hex::encode(absence.target));
+        println!("Target Absent: {}", absence.absent);
+    }
+    if let Some(erasure) = values.erasure {
+        println!(
+            "Erasure Coding: {} of {} chunks of {} bytes",
+            erasure.data_chunks, erasure.total_chunks, erasure.chunk_size
+        );
+        println!("Erasure Sample Index: {}", erasure.index);
+        println!("Erasure Sampled: {}", erasure.sampled);
+    }
+    if let Some(count) = values.leaf_count {
+        println!("Leaf Count: {}", count.total_leaves);
+        println!("Leaf Count Verified: {}", count.verified);
+    }
+    if let Some(batch) = &values.batch {
+        println!("Batch Leaves: {}", batch.len());
+        for entry in batch {
+            println!("  {}: 0x{}", entry.index, hex::encode(entry.leaf));
+        }
+    }
+    if let Some(aggregate) = &values.aggregate {
+        println!(
+            "Aggregated Proofs Verified: {} / {}",
+            aggregate.verified, aggregate.total
+        );
+        println!(
+            "Aggregated Entries Hash: 0x{}",
+            hex::encode(aggregate.entries_hash)
+        );
+        for index in &aggregate.failed {
+            println!("  Failed Leaf Index: {}", index);
+        }
+    }
+    if let Some(imt) = values.imt {
+        println!("IMT Query: 0x{}", hex::encode(imt.value));
+        println!("IMT Non-Member: {}", imt.non_member);
+    }
+    if let Some(range) = &values.range {
+        println!("Range: {}..={}", range.first, range.last);
+        println!("Range Prefix: 0x{}", hex::encode(&range.prefix));
+        println!("Range Count: {}", range.count);
+        println!("Range Verified: {}", range.verified);
+    }
+    if let Some(reveal) = &values.reveal {
+        println!("Reveal Phase: {:?}", reveal.phase);
+        if reveal.phase == RevealPhase::Reveal {
+            println!("Revealed Value: {}", String::from_utf8_lossy(&reveal.value));
+            println!("Reveal Matches: {}", reveal.matches);
+        }
+    }
+    if let Some(diagnostics) = &values.diagnostics {
+        let codes: Vec<String> = diagnostics
+            .iter()
+            .map(|&diagnostic| format!("{} ({:?})", diagnostic as u8, diagnostic))
+            .collect();
+        println!("Diagnostics: {}", codes.join(", "));
+    }
+    if let Some(checkpoint) = values.checkpoint {
+        println!(
+            "Checkpoint Root: 0x{}",
+            hex::encode(checkpoint.checkpoint_root)
+        );
+        println!(
+            "Checkpoint Index: {} / {}",
+            checkpoint.index, checkpoint.count
+        );
+        println!("Checkpoint Valid: {}", checkpoint.valid);
+    }
+    if let Some(transform) = values.transform {
+        println!(
+            "Leaf Transform Spec Hash: 0x{}",
+            hex::encode(transform.spec_hash)
+        );
+        println!("Leaf Derivation Valid: {}", transform.derived);
+    }
+    if let Some(preimage) = values.preimage {
+        println!("Preimage Hash: 0x{}", hex::encode(preimage.data_hash));
+        println!("Preimage Length: {} bytes", preimage.length);
+        println!("Preimage Hashes To Leaf: {}", preimage.hashes_to_leaf);
+    }
+    if let Some(bloom) = values.bloom {
+        println!("Bloom Filter Hash: 0x{}", hex::encode(bloom.filter_hash));
+        println!("Bloom Filter: {} bits, {} hashes", bloom.bits, bloom.hashes);
+        println!("Bloom Pre-Check Passed: {}", bloom.passed);
+    }
+    if let Some(pair) = values.pair {
+        println!("Pair Indices: {} -> {}", pair.index_a, pair.index_b);
+        println!("Pair Leaf: 0x{}", hex::encode(pair.leaf_b));
+        println!("Pair Status: {:?}", pair.status);
+    }
+    if let Some(cap) = values.cap {
+        println!("Cap Level: {}", cap.cap_level);
+        println!("Cap Position: {}", cap.position);
+        println!("Cap Node: 0x{}", hex::encode(cap.node));
+    }
+    if let Some(promotions) = values.promotions {
+        let levels: Vec<String> = (0..promotions.depth)
+            .filter(|level| (promotions.promoted >> level) & 1 == 1)
+            .map(|level| level.to_string())
+            .collect();
+        println!("Tree Depth: {}", promotions.depth);
+        println!("Promoted Levels: [{}]", levels.join(", "));
+    }
+    if let Some(vdf) = values.vdf {
+        println!("VDF Challenge: 0x{}", hex::encode(vdf.challenge));
+        println!("VDF Iterations: {}", vdf.iterations);
+        println!("VDF Output: 0x{}", hex::encode(vdf.output));
+        println!("VDF Valid: {}", vdf.valid);
+    }
+    if let Some(bls) = values.bls {
+        println!("BLS Public Key: 0x{}", hex::encode(bls.public_key));
+        println!("BLS Signed: {:?}", bls.signed);
+        println!("BLS Signature Valid: {}", bls.valid);
+    }
+    if let Some(forest) = values.forest {
+        println!("Forest Global Index: {}", forest.global_index);
+        println!("Forest Tree: {}", forest.tree);
+        println!("Forest Status: {:?}", forest.status);
+        println!("Forest Sizes Hash: 0x{}", hex::encode(forest.sizes_hash));
+    }
+    if let Some(canonicalization) = values.canonicalization {
+        println!("Canonicalization: {:?}", canonicalization);
+    }
+    if let Some(committed) = values.elf_hash {
+        println!("ELF Hash: 0x{}", hex::encode(committed));
+        println!("ELF Hash Matches Embedded ELF: {}", committed == elf_hash());
+    }
+    if let Some(eip712_digest) = values.eip712_digest {
+        println!("EIP-712 Digest: 0x{}", hex::encode(eip712_digest));
+    }
+    for (label, result) in values.results.iter().flatten() {
+        println!("Result {}: {}", String::from_utf8_lossy(label), result);
+    }
+}
+
+/// Returns the public values layout as JSON, generated from [`goldinals_lib::layout`].
+pub fn layout_schema() -> serde_json::Value {
+    use goldinals_lib::layout::{
+        ABI_STRUCT, CORE_FIELDS, FIELD_PACKED_WORDS, LAYOUT_VERSION, RECORDS, RECORD_FRAMING,
+    };
+
+    serde_json::json!({
+        "version": LAYOUT_VERSION,
+        "core_length": CORE_LEN,
+        "strict_length": STRICT_LEN,
+        "field_packed": FIELD_PACKED_WORDS,
+        "abi_struct": ABI_STRUCT,
+        "core": CORE_FIELDS,
+        "record_framing": RECORD_FRAMING,
+        "records": RECORDS,
+    })
+}
+
+/// Prints metadata about the keys returned by `client.setup`.
+pub fn print_key_info(pk: &SP1ProvingKey, vk: &SP1VerifyingKey) {
+    let pk_len = bincode::serialize(pk)
+        .expect("failed to serialize proving key")
+        .len();
+    let vk_len = bincode::serialize(vk)
+        .expect("failed to serialize verifying key")
+        .len();
+
+    println!("Verification Key Hash: {}", vk.bytes32());
+    println!("Verification Key Size: {} bytes", vk_len);
+    println!("Proving Key Size: {} bytes", pk_len);
+    println!("ELF Size: {} bytes", pk.elf.len());
+    println!("Start PC: {}", vk.vk.pc_start);
+    println!("Preprocessed Traces: {}", pk.pk.traces.len());
+    println!("Chips: {}", vk.vk.chip_information.len());
+    for (name, _, dimensions) in &vk.vk.chip_information {
+        println!("  {}: {}x{}", name, dimensions.width, dimensions.height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goldinals_lib::public_values::Diagnostic;
+
+    #[test]
+    fn current_header_is_accepted() {
+        let mut stdin = SP1Stdin::new();
+        write_header(&mut stdin);
+        assert_eq!(
+            check_header(&stdin, format::SUPPORTED_VERSIONS),
+            Ok(format::FORMAT_VERSION)
+        );
+    }
+
+    #[test]
+    fn v1_reader_rejects_v2_payload() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write_slice(&format::header(2));
+        stdin.write(&(Scheme::Sha256 as u8));
+        assert_eq!(
+            check_header(&stdin, &[1]),
+            Err(HeaderError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn headerless_payload_is_rejected() {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&(Scheme::Sha256 as u8));
+        assert_eq!(
+            check_header(&stdin, format::SUPPORTED_VERSIONS),
+            Err(HeaderError::Missing)
+        );
+    }
+
+    /// Writes a `verify_tree` input for `scheme` with a one-leaf tree and an empty proof, with a
+    /// preimage so that the program would hash a derived leaf with the parameters.
+    fn tree_input(
+        scheme: Scheme,
+        hash_bytes: Option<u8>,
+        personalization: Option<&[u8]>,
+    ) -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        write_header(&mut stdin);
+        stdin.write(&(scheme as u8));
+        if let Some(n) = hash_bytes {
+            stdin.write(&n);
+        }
+        if let Some(personalization) = personalization {
+            stdin.write(&personalization.to_vec());
+        }
+        stdin.write(&[0u8; 32]);
+        stdin.write(&[0u8; 32]);
+        stdin.write(&Vec::<u8>::new());
+        stdin.write(&0usize);
+        stdin.write(&1usize);
+        stdin.write(&Options {
+            preimage: Some(b"leaf".to_vec()),
+            ..Options::default()
+        });
+        stdin
+    }
+
+    /// Executes the program and decodes its public values.
+    fn execute(stdin: SP1Stdin) -> PublicValues {
+        let (output, _) = ProverClient::new()
+            .execute(MERKLE_ELF, stdin)
+            .run()
+            .expect("execution failed");
+        decode_public_values(output.as_slice()).unwrap()
+    }
+
+    #[test]
+    #[ignore = "executes the program, which needs an up-to-date ELF"]
+    fn oversized_hash_bytes_are_reported() {
+        let values = execute(tree_input(Scheme::TruncatedSha256, Some(33), None));
+        assert!(!values.is_valid);
+        assert_eq!(
+            values.diagnostics,
+            Some(vec![Diagnostic::InvalidParameters])
+        );
+    }
+
+    #[test]
+    #[ignore = "executes the program, which needs an up-to-date ELF"]
+    fn oversized_personalization_is_reported() {
+        let values = execute(tree_input(Scheme::Blake2b, None, Some(&[0x61; 17])));
+        assert!(!values.is_valid);
+        assert_eq!(
+            values.diagnostics,
+            Some(vec![Diagnostic::InvalidParameters])
+        );
+    }
+}
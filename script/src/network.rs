@@ -0,0 +1,131 @@
+//! Selecting the prover with `--prover`, and proving on the SP1 prover network without waiting.
+//!
+//! A Groth16 or PLONK proof on the network can take longer than a terminal session should stay
+//! open, so `--no-wait` only submits the request and prints its id. `--status <id>` prints how the
+//! request is doing and `--fetch <id>` waits for its proof and handles it as if it had just been
+//! proven. The network prover reads its key from `SP1_PRIVATE_KEY` and its RPC from
+//! `PROVER_NETWORK_RPC`, as the SDK does.
+
+use clap::{Args, ValueEnum};
+use sp1_sdk::{
+    block_on, network::proto::network::ProofStatus, NetworkProverV1, ProverClient,
+    SP1ProofWithPublicValues, SP1Stdin,
+};
+
+pub use sp1_sdk::network::proto::network::ProofMode;
+
+use crate::{prover_client, use_mock_prover};
+
+/// The provers `--prover` selects.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum ProverKind {
+    /// Prove locally on the CPU.
+    Cpu,
+    /// Prove locally on a GPU, in builds with the `cuda` feature.
+    Cuda,
+    /// Prove on the SP1 prover network.
+    Network,
+}
+
+/// The prover and network request arguments, shared by both binaries.
+#[derive(Args, Debug)]
+pub struct ProverArgs {
+    /// The prover to prove on [default: the one `SP1_PROVER` selects, or cpu].
+    #[clap(long, value_enum)]
+    pub prover: Option<ProverKind>,
+
+    /// Submit the proof request to the prover network, print its id and exit instead of waiting
+    /// for the proof. Requires `--prover network`.
+    #[clap(long, conflicts_with_all = ["status", "fetch"])]
+    pub no_wait: bool,
+
+    /// Print the status of this prover network request, then exit.
+    #[clap(long, value_name = "REQUEST_ID", conflicts_with = "fetch")]
+    pub status: Option<String>,
+
+    /// Wait for the proof of this prover network request instead of proving, ignoring the tree
+    /// arguments.
+    #[clap(long, value_name = "REQUEST_ID")]
+    pub fetch: Option<String>,
+}
+
+impl ProverArgs {
+    /// Creates the prover client `--prover` selects, or the one from the environment without it.
+    pub fn client(&self) -> Result<ProverClient, String> {
+        client(self.prover)
+    }
+
+    /// Checks that `--no-wait` proves on the network, since only network requests outlive the
+    /// process, and that network requests have a key before anything slow runs.
+    pub fn check(&self) -> Result<(), String> {
+        if self.no_wait && self.prover != Some(ProverKind::Network) {
+            return Err("--no-wait requires --prover network".to_string());
+        }
+        if self.no_wait || self.fetch.is_some() {
+            network_key()?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates the prover client of `kind`, or the one from the environment without one.
+pub fn client(kind: Option<ProverKind>) -> Result<ProverClient, String> {
+    let Some(kind) = kind else {
+        return Ok(prover_client());
+    };
+    if use_mock_prover() {
+        return Err("SP1_USE_MOCK cannot be combined with --prover".to_string());
+    }
+    match kind {
+        ProverKind::Cpu => Ok(ProverClient::local()),
+        #[cfg(feature = "cuda")]
+        ProverKind::Cuda => Ok(ProverClient {
+            prover: Box::new(sp1_sdk::CudaProver::new(sp1_sdk::SP1Prover::new())),
+        }),
+        #[cfg(not(feature = "cuda"))]
+        ProverKind::Cuda => Err("--prover cuda needs a build with `--features cuda`".to_string()),
+        ProverKind::Network => {
+            network_key()?;
+            Ok(ProverClient::network())
+        }
+    }
+}
+
+/// Returns the network key from `SP1_PRIVATE_KEY`, which the SDK would otherwise panic without.
+fn network_key() -> Result<String, String> {
+    std::env::var("SP1_PRIVATE_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| "SP1_PRIVATE_KEY must be set for the prover network".to_string())
+}
+
+/// Creates a client of the prover network.
+fn network_prover() -> Result<NetworkProverV1, String> {
+    Ok(NetworkProverV1::new_from_key(&network_key()?))
+}
+
+/// Submits a request to prove `stdin` on the network and returns its id, without waiting.
+pub fn submit(elf: &[u8], stdin: SP1Stdin, mode: ProofMode) -> Result<String, String> {
+    let prover = network_prover()?;
+    block_on(prover.request_proof(elf, stdin, mode))
+        .map_err(|e| format!("failed to submit the proof request: {}", e))
+}
+
+/// Prints the status of the request `id` and returns whether its proof is ready.
+pub fn print_status(id: &str) -> Result<bool, String> {
+    let prover = network_prover()?;
+    let (status, _) = block_on(prover.get_proof_status(id))
+        .map_err(|e| format!("failed to get the status of {}: {}", id, e))?;
+    println!("Proof Request: {}", id);
+    println!("Status: {}", status.status().as_str_name());
+    if status.status() == ProofStatus::ProofUnclaimed {
+        println!("Unclaim Reason: {}", status.unclaim_description());
+    }
+    Ok(status.status() == ProofStatus::ProofFulfilled)
+}
+
+/// Waits for the proof of the request `id`.
+pub fn fetch(id: &str) -> Result<SP1ProofWithPublicValues, String> {
+    let prover = network_prover()?;
+    block_on(prover.wait_proof(id, None)).map_err(|e| format!("failed to fetch {}: {}", id, e))
+}
@@ -0,0 +1,60 @@
+//! Protobuf datasets for `--leaves-proto`.
+//!
+//! A dataset is a stream of length-delimited messages, each a varint byte length followed by the
+//! serialized message, as written by `Message::encode_length_delimited` or Java's
+//! `writeDelimitedTo`. Each message's bytes are the data of one leaf, exactly as serialized.
+//!
+//! Protobuf serialization is not canonical: field order, packed versus unpacked repeated fields,
+//! map entry order and unknown fields may all differ between encoders for the same message. Leaves
+//! are only reproducible if every producer serializes identically, e.g. with one library's
+//! deterministic mode or by re-encoding through [`reencode`].
+
+use prost::{
+    encoding::{decode_key, skip_field, DecodeContext},
+    Message,
+};
+
+/// Splits a stream of length-delimited messages into the messages' bytes.
+///
+/// Errors when a length prefix is malformed or runs past the end of the data.
+pub fn split_delimited(mut data: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    let total = data.len();
+    let mut messages = Vec::new();
+    while !data.is_empty() {
+        let offset = total - data.len();
+        let len = prost::decode_length_delimiter(&mut data)
+            .map_err(|e| format!("invalid length prefix at byte {}: {}", offset, e))?;
+        if len > data.len() {
+            return Err(format!(
+                "message {} at byte {} is {} bytes, but only {} remain",
+                messages.len(),
+                offset,
+                len,
+                data.len()
+            ));
+        }
+        let (message, rest) = data.split_at(len);
+        messages.push(message.to_vec());
+        data = rest;
+    }
+    Ok(messages)
+}
+
+/// Checks that `message` is well-formed protobuf wire format, without knowing its type.
+pub fn check_wire_format(mut message: &[u8]) -> Result<(), String> {
+    while !message.is_empty() {
+        let (tag, wire_type) = decode_key(&mut message).map_err(|e| e.to_string())?;
+        skip_field(wire_type, tag, &mut message, DecodeContext::default())
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Decodes `message` as `M` and re-encodes it, so that leaves derived from messages of a generated
+/// type do not depend on the producer's encoder. `prost` writes fields in field-number order and
+/// drops unknown fields; map fields are only ordered if generated as `BTreeMap`.
+pub fn reencode<M: Message + Default>(message: &[u8]) -> Result<Vec<u8>, String> {
+    M::decode(message)
+        .map(|decoded| decoded.encode_to_vec())
+        .map_err(|e| e.to_string())
+}
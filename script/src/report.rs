@@ -0,0 +1,104 @@
+//! Machine-readable run reports for `--report-path` and `--bench`.
+//!
+//! A report records what a run cost: the cycles and syscalls of the execution, the wall-clock time
+//! of each phase, the process's peak memory and the size of what it produced. Phases a run skips
+//! are `null`, so every report has the same fields and a bincode report decodes into the same
+//! struct as a JSON one.
+
+use std::{collections::BTreeMap, path::Path, time::Instant};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::ExecutionReport;
+
+/// The encodings `--report-format` selects.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum ReportFormat {
+    Json,
+    Bincode,
+}
+
+/// The wall-clock time of each phase of a run, in milliseconds.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timings {
+    /// Building the inputs, including the tree.
+    pub build_ms: Option<u128>,
+    pub execute_ms: Option<u128>,
+    /// Setting up the proving and verifying keys.
+    pub setup_ms: Option<u128>,
+    pub prove_ms: Option<u128>,
+    pub verify_ms: Option<u128>,
+}
+
+/// The cost of one run.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    /// The number of synthetic leaves, in `--bench` reports.
+    pub total_leaves: Option<usize>,
+    pub cycles: Option<u64>,
+    /// The number of calls of each syscall the execution made at least once.
+    pub syscall_counts: BTreeMap<String, u64>,
+    pub timings: Timings,
+    /// The process's peak resident memory so far, read from `/proc/self/status` (Linux only).
+    pub peak_memory_bytes: Option<u64>,
+    pub public_values_bytes: Option<usize>,
+    /// The length of the bincode-serialized proof with its public values.
+    pub proof_bytes: Option<usize>,
+}
+
+/// The reports of a `--bench` sweep, one per tree size.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchReport {
+    /// `execute` or `prove`.
+    pub mode: String,
+    pub points: Vec<RunReport>,
+}
+
+impl RunReport {
+    /// Records the cycles, syscalls and public values of an execution.
+    pub fn record_execution(&mut self, report: &ExecutionReport, public_values: &[u8]) {
+        self.cycles = Some(report.total_instruction_count());
+        self.syscall_counts = report
+            .syscall_counts
+            .iter()
+            .filter(|(_, &count)| count > 0)
+            .map(|(syscall, &count)| (format!("{:?}", syscall), count))
+            .collect();
+        self.public_values_bytes = Some(public_values.len());
+    }
+
+    /// Records the peak memory, once the run is over.
+    pub fn finish(&mut self) {
+        self.peak_memory_bytes = peak_memory_bytes();
+    }
+}
+
+/// Runs `f` and returns its result with its duration in milliseconds.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, u128) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed().as_millis())
+}
+
+/// Returns the process's peak resident memory, or `None` off Linux.
+pub fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Writes `report` to `path` in `format`.
+pub fn write(path: &Path, format: ReportFormat, report: &impl Serialize) -> Result<(), String> {
+    let bytes = match format {
+        ReportFormat::Json => serde_json::to_vec_pretty(report).expect("reports serialize"),
+        ReportFormat::Bincode => bincode::serialize(report).expect("reports serialize"),
+    };
+    std::fs::write(path, bytes)
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    println!("Wrote report to {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//! A minimal proving service for `--serve` and the `server` binary, enabled with the `serve`
+//! feature.
+//!
+//! The prover client and keys are set up once at startup and shared by every request. Requests are
+//! queued on a bounded channel and proven one at a time by a dedicated worker thread, so a burst of
+//! requests cannot start more proofs than the machine can handle; when the queue is full the server
+//! answers `503` immediately. Request bodies are [`Witness`] values.
+//!
+//! Every request gets a random id. `POST /prove` waits for the proof unless `?wait=false` is given,
+//! in which case it answers with the id at once and `GET /proof/{id}` returns the proof when it is
+//! ready. Only the last `--keep-results` finished requests are kept for `GET`.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use clap::Args;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, ProverClient, SP1Stdin};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{witness::Witness, MERKLE_ELF};
+
+/// The service arguments, shared by `--serve` and the `server` binary.
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// The number of requests that may wait for the prover before new ones are rejected.
+    #[clap(long, default_value = "16")]
+    pub queue_size: usize,
+
+    /// The number of finished requests whose proofs `GET /proof/{id}` can still return.
+    #[clap(long, default_value = "64")]
+    pub keep_results: usize,
+}
+
+/// The body of a successful `/prove` or `/proof/{id}` response.
+#[derive(Clone, Serialize)]
+pub struct ProveResponse {
+    /// The id of the request.
+    pub id: String,
+    /// The committed public values.
+    pub public_values: String,
+    /// The bincode-serialized `SP1ProofWithPublicValues`, as written by its `save` method.
+    pub proof: String,
+    /// The verification key hash the proof was verified against.
+    pub vkey: String,
+}
+
+/// A queued proof request and, if the request waits for it, the channel its result is sent on.
+struct Job {
+    id: String,
+    stdin: SP1Stdin,
+    respond: Option<oneshot::Sender<Result<ProveResponse, String>>>,
+}
+
+/// Where a request is, as `GET /proof/{id}` reports it.
+#[derive(Clone)]
+enum Status {
+    Queued,
+    Proving,
+    Done(ProveResponse),
+    Failed(String),
+}
+
+/// The status of every request, up to `--keep-results` of them finished.
+struct Requests {
+    statuses: HashMap<String, Status>,
+    /// The finished requests, oldest first.
+    finished: VecDeque<String>,
+    keep_results: usize,
+}
+
+impl Requests {
+    fn finish(&mut self, id: String, status: Status) {
+        self.statuses.insert(id.clone(), status);
+        self.finished.push_back(id);
+        while self.finished.len() > self.keep_results {
+            let oldest = self.finished.pop_front().unwrap();
+            self.statuses.remove(&oldest);
+        }
+    }
+}
+
+/// The state shared by the handlers.
+#[derive(Clone)]
+struct Service {
+    jobs: mpsc::Sender<Job>,
+    requests: Arc<Mutex<Requests>>,
+}
+
+/// Sets up the keys once and serves `/prove` and `/proof/{id}` on `addr` with `client` until the
+/// process is stopped.
+pub fn run(addr: SocketAddr, client: ProverClient, args: &ServeArgs) {
+    let (pk, vk) = client.setup(MERKLE_ELF);
+    let vkey = vk.bytes32();
+
+    let (jobs, mut queue) = mpsc::channel::<Job>(args.queue_size);
+    let requests = Arc::new(Mutex::new(Requests {
+        statuses: HashMap::new(),
+        finished: VecDeque::new(),
+        keep_results: args.keep_results,
+    }));
+    let worker_requests = requests.clone();
+    std::thread::spawn(move || {
+        while let Some(job) = queue.blocking_recv() {
+            worker_requests
+                .lock()
+                .unwrap()
+                .statuses
+                .insert(job.id.clone(), Status::Proving);
+            let result = client
+                .prove(&pk, job.stdin)
+                .run()
+                .map_err(|e| format!("failed to generate proof: {}", e))
+                .and_then(|proof| {
+                    client
+                        .verify(&proof, &vk)
+                        .map_err(|e| format!("failed to verify proof: {}", e))?;
+                    Ok(ProveResponse {
+                        id: job.id.clone(),
+                        public_values: format!("0x{}", hex::encode(proof.public_values.as_slice())),
+                        proof: format!(
+                            "0x{}",
+                            hex::encode(bincode::serialize(&proof).expect("failed to serialize"))
+                        ),
+                        vkey: vkey.clone(),
+                    })
+                });
+            let status = match &result {
+                Ok(response) => Status::Done(response.clone()),
+                Err(e) => Status::Failed(e.clone()),
+            };
+            worker_requests.lock().unwrap().finish(job.id, status);
+            // The client may have disconnected; the proof is then only kept for `GET`.
+            if let Some(respond) = job.respond {
+                let _ = respond.send(result);
+            }
+        }
+    });
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start the async runtime");
+    runtime.block_on(async move {
+        let app = Router::new()
+            .route("/prove", post(prove))
+            .route("/proof/:id", get(proof))
+            .with_state(Service { jobs, requests });
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .unwrap_or_else(|e| panic!("failed to bind {}: {}", addr, e));
+        println!("Listening on http://{}", addr);
+        axum::serve(listener, app).await.expect("server failed");
+    });
+}
+
+/// The query of a `/prove` request.
+#[derive(Deserialize)]
+struct ProveQuery {
+    /// Whether to wait for the proof rather than answer with the request id at once.
+    #[serde(default = "default_wait")]
+    wait: bool,
+}
+
+fn default_wait() -> bool {
+    true
+}
+
+async fn prove(
+    State(service): State<Service>,
+    Query(query): Query<ProveQuery>,
+    Json(witness): Json<Witness>,
+) -> Response {
+    let stdin = match witness.to_stdin() {
+        Ok(stdin) => stdin,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+
+    let id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+    let (respond, result) = match query.wait {
+        true => {
+            let (respond, result) = oneshot::channel();
+            (Some(respond), Some(result))
+        }
+        false => (None, None),
+    };
+    // Register the request before the worker can pick it up and mark it as proving.
+    service
+        .requests
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(id.clone(), Status::Queued);
+    let job = Job {
+        id: id.clone(),
+        stdin,
+        respond,
+    };
+    if service.jobs.try_send(job).is_err() {
+        service.requests.lock().unwrap().statuses.remove(&id);
+        return error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "the proving queue is full".to_string(),
+        );
+    }
+
+    let Some(result) = result else {
+        return pending(&id, "queued");
+    };
+    match result.await {
+        Ok(Ok(response)) => Json(response).into_response(),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+        Err(_) => error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "the prover stopped".to_string(),
+        ),
+    }
+}
+
+async fn proof(State(service): State<Service>, Path(id): Path<String>) -> Response {
+    let status = service.requests.lock().unwrap().statuses.get(&id).cloned();
+    match status {
+        Some(Status::Queued) => pending(&id, "queued"),
+        Some(Status::Proving) => pending(&id, "proving"),
+        Some(Status::Done(response)) => Json(response).into_response(),
+        Some(Status::Failed(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+        None => error(
+            StatusCode::NOT_FOUND,
+            format!("no proof request {}, or its result was dropped", id),
+        ),
+    }
+}
+
+/// Answers `202` for a request whose proof is not ready yet.
+fn pending(id: &str, status: &str) -> Response {
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({ "id": id, "status": status })),
+    )
+        .into_response()
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
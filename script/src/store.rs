@@ -0,0 +1,194 @@
+//! An on-disk store of every level of a synthetic binary tree, for `--tree-path`.
+//!
+//! Building a tree of tens of millions of leaves dominates a run, so the store is built once and
+//! later runs read only the nodes on a leaf's path: a proof costs one seek per level instead of
+//! rehashing every leaf. The file is
+//!
+//! ```text
+//! MAGIC (4) || VERSION (1) || scheme (1) || hash_bytes (1) || total_leaves (8, big-endian)
+//!           || level 0 || level 1 || ... || root
+//! ```
+//!
+//! where level 0 holds the leaves and every level holds its 32-byte nodes left to right, shaped as
+//! in [`goldinals_lib::tree`]. The header identifies the tree only by its scheme, node length and
+//! size; leaf derivation options such as `--leaf-transform` are not recorded, so the caller checks
+//! the stored leaf it proves against the leaf it expects.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use goldinals_lib::tree;
+use rayon::prelude::*;
+
+use crate::{LeafFn, ParentFn, StreamedProof};
+
+/// The first bytes of a tree store.
+pub const MAGIC: [u8; 4] = *b"GLDT";
+
+/// The version of the layout written by this build.
+pub const VERSION: u8 = 1;
+
+/// The length of the header before level 0.
+const HEADER_LEN: u64 = 15;
+
+/// What a tree store holds, checked when it is opened.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StoreHeader {
+    pub scheme: u8,
+    /// The node length of truncated trees, 32 otherwise.
+    pub hash_bytes: u8,
+    pub total_leaves: u64,
+}
+
+impl StoreHeader {
+    fn to_bytes(self) -> [u8; HEADER_LEN as usize] {
+        let mut bytes = [0; HEADER_LEN as usize];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = VERSION;
+        bytes[5] = self.scheme;
+        bytes[6] = self.hash_bytes;
+        bytes[7..].copy_from_slice(&self.total_leaves.to_be_bytes());
+        bytes
+    }
+}
+
+/// A tree store opened for reading proofs.
+pub struct TreeStore {
+    file: File,
+    total_leaves: usize,
+}
+
+impl TreeStore {
+    /// Opens the store at `path`, or builds it there first if it does not exist.
+    pub fn open_or_build(
+        path: &Path,
+        header: StoreHeader,
+        leaf: LeafFn,
+        parent: ParentFn,
+    ) -> Result<Self, String> {
+        if !path.exists() {
+            build(path, header, leaf, parent)?;
+        }
+        Self::open(path, header)
+    }
+
+    /// Opens the store at `path`, failing unless it holds the tree `expected` describes.
+    pub fn open(path: &Path, expected: StoreHeader) -> Result<Self, String> {
+        let mut file =
+            File::open(path).map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+        let mut bytes = [0; HEADER_LEN as usize];
+        file.read_exact(&mut bytes)
+            .map_err(|_| format!("{} is not a tree store", path.display()))?;
+        if bytes[..4] != MAGIC {
+            return Err(format!("{} is not a tree store", path.display()));
+        }
+        if bytes[4] != VERSION {
+            return Err(format!(
+                "{} is a version {} tree store, this build reads version {}",
+                path.display(),
+                bytes[4],
+                VERSION
+            ));
+        }
+        if bytes != expected.to_bytes() {
+            return Err(format!(
+                "{} stores a different tree (scheme {}, {}-byte nodes, {} leaves); delete it to \
+                 rebuild",
+                path.display(),
+                bytes[5],
+                bytes[6],
+                u64::from_be_bytes(bytes[7..].try_into().unwrap())
+            ));
+        }
+        Ok(Self {
+            file,
+            total_leaves: expected.total_leaves as usize,
+        })
+    }
+
+    /// Reads the root and the leaf at `index` with its sibling path, bottom-up as in
+    /// [`tree::proof_path`].
+    pub fn root_and_proof(&mut self, index: usize) -> Result<StreamedProof, String> {
+        if index >= self.total_leaves {
+            return Err(format!(
+                "leaf {} is outside the tree of {}",
+                index, self.total_leaves
+            ));
+        }
+        let leaf = self.node(0, index)?;
+        let mut path = Vec::new();
+        let mut width = self.total_leaves;
+        for height in 0..tree::height(self.total_leaves) {
+            let sibling = (index >> height) ^ 1;
+            if sibling < width {
+                path.push(self.node(height, sibling)?);
+            }
+            width = width.div_ceil(2);
+        }
+        let root = self.node(tree::height(self.total_leaves), 0)?;
+        Ok(StreamedProof { root, leaf, path })
+    }
+
+    /// Reads the node at `position` of the level `height` levels above the leaves.
+    fn node(&mut self, height: usize, position: usize) -> Result<[u8; 32], String> {
+        let offset = (level_start(self.total_leaves, height) + position) as u64 * 32;
+        let mut node = [0; 32];
+        self.file
+            .seek(SeekFrom::Start(HEADER_LEN + offset))
+            .and_then(|_| self.file.read_exact(&mut node))
+            .map_err(|e| format!("failed to read the tree store: {}", e))?;
+        Ok(node)
+    }
+}
+
+/// Returns the number of nodes below the level `height` levels above the leaves.
+fn level_start(total_leaves: usize, height: usize) -> usize {
+    let mut width = total_leaves;
+    let mut start = 0;
+    for _ in 0..height {
+        start += width;
+        width = width.div_ceil(2);
+    }
+    start
+}
+
+/// Hashes every level of the tree in parallel and writes them to `path`.
+///
+/// Only two levels are held in memory at a time. The store is written next to `path` and renamed
+/// into place once complete, so an interrupted build is never mistaken for a store.
+fn build(path: &Path, header: StoreHeader, leaf: LeafFn, parent: ParentFn) -> Result<(), String> {
+    let partial = path.with_extension("partial");
+    let write_error = |e: std::io::Error| format!("failed to write {}: {}", partial.display(), e);
+    let mut out = BufWriter::new(File::create(&partial).map_err(write_error)?);
+    out.write_all(&header.to_bytes()).map_err(write_error)?;
+
+    let mut level = (0..header.total_leaves as usize)
+        .into_par_iter()
+        .map(leaf)
+        .collect::<Result<Vec<_>, _>>()?;
+    loop {
+        for node in &level {
+            out.write_all(node).map_err(write_error)?;
+        }
+        if level.len() <= 1 {
+            break;
+        }
+        level = level
+            .par_chunks(2)
+            .map(|pair| match pair {
+                [left, right] => parent(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    out.into_inner()
+        .map_err(|e| write_error(e.into_error()))?
+        .sync_all()
+        .map_err(write_error)?;
+    std::fs::rename(&partial, path)
+        .map_err(|e| format!("failed to move the tree store to {}: {}", path.display(), e))
+}
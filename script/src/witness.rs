@@ -0,0 +1,247 @@
+//! Self-contained tree witnesses in JSON, as accepted by `--serve` and `--witnesses-file`.
+
+use goldinals_lib::{
+    blake2b,
+    inclusion::{self, Parameters},
+    kary,
+    public_values::Diagnostic,
+    truncated, Options, Scheme,
+};
+use serde::Deserialize;
+use sp1_sdk::SP1Stdin;
+
+use crate::{parse_hash, write_header};
+
+/// A tree witness with every byte string hex-encoded.
+#[derive(Deserialize)]
+pub struct Witness {
+    /// `sha256`, `hybrid`, `truncated_sha256`, `kary`, `keccak256`, `blake2b` or `blake3`
+    /// [default: `sha256`].
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+    /// The node length, required for `truncated_sha256`.
+    pub hash_bytes: Option<u8>,
+    /// The number of children per node, required for `kary`.
+    pub arity: Option<u8>,
+    /// The BLAKE2b personalization, at most 16 bytes, for `blake2b` [default: empty].
+    pub personalization: Option<String>,
+    pub root: String,
+    pub leaf: String,
+    /// The proof bytes in the scheme's format, e.g. `rs_merkle::MerkleProof::to_bytes`.
+    pub proof: String,
+    pub leaf_index: usize,
+    pub total_leaves: usize,
+}
+
+/// The hex fields of a [`Witness`], decoded.
+struct Decoded {
+    root: [u8; 32],
+    leaf: [u8; 32],
+    proof: Vec<u8>,
+}
+
+fn default_scheme() -> String {
+    "sha256".to_string()
+}
+
+impl Witness {
+    /// Writes the witness as guest inputs, with no optional features enabled.
+    pub fn to_stdin(&self) -> Result<SP1Stdin, String> {
+        self.to_stdin_with(&Options::default())
+    }
+
+    /// Writes the witness as guest inputs with `options`, which must not need the whole tree.
+    pub fn to_stdin_with(&self, options: &Options) -> Result<SP1Stdin, String> {
+        let scheme = self.parse_scheme()?;
+        let Decoded { root, leaf, proof } = self.decode()?;
+
+        let mut stdin = SP1Stdin::new();
+        write_header(&mut stdin);
+        stdin.write(&(scheme as u8));
+        if let Some(n) = self.hash_bytes {
+            stdin.write(&n);
+        }
+        if let Some(arity) = self.arity {
+            stdin.write(&arity);
+        }
+        if scheme == Scheme::Blake2b {
+            stdin.write(&self.personalization().to_vec());
+        }
+        stdin.write(&root);
+        stdin.write(&leaf);
+        stdin.write(&proof);
+        stdin.write(&self.leaf_index);
+        stdin.write(&self.total_leaves);
+        stdin.write(options);
+        Ok(stdin)
+    }
+
+    /// Verifies the witness under its own scheme as the program does, returning the `is_valid`
+    /// it would commit and the diagnostic it would record, if any.
+    ///
+    /// A proof that cannot be parsed or an index outside the tree gives `is_valid = false` with a
+    /// diagnostic rather than an error; errors are reserved for witnesses the host cannot encode.
+    pub fn expected_validity(&self) -> Result<(bool, Option<Diagnostic>), String> {
+        let scheme = self.parse_scheme()?;
+        let decoded = self.decode()?;
+        Ok(
+            match verify_as(
+                &decoded,
+                scheme,
+                self.parameters(self.hash_bytes, self.arity),
+                self.leaf_index,
+                self.total_leaves,
+            ) {
+                Ok(is_valid) => (is_valid, None),
+                Err(diagnostic) => (false, Some(diagnostic)),
+            },
+        )
+    }
+
+    fn personalization(&self) -> &[u8] {
+        self.personalization.as_deref().map_or(&[], str::as_bytes)
+    }
+
+    fn parameters(&self, hash_bytes: Option<u8>, arity: Option<u8>) -> Parameters {
+        Parameters {
+            hash_bytes,
+            arity,
+            personalization: self.personalization(),
+        }
+    }
+
+    fn parse_scheme(&self) -> Result<Scheme, String> {
+        if self.personalization.is_some() && self.scheme != "blake2b" {
+            return Err("personalization is only supported with blake2b".to_string());
+        }
+        Ok(match (self.scheme.as_str(), self.hash_bytes, self.arity) {
+            ("sha256", None, None) => Scheme::Sha256,
+            ("hybrid", None, None) => Scheme::Hybrid,
+            ("keccak256", None, None) => Scheme::Keccak256,
+            ("blake2b", None, None)
+                if blake2b::is_valid_personalization(self.personalization()) =>
+            {
+                Scheme::Blake2b
+            }
+            ("blake3", None, None) => Scheme::Blake3,
+            ("truncated_sha256", Some(n), None) if goldinals_lib::truncated::is_valid_len(n) => {
+                Scheme::TruncatedSha256
+            }
+            ("kary", None, Some(arity)) if goldinals_lib::kary::is_valid_arity(arity) => {
+                Scheme::Kary
+            }
+            _ => return Err("invalid combination of scheme, hash_bytes and arity".to_string()),
+        })
+    }
+
+    /// Verifies the witness under every supported scheme configuration, ignoring its `scheme`,
+    /// `hash_bytes` and `arity`, and returns those it verifies under, e.g. `kary (arity 4)`.
+    ///
+    /// That is sha256, hybrid, keccak256, blake2b under the witness's own personalization, blake3,
+    /// truncated_sha256 for each node length from 1 to 32 and kary for each arity from 2 to 16, so
+    /// at most 52 attempts. Patricia proofs have their own format and are not tried.
+    pub fn detect_schemes(&self) -> Result<Vec<String>, String> {
+        let decoded = self.decode()?;
+        let (index, total) = (self.leaf_index, self.total_leaves);
+        let personalization = self.personalization();
+        let verifies = |scheme, hash_bytes, arity| {
+            verify_as(
+                &decoded,
+                scheme,
+                self.parameters(hash_bytes, arity),
+                index,
+                total,
+            ) == Ok(true)
+        };
+
+        let mut matches = Vec::new();
+        if verifies(Scheme::Sha256, None, None) {
+            matches.push("sha256".to_string());
+        }
+        if verifies(Scheme::Hybrid, None, None) {
+            matches.push("hybrid".to_string());
+        }
+        if verifies(Scheme::Keccak256, None, None) {
+            matches.push("keccak256".to_string());
+        }
+        if blake2b::is_valid_personalization(personalization)
+            && verifies(Scheme::Blake2b, None, None)
+        {
+            matches.push("blake2b".to_string());
+        }
+        if verifies(Scheme::Blake3, None, None) {
+            matches.push("blake3".to_string());
+        }
+        for n in (1..=32).filter(|&n| truncated::is_valid_len(n)) {
+            if verifies(Scheme::TruncatedSha256, Some(n), None) {
+                matches.push(format!("truncated_sha256 (hash_bytes {})", n));
+            }
+        }
+        for arity in (2..=16).filter(|&arity| kary::is_valid_arity(arity)) {
+            if verifies(Scheme::Kary, None, Some(arity)) {
+                matches.push(format!("kary (arity {})", arity));
+            }
+        }
+        Ok(matches)
+    }
+
+    fn decode(&self) -> Result<Decoded, String> {
+        Ok(Decoded {
+            root: parse_hash(&self.root).map_err(|e| format!("root: {}", e))?,
+            leaf: parse_hash(&self.leaf).map_err(|e| format!("leaf: {}", e))?,
+            proof: hex::decode(self.proof.strip_prefix("0x").unwrap_or(&self.proof))
+                .map_err(|e| format!("proof: {}", e))?,
+        })
+    }
+}
+
+/// Verifies a decoded witness with [`inclusion::verify`], as the program's `verify_tree` does.
+fn verify_as(
+    decoded: &Decoded,
+    scheme: Scheme,
+    parameters: Parameters,
+    index: usize,
+    total_leaves: usize,
+) -> Result<bool, Diagnostic> {
+    let Decoded { root, leaf, proof } = decoded;
+    inclusion::verify(scheme, parameters, *root, *leaf, proof, index, total_leaves)
+}
+
+#[cfg(test)]
+mod tests {
+    use goldinals_lib::sha256::Sha256Hasher;
+    use rs_merkle::{Hasher, MerkleTree};
+
+    use super::*;
+
+    /// A valid sha256 witness for leaf 2 of a five-leaf tree.
+    fn witness() -> Witness {
+        let leaves: Vec<[u8; 32]> = (0u8..5).map(|i| Sha256Hasher::hash(&[i])).collect();
+        let tree = MerkleTree::<Sha256Hasher>::from_leaves(&leaves);
+        Witness {
+            scheme: default_scheme(),
+            hash_bytes: None,
+            arity: None,
+            personalization: None,
+            root: hex::encode(tree.root().unwrap()),
+            leaf: hex::encode(leaves[2]),
+            proof: hex::encode(tree.proof(&[2]).to_bytes()),
+            leaf_index: 2,
+            total_leaves: leaves.len(),
+        }
+    }
+
+    #[test]
+    fn valid_witness_verifies() {
+        assert_eq!(witness().expected_validity(), Ok((true, None)));
+    }
+
+    #[test]
+    fn out_of_range_index_matches_no_scheme() {
+        let witness = Witness {
+            leaf_index: 5,
+            ..witness()
+        };
+        assert_eq!(witness.detect_schemes(), Ok(Vec::new()));
+    }
+}